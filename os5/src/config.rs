@@ -1,14 +1,38 @@
 //! Constants used in rCore
 
 pub const USER_STACK_SIZE: usize = 4096 * 4;
+/// Largest user stack an app may ask for in its `.stack_size` section
+pub const USER_STACK_SIZE_MAX: usize = 4096 * 256;
 pub const KERNEL_STACK_SIZE: usize = 4096 * 20;
+/// Kernel stacks of exited tasks kept mapped for reuse by the next task with the same pid
+pub const KERNEL_STACK_POOL_SIZE: usize = 8;
 pub const KERNEL_HEAP_SIZE: usize = 0x20_0000;
 pub const MEMORY_END: usize = 0x88000000;
+/// Pages of swap space, taken from the top of memory
+pub const SWAP_PAGES: usize = 256;
+/// Start of the swap space, the frame allocator stops here
+pub const SWAP_START: usize = MEMORY_END - SWAP_PAGES * PAGE_SIZE;
 pub const PAGE_SIZE: usize = 0x1000;
 pub const PAGE_SIZE_BITS: usize = 0xc;
+/// Size of a megapage, mapped by a single level-1 PTE
+pub const HUGE_PAGE_SIZE: usize = 0x20_0000;
 pub const MAX_SYSCALL_NUM: usize = 500;
+pub const MAX_REGION_NAME_LEN: usize = 32;
+pub const MAX_PATH_LEN: usize = 256;
+pub const MAX_TASK_NAME_LEN: usize = 32;
+pub const CONSOLE_LINE_BUFFER_SIZE: usize = 256;
+pub const MAX_PROC_DEPTH: usize = 16;
+/// Unreaped zombies past which the timer tick warns about a missing waitpid
+pub const ZOMBIE_WARN_THRESHOLD: usize = 64;
+/// At most one zombie warning is printed per this many ms
+pub const ZOMBIE_WARN_INTERVAL_MS: usize = 1000;
 
 pub const TRAMPOLINE: usize = usize::MAX - PAGE_SIZE + 1;
 pub const TRAP_CONTEXT: usize = TRAMPOLINE - PAGE_SIZE;
+/// Read-only page shared with every user space, holding the time
+pub const VDSO: usize = TRAP_CONTEXT - PAGE_SIZE;
+/// mmap areas end at or below this, the top of the lower half of the Sv39 space;
+/// higher addresses up to the vDSO page are not canonical
+pub const USER_MMAP_END: usize = 1 << 38;
 pub const CLOCK_FREQ: usize = 12500000;
 pub const BIG_STRIDE:u32 = u32::MAX;
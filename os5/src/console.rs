@@ -1,6 +1,7 @@
 //! SBI console driver, for text output
 
 use crate::sbi::console_putchar;
+use alloc::string::String;
 use core::fmt::{self, Write};
 
 struct Stdout;
@@ -18,6 +19,13 @@ pub fn print(args: fmt::Arguments) {
     Stdout.write_fmt(args).unwrap();
 }
 
+/// Print stdout bytes of a task, with no task borrowed
+pub fn print_stdout(bytes: &[u8]) {
+    if !bytes.is_empty() {
+        print(format_args!("{}", String::from_utf8_lossy(bytes)));
+    }
+}
+
 #[macro_export]
 /// print string macro
 macro_rules! print {
@@ -2,8 +2,13 @@
 
 use crate::console::ANSICON;
 use crate::sbi::shutdown;
+use crate::task::processor::dump_current_task;
 
 use core::panic::PanicInfo;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Set by the first panic, so a panic while dumping the task does not dump again
+static PANICKING: AtomicBool = AtomicBool::new(false);
 
 #[panic_handler]
 /// panic handler
@@ -25,5 +30,8 @@ fn panic(info: &PanicInfo) -> ! {
             info.message().unwrap()
         );
     }
+    if !PANICKING.swap(true, Ordering::Relaxed) {
+        dump_current_task();
+    }
     shutdown()
 }
@@ -61,6 +61,19 @@ pub fn get_app_data_by_name(name: &str) -> Option<&'static [u8]> {
         .map(get_app_data)
 }
 
+/// Size of the largest embedded app, an upper bound on any image worth loading
+pub fn max_app_size() -> usize {
+    (0..get_num_app())
+        .map(|i| get_app_data(i).len())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Names of all embedded apps, in load order
+pub fn app_names() -> &'static [&'static str] {
+    APP_NAMES.as_slice()
+}
+
 /// Print all of app names during kernel initialization
 pub fn list_apps() {
     println!("/**** APPS ****");
@@ -43,3 +43,22 @@ pub fn init() {
         _ => LevelFilter::Off,
     });
 }
+
+/// Change the most verbose level emitted at runtime, 0 (off) to 5 (trace).
+///
+/// The `log` macros compare against this on every call, so the change takes
+/// effect at once. Return the previous level, or `None` if `level` is invalid.
+pub fn set_level(level: usize) -> Option<usize> {
+    let filter = match level {
+        0 => LevelFilter::Off,
+        1 => LevelFilter::Error,
+        2 => LevelFilter::Warn,
+        3 => LevelFilter::Info,
+        4 => LevelFilter::Debug,
+        5 => LevelFilter::Trace,
+        _ => return None,
+    };
+    let previous = log::max_level() as usize;
+    log::set_max_level(filter);
+    Some(previous)
+}
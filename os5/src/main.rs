@@ -64,6 +64,17 @@ pub fn rust_main() -> ! {
     println!("[kernel] Hello, world!");
     mm::init();
     mm::remap_test();
+    #[cfg(feature = "selftest")]
+    {
+        mm::translate_va_test();
+        mm::areas_iter_test();
+        mm::translated_byte_buffer_test();
+        mm::frame_allocator_stress_test();
+        task::kernel_stack_pool_test();
+        syscall::no_current_task_test();
+    }
+    #[cfg(feature = "verify-fork")]
+    mm::verify_fork_equivalence_test();
     task::add_initproc();
     info!("after initproc!");
     trap::init();
@@ -2,8 +2,10 @@
 //! controls all the frames in the operating system.
 
 use super::{PhysAddr, PhysPageNum};
-use crate::config::MEMORY_END;
+use crate::config::SWAP_START;
 use crate::sync::UPSafeCell;
+#[cfg(feature = "selftest")]
+use alloc::collections::BTreeSet;
 use alloc::vec::Vec;
 use core::fmt::{self, Debug, Formatter};
 use lazy_static::*;
@@ -55,6 +57,34 @@ impl StackFrameAllocator {
         self.end = r.0;
         info!("last {} Physical Frames.", self.end - self.current);
     }
+    /// Number of frames that can still be allocated
+    pub fn remaining(&self) -> usize {
+        self.end - self.current + self.recycled.len()
+    }
+    /// Allocate `count` contiguous frames starting at a multiple of `count`.
+    /// Only the never allocated range is used, the frames skipped to align
+    /// the block are recycled.
+    pub fn alloc_contiguous(&mut self, count: usize) -> Option<PhysPageNum> {
+        let start = (self.current + count - 1) / count * count;
+        if start + count > self.end {
+            return None;
+        }
+        // every recycled frame lies below `current`, the skipped ones are not
+        // among them; the scan is linear, so debug builds only
+        debug_assert!(
+            self.recycled.iter().all(|&ppn| ppn < self.current),
+            "recycled frame at or above ppn={:#x}",
+            self.current
+        );
+        self.recycled.extend(self.current..start);
+        self.current = start + count;
+        Some(start.into())
+    }
+    /// Number of frames `alloc_contiguous` can still hand out in blocks of `count`
+    pub fn contiguous_remaining(&self, count: usize) -> usize {
+        let start = (self.current + count - 1) / count * count;
+        self.end.saturating_sub(start) / count * count
+    }
 }
 impl FrameAllocator for StackFrameAllocator {
     fn new() -> Self {
@@ -76,10 +106,15 @@ impl FrameAllocator for StackFrameAllocator {
     }
     fn dealloc(&mut self, ppn: PhysPageNum) {
         let ppn = ppn.0;
-        // validity check
-        if ppn >= self.current || self.recycled.iter().any(|v| *v == ppn) {
+        // validity check, the double free scan is linear so debug builds only
+        if ppn >= self.current {
             panic!("Frame ppn={:#x} has not been allocated!", ppn);
         }
+        debug_assert!(
+            !self.recycled.contains(&ppn),
+            "Frame ppn={:#x} is freed twice!",
+            ppn
+        );
         // recycle
         self.recycled.push(ppn);
     }
@@ -99,7 +134,7 @@ pub fn init_frame_allocator() {
     }
     FRAME_ALLOCATOR.exclusive_access().init(
         PhysAddr::from(ekernel as usize).ceil(),
-        PhysAddr::from(MEMORY_END).floor(),
+        PhysAddr::from(SWAP_START).floor(),
     );
 }
 
@@ -111,6 +146,26 @@ pub fn frame_alloc() -> Option<FrameTracker> {
         .map(FrameTracker::new)
 }
 
+/// number of free frames left in the frame allocator
+pub fn frame_remaining() -> usize {
+    FRAME_ALLOCATOR.exclusive_access().remaining()
+}
+
+/// allocate `count` physically contiguous frames, aligned to `count` frames
+pub fn frame_alloc_contiguous(count: usize) -> Option<Vec<FrameTracker>> {
+    let start = FRAME_ALLOCATOR.exclusive_access().alloc_contiguous(count)?;
+    Some(
+        (start.0..start.0 + count)
+            .map(|ppn| FrameTracker::new(ppn.into()))
+            .collect(),
+    )
+}
+
+/// number of frames left for contiguous allocations of `count` frames
+pub fn frame_contiguous_remaining(count: usize) -> usize {
+    FRAME_ALLOCATOR.exclusive_access().contiguous_remaining(count)
+}
+
 /// deallocate a frame
 fn frame_dealloc(ppn: PhysPageNum) {
     FRAME_ALLOCATOR.exclusive_access().dealloc(ppn);
@@ -134,3 +189,32 @@ pub fn frame_allocator_test() {
     drop(v);
     info!("frame_allocator_test passed!");
 }
+
+/// Allocate and free a few thousand frames in an interleaved order, checking
+/// that no frame is handed out twice and that every frame comes back
+#[cfg(feature = "selftest")]
+pub fn frame_allocator_stress_test() {
+    const FRAMES: usize = 2048;
+    let remaining = frame_remaining();
+    let mut held: Vec<FrameTracker> = (0..FRAMES).map(|_| frame_alloc().unwrap()).collect();
+    let distinct = |held: &Vec<FrameTracker>| {
+        held.iter().map(|frame| frame.ppn.0).collect::<BTreeSet<_>>().len() == held.len()
+    };
+    assert!(distinct(&held));
+    // free every other frame, then take as many again from the free list
+    let mut kept = Vec::new();
+    for (i, frame) in held.drain(..).enumerate() {
+        if i % 2 == 0 {
+            kept.push(frame);
+        }
+    }
+    held = kept;
+    for _ in 0..FRAMES / 2 {
+        held.push(frame_alloc().unwrap());
+    }
+    assert!(distinct(&held));
+    assert_eq!(frame_remaining(), remaining - FRAMES);
+    drop(held);
+    assert_eq!(frame_remaining(), remaining);
+    info!("frame_allocator_stress_test passed!");
+}
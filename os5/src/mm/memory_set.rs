@@ -1,12 +1,20 @@
 //! Implementation of [`MapArea`] and [`MemorySet`].
 
-use super::{frame_alloc, FrameTracker};
+use super::{frame_alloc, frame_alloc_contiguous, frame_remaining, FrameTracker};
+use super::{swap_alloc, SwapSlot};
 use super::{PTEFlags, PageTable, PageTableEntry};
 use super::{PhysAddr, PhysPageNum, VirtAddr, VirtPageNum};
 use super::{StepByOne, VPNRange};
-use crate::config::{MEMORY_END, PAGE_SIZE, TRAMPOLINE, TRAP_CONTEXT, USER_STACK_SIZE};
+#[cfg(feature = "selftest")]
+use super::translated_byte_buffer;
+use crate::config::{
+    HUGE_PAGE_SIZE, MEMORY_END, PAGE_SIZE, TRAMPOLINE, TRAP_CONTEXT, USER_STACK_SIZE,
+    USER_STACK_SIZE_MAX, VDSO,
+};
+use crate::timer::VDSO_PAGE;
 use crate::sync::UPSafeCell;
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 use lazy_static::*;
@@ -59,6 +67,200 @@ impl MemorySet {
             None,
         );
     }
+    /// Like `insert_framed_area`, but a fork shares the frames with the child
+    /// instead of copying them. Assume that no conflicts.
+    pub fn insert_shared_area(
+        &mut self,
+        start_va: VirtAddr,
+        end_va: VirtAddr,
+        permission: MapPermission,
+    ) {
+        let mut area = MapArea::new(start_va, end_va, MapType::Framed, permission);
+        area.shared = true;
+        self.push(area, None);
+    }
+    /// Reserve `[start_va, end_va)` as a stack growing down from its last page,
+    /// the only one backed now. The first page is a guard and never backed,
+    /// the pages between are backed on demand by `grow_down`.
+    pub fn insert_grows_down_area(
+        &mut self,
+        start_va: VirtAddr,
+        end_va: VirtAddr,
+        permission: MapPermission,
+    ) {
+        let top: VirtAddr = VirtPageNum(end_va.ceil().0 - 1).into();
+        self.insert_reserved_area(start_va, top);
+        let mut area = MapArea::new(top, end_va, MapType::Framed, permission);
+        area.grows_down = true;
+        self.push(area, None);
+    }
+    /// Map `[start_va, end_va)` with megapages, both ends must be 2M aligned.
+    /// Assume that no conflicts.
+    pub fn insert_huge_area(
+        &mut self,
+        start_va: VirtAddr,
+        end_va: VirtAddr,
+        permission: MapPermission,
+    ) {
+        self.push(
+            MapArea::new(start_va, end_va, MapType::Huge, permission),
+            None,
+        );
+    }
+    /// Record `[start_va, end_va)` as taken without backing it with frames.
+    /// Assume that no conflicts.
+    pub fn insert_reserved_area(&mut self, start_va: VirtAddr, end_va: VirtAddr) {
+        self.push(
+            MapArea::new(start_va, end_va, MapType::Reserved, MapPermission::U),
+            None,
+        );
+    }
+    /// Back `[start, end)` with frames, it must lie inside a single reserved area.
+    /// Whatever is left of that area on either side stays reserved.
+    pub fn commit_reserved(
+        &mut self,
+        start: VirtPageNum,
+        end: VirtPageNum,
+        permission: MapPermission,
+    ) -> bool {
+        let index = match self.areas.iter().position(|area| {
+            area.map_type == MapType::Reserved
+                && area.vpn_range.get_start() <= start
+                && end <= area.vpn_range.get_end()
+        }) {
+            Some(index) => index,
+            None => return false,
+        };
+        let reserved = self.areas.remove(index);
+        let (area_start, area_end) = (reserved.vpn_range.get_start(), reserved.vpn_range.get_end());
+        if area_start < start {
+            self.insert_reserved_area(area_start.into(), start.into());
+        }
+        if end < area_end {
+            self.insert_reserved_area(end.into(), area_end.into());
+        }
+        self.insert_framed_area(start.into(), end.into(), permission);
+        true
+    }
+    /// Extend the grow-down area sitting right above the reservation that
+    /// holds `vpn` down to `vpn`, taking the pages from the reservation.
+    /// False if there is none, if `vpn` is the guard page or frames are short.
+    pub fn grow_down(&mut self, vpn: VirtPageNum) -> bool {
+        let (index, reserved_start) = match self.areas.iter().position(|area| {
+            area.map_type == MapType::Reserved
+                && area.vpn_range.get_start() < vpn
+                && vpn < area.vpn_range.get_end()
+        }) {
+            Some(index) => (index, self.areas[index].vpn_range.get_start()),
+            None => return false,
+        };
+        let old_start = self.areas[index].vpn_range.get_end();
+        let area = match self
+            .areas
+            .iter_mut()
+            .find(|area| area.grows_down && area.vpn_range.get_start() == old_start)
+        {
+            Some(area) => area,
+            None => return false,
+        };
+        // the page tables on the way may need frames too
+        if frame_remaining() < old_start.0 - vpn.0 + 3 {
+            return false;
+        }
+        area.vpn_range = VPNRange::new(vpn, area.vpn_range.get_end());
+        for page in VPNRange::new(vpn, old_start) {
+            area.map_one(&mut self.page_table, page);
+        }
+        self.areas[index].vpn_range = VPNRange::new(reserved_start, vpn);
+        true
+    }
+    /// Whether `va` lies in a stack: the user stack, a grow-down area or the
+    /// reservation such an area grows into. Looks at the areas only, so it
+    /// neither faults pages in nor cares whether they are resident
+    pub fn in_stack(&self, va: VirtAddr) -> bool {
+        let area = match self.area_index(va.floor()) {
+            Some(index) => &self.areas[index],
+            None => return false,
+        };
+        area.user_stack
+            || area.grows_down
+            || (area.map_type == MapType::Reserved
+                && self.areas.iter().any(|above| {
+                    above.grows_down && above.vpn_range.get_start() == area.vpn_range.get_end()
+                }))
+    }
+    /// Size of the page mapping `vpn`, `None` if it is not mapped
+    pub fn page_size(&self, vpn: VirtPageNum) -> Option<usize> {
+        self.page_table.page_size(vpn)
+    }
+    /// Page range and permission of every area, in the order they were added
+    pub fn areas_iter(&self) -> impl Iterator<Item = (VPNRange, MapPermission)> + '_ {
+        self.areas.iter().map(|area| (area.vpn_range, area.map_perm))
+    }
+    /// Index of the area covering `vpn`
+    fn area_index(&self, vpn: VirtPageNum) -> Option<usize> {
+        self.areas_iter()
+            .position(|(range, _)| range.get_start() <= vpn && vpn < range.get_end())
+    }
+    /// Whether `vpn` is backed by a frame, `None` if no area covers it
+    pub fn is_resident(&self, vpn: VirtPageNum) -> Option<bool> {
+        self.area_index(vpn)
+            .map(|_| self.translate(vpn).map_or(false, |pte| pte.is_valid()))
+    }
+    /// Number of frames mapped by this address space, shared and page table frames included
+    pub fn frame_count(&self) -> usize {
+        self.page_table.frames.len()
+            + self
+                .areas
+                .iter()
+                .map(|area| area.data_frames.len())
+                .sum::<usize>()
+    }
+    /// 64-bit FNV-1a hash of the address and contents of every user page that
+    /// holds data, swapped out pages included. The area covering `skip` is
+    /// left out, so a caller can exclude the stack it is running on.
+    pub fn checksum(&self, skip: VirtPageNum) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x100_0000_01b3;
+        let fold = |hash: u64, bytes: &[u8]| {
+            bytes
+                .iter()
+                .fold(hash, |hash, &byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+        };
+        let skipped = self.area_index(skip);
+        let mut hash = FNV_OFFSET_BASIS;
+        for (index, area) in self.areas.iter().enumerate() {
+            if Some(index) == skipped || !area.map_perm.contains(MapPermission::U) {
+                continue;
+            }
+            for vpn in area.vpn_range {
+                let bytes = match area.swapped.get(&vpn) {
+                    Some(slot) => slot.bytes(),
+                    None => match self.translate(vpn).filter(|pte| pte.is_valid()) {
+                        Some(pte) => pte.ppn().get_bytes_array(),
+                        None => continue,
+                    },
+                };
+                hash = fold(hash, &vpn.0.to_le_bytes());
+                hash = fold(hash, bytes);
+            }
+        }
+        hash
+    }
+    /// Map a copy of `data` at `start_va` in freshly allocated frames.
+    /// Assume that no conflicts.
+    pub fn insert_framed_area_with_data(
+        &mut self,
+        start_va: VirtAddr,
+        permission: MapPermission,
+        data: &[u8],
+    ) {
+        let end_va = VirtAddr::from(start_va.0 + data.len());
+        self.push(
+            MapArea::new(start_va, end_va, MapType::Framed, permission),
+            Some(data),
+        );
+    }
     pub fn remove_area_with_start_vpn(&mut self, start_vpn: VirtPageNum) {
         if let Some((idx, area)) = self
             .areas
@@ -85,6 +287,14 @@ impl MemorySet {
             PTEFlags::R | PTEFlags::X,
         );
     }
+    /// The vDSO page is not collected by areas either, it is a kernel page.
+    fn map_vdso(&mut self) {
+        self.page_table.map(
+            VirtAddr::from(VDSO).into(),
+            PhysAddr::from(&VDSO_PAGE as *const _ as usize).into(),
+            PTEFlags::R | PTEFlags::U,
+        );
+    }
     /// Without kernel stacks.
     pub fn new_kernel() -> Self {
         let mut memory_set = Self::new_bare();
@@ -151,11 +361,13 @@ impl MemorySet {
         memory_set
     }
     /// Include sections in elf and trampoline and TrapContext and user stack,
-    /// also returns user_sp and entry point.
+    /// also returns user_sp and entry point. The stack is sized by
+    /// [`elf_stack_size`].
     pub fn from_elf(elf_data: &[u8]) -> (Self, usize, usize) {
         let mut memory_set = Self::new_bare();
         // map trampoline
         memory_set.map_trampoline();
+        memory_set.map_vdso();
         // map program headers of elf, with U flag
         let elf = xmas_elf::ElfFile::new(elf_data).unwrap();
         let elf_header = elf.header;
@@ -192,16 +404,15 @@ impl MemorySet {
         let mut user_stack_bottom: usize = max_end_va.into();
         // guard page
         user_stack_bottom += PAGE_SIZE;
-        let user_stack_top = user_stack_bottom + USER_STACK_SIZE;
-        memory_set.push(
-            MapArea::new(
-                user_stack_bottom.into(),
-                user_stack_top.into(),
-                MapType::Framed,
-                MapPermission::R | MapPermission::W | MapPermission::U,
-            ),
-            None,
+        let user_stack_top = user_stack_bottom + elf_stack_size(&elf);
+        let mut user_stack = MapArea::new(
+            user_stack_bottom.into(),
+            user_stack_top.into(),
+            MapType::Framed,
+            MapPermission::R | MapPermission::W | MapPermission::U,
         );
+        user_stack.user_stack = true;
+        memory_set.push(user_stack, None);
         // map TrapContext
         memory_set.push(
             MapArea::new(
@@ -218,19 +429,92 @@ impl MemorySet {
             elf.header.pt2.entry_point() as usize,
         )
     }
+    /// Whether `from_elf` can load `elf_data`, which may come from user space.
+    /// Every loadable segment must lie inside the image and below the trap context,
+    /// and no two of them, or one and the user stack `from_elf` puts above the
+    /// last one, may share a page. Checked in full before exec touches the
+    /// caller, so a bad image leaves it as it was.
+    pub fn elf_is_valid(elf_data: &[u8]) -> bool {
+        let elf = match xmas_elf::ElfFile::new(elf_data) {
+            Ok(elf) => elf,
+            Err(_) => return false,
+        };
+        if elf.header.pt1.magic != [0x7f, 0x45, 0x4c, 0x46] {
+            return false;
+        }
+        let mut segments: Vec<(VirtPageNum, VirtPageNum)> = Vec::new();
+        for i in 0..elf.header.pt2.ph_count() {
+            let ph = match elf.program_header(i) {
+                Ok(ph) => ph,
+                Err(_) => return false,
+            };
+            match ph.get_type() {
+                Ok(xmas_elf::program::Type::Load) => {}
+                Ok(_) => continue,
+                Err(_) => return false,
+            }
+            let file_end = ph.offset().checked_add(ph.file_size());
+            let mem_end = ph.virtual_addr().checked_add(ph.mem_size());
+            match (file_end, mem_end) {
+                (Some(file_end), Some(mem_end))
+                    if file_end <= elf_data.len() as u64
+                        && ph.file_size() <= ph.mem_size()
+                        && mem_end <= VDSO as u64 =>
+                {
+                    segments.push((
+                        VirtAddr::from(ph.virtual_addr() as usize).floor(),
+                        VirtAddr::from(mem_end as usize).ceil(),
+                    ));
+                }
+                _ => return false,
+            }
+        }
+        // from_elf puts the stack above the last segment, not the highest one,
+        // so it has to be checked against the others too
+        let stack_bottom = segments
+            .last()
+            .map_or(0, |&(_, end)| usize::from(VirtAddr::from(end)))
+            + PAGE_SIZE;
+        let stack_size = elf_stack_size(&elf);
+        if stack_bottom + stack_size > VDSO {
+            return false;
+        }
+        segments.push((
+            VirtAddr::from(stack_bottom).floor(),
+            VirtAddr::from(stack_bottom + stack_size).ceil(),
+        ));
+        segments.sort();
+        segments.windows(2).all(|pair| pair[0].1 <= pair[1].0)
+    }
     /// Copy an identical user_space
     pub fn from_existed_user(user_space: &MemorySet) -> MemorySet {
         let mut memory_set = Self::new_bare();
         // map trampoline
         memory_set.map_trampoline();
+        memory_set.map_vdso();
         // copy data sections/trap_context/user_stack
         for area in user_space.areas.iter() {
-            let new_area = MapArea::from_another(area);
+            let mut new_area = MapArea::from_another(area);
+            if area.map_type == MapType::Reserved {
+                memory_set.areas.push(new_area);
+                continue;
+            }
+            if area.is_read_only() || area.shared {
+                // nobody writes these frames any more, or writes are meant to be
+                // seen by both, so both spaces map the same ones
+                new_area.share_frames(area, &mut memory_set.page_table);
+                memory_set.areas.push(new_area);
+                continue;
+            }
             memory_set.push(new_area, None);
-            // copy data from another space
+            // copy data from another space, or from swap space for its swapped out pages
             for vpn in area.vpn_range {
-                let src_ppn = user_space.translate(vpn).unwrap().ppn();
                 let dst_ppn = memory_set.translate(vpn).unwrap().ppn();
+                if let Some(slot) = area.swapped.get(&vpn) {
+                    dst_ppn.get_bytes_array().copy_from_slice(slot.bytes());
+                    continue;
+                }
+                let src_ppn = user_space.translate(vpn).unwrap().ppn();
                 dst_ppn
                     .get_bytes_array()
                     .copy_from_slice(src_ppn.get_bytes_array());
@@ -252,40 +536,197 @@ impl MemorySet {
         //*self = Self::new_bare();
         self.areas.clear();
     }
-    //lab 3
-    pub fn unmap(&mut self,_start:VirtAddr,_end:VirtAddr) -> bool{
+    /// Unmap the user areas that exactly tile `[start, end)`.
+    /// The whole range is validated against the area list first, stopping at
+    /// the first uncovered vpn, so a gap or an area sticking out of the range
+    /// fails with nothing unmapped.
+    pub fn unmap_range(&mut self, start: VirtPageNum, end: VirtPageNum) -> bool {
+        let mut covering: Vec<(VirtPageNum, VirtPageNum)> = self
+            .areas
+            .iter()
+            .filter(|area| area.vpn_range.get_start() < end && area.vpn_range.get_end() > start)
+            .map(|area| {
+                if area.map_perm.contains(MapPermission::U) {
+                    (area.vpn_range.get_start(), area.vpn_range.get_end())
+                } else {
+                    // never let a user unmap its trap context
+                    (end, end)
+                }
+            })
+            .collect();
+        covering.sort();
+        let mut next = start;
+        for (area_start, area_end) in covering {
+            if area_start != next || area_end > end {
+                return false;
+            }
+            next = area_end;
+        }
+        if next != end {
+            return false;
+        }
         let mut index = 0;
-        for maparea in &mut self.areas{
-            if maparea.find(_start,_end){
-                maparea.unmap(&mut self.page_table);
-                self.areas.remove(index);
-                return true;
-                break;
+        while index < self.areas.len() {
+            let area = &self.areas[index];
+            if area.vpn_range.get_start() >= start && area.vpn_range.get_end() <= end {
+                let mut area = self.areas.remove(index);
+                area.unmap(&mut self.page_table);
+            } else {
+                index += 1;
+            }
+        }
+        true
+    }
+    /// Number of pages of `[start, end)` `swap_out` would move, `None` unless
+    /// every page belongs to a private, writable user area of 4K pages
+    pub fn swappable_pages(&self, start: VirtPageNum, end: VirtPageNum) -> Option<usize> {
+        let mut count = 0;
+        let mut vpn = start;
+        while vpn < end {
+            let area = self
+                .areas
+                .iter()
+                .find(|area| area.vpn_range.get_start() <= vpn && vpn < area.vpn_range.get_end())?;
+            if !area.is_swappable() {
+                return None;
+            }
+            if !area.swapped.contains_key(&vpn) && !area.locked.contains(&vpn) {
+                count += 1;
+            }
+            vpn.step();
+        }
+        Some(count)
+    }
+    /// Move the frames of `[start, end)` to swap space and unmap them, pages
+    /// already swapped out or locked are skipped. Check `swappable_pages` and the free
+    /// swap space first. Return how many pages were moved.
+    pub fn swap_out(&mut self, start: VirtPageNum, end: VirtPageNum) -> usize {
+        let mut count = 0;
+        for area in self.areas.iter_mut() {
+            let mut vpn = area.vpn_range.get_start().max(start);
+            let area_end = area.vpn_range.get_end().min(end);
+            while vpn < area_end {
+                if area.swap_out_one(&mut self.page_table, vpn) {
+                    count += 1;
+                }
+                vpn.step();
+            }
+        }
+        count
+    }
+    /// Map `vpn` again with the contents it had when swapped out,
+    /// false if it is not swapped out or no frame is left for it
+    pub fn swap_in(&mut self, vpn: VirtPageNum) -> bool {
+        match self.areas.iter_mut().find(|area| area.swapped.contains_key(&vpn)) {
+            // the page tables on the way may need frames too
+            Some(area) if frame_remaining() >= 3 => {
+                area.swap_in_one(&mut self.page_table, vpn);
+                true
+            }
+            _ => false,
+        }
+    }
+    /// Lock every page of `[start, end)` against `swap_out`, or unlock it.
+    /// Locking brings swapped out pages back first. False with nothing
+    /// changed unless user areas holding pages cover the whole range, or if
+    /// there are not enough frames to bring the swapped out pages back.
+    pub fn set_locked(&mut self, start: VirtPageNum, end: VirtPageNum, locked: bool) -> bool {
+        let mut swapped = 0;
+        let mut vpn = start;
+        while vpn < end {
+            let area = match self.area_index(vpn) {
+                Some(index) => &self.areas[index],
+                None => return false,
+            };
+            if !area.map_perm.contains(MapPermission::U) || area.map_type == MapType::Reserved {
+                return false;
+            }
+            if area.swapped.contains_key(&vpn) {
+                swapped += 1;
+            }
+            vpn.step();
+        }
+        // the page tables on the way may need frames too
+        if locked && swapped > 0 && frame_remaining() < swapped + 3 {
+            return false;
+        }
+        for area in self.areas.iter_mut() {
+            let mut vpn = area.vpn_range.get_start().max(start);
+            let area_end = area.vpn_range.get_end().min(end);
+            while vpn < area_end {
+                if !locked {
+                    area.locked.remove(&vpn);
+                } else {
+                    if area.swapped.contains_key(&vpn) {
+                        area.swap_in_one(&mut self.page_table, vpn);
+                    }
+                    area.locked.insert(vpn);
+                }
+                vpn.step();
             }
-            index +=1;
         }
-        false
+        true
     }
     //lab 3
+    /// Whether a page of `[start_va, end_va)` belongs to an area, or is the
+    /// vDSO page or above it, which are mapped outside of `areas`
     pub fn check_va_overlap(&self, start_va: usize, end_va: usize) -> bool {
-        for area in &self.areas {
-            let s:VirtAddr = area.vpn_range.get_start().into();
-            let e: VirtAddr = area.vpn_range.get_end().into();
-
-            if (s < end_va.into() && s >= start_va.into()) || (e < end_va.into() && e > start_va.into()) {
-                return true;
+        let start = VirtAddr::from(start_va).floor();
+        let end = VirtAddr::from(end_va).ceil();
+        if end > VirtAddr::from(VDSO).floor() {
+            return true;
+        }
+        self.areas.iter().any(|area| {
+            let (s, e) = (area.vpn_range.get_start(), area.vpn_range.get_end());
+            s < end && start < e
+        })
+    }
+    /// Attach a debugging label to the area covering `va`,
+    /// return false if no area covers it.
+    pub fn name_area(&mut self, va: VirtAddr, name: String) -> bool {
+        match self.area_index(va.floor()) {
+            Some(index) => {
+                self.areas[index].name = Some(name);
+                true
             }
+            None => false,
+        }
+    }
+    /// Print every area with its permission and label.
+    pub fn dump(&self) {
+        for area in self.areas.iter() {
+            let start: VirtAddr = area.vpn_range.get_start().into();
+            let end: VirtAddr = area.vpn_range.get_end().into();
+            println!(
+                "[kernel] [{:#x}, {:#x}) {:?} {}",
+                start.0,
+                end.0,
+                area.map_perm,
+                area.name.as_deref().unwrap_or("")
+            );
         }
-        false
     }
 }
 
 /// map area structure, controls a contiguous piece of virtual memory
 pub struct MapArea {
     vpn_range: VPNRange,
-    data_frames: BTreeMap<VirtPageNum, FrameTracker>,
+    data_frames: BTreeMap<VirtPageNum, Arc<FrameTracker>>,
     map_type: MapType,
     map_perm: MapPermission,
+    /// Optional label shown in the memory map dump
+    name: Option<String>,
+    /// Forked spaces map the same frames instead of copies
+    shared: bool,
+    /// Pages moved to swap space, they are unmapped and have no frame
+    swapped: BTreeMap<VirtPageNum, SwapSlot>,
+    /// A stack that extends into the reservation right below it on a fault
+    grows_down: bool,
+    /// The user stack `from_elf` maps
+    user_stack: bool,
+    /// Pages pinned by mlock, swap_out leaves them resident. Not inherited
+    /// across fork
+    locked: BTreeSet<VirtPageNum>,
 }
 
 impl MapArea {
@@ -302,21 +743,26 @@ impl MapArea {
             data_frames: BTreeMap::new(),
             map_type,
             map_perm,
+            name: None,
+            shared: false,
+            swapped: BTreeMap::new(),
+            grows_down: false,
+            user_stack: false,
+            locked: BTreeSet::new(),
         }
     }
-    //lab 3
-    pub fn find(&self,_start:VirtAddr,_end:VirtAddr) -> bool{
-        if self.vpn_range.get_start()==_start.floor() && self.vpn_range.get_end()==_end.floor(){
-            return true;
-        }
-        false
-    }
     pub fn from_another(another: &MapArea) -> Self {
         Self {
             vpn_range: VPNRange::new(another.vpn_range.get_start(), another.vpn_range.get_end()),
             data_frames: BTreeMap::new(),
             map_type: another.map_type,
             map_perm: another.map_perm,
+            name: another.name.clone(),
+            shared: another.shared,
+            swapped: BTreeMap::new(),
+            grows_down: another.grows_down,
+            user_stack: another.user_stack,
+            locked: BTreeSet::new(),
         }
     }
     pub fn map_one(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) {
@@ -328,14 +774,60 @@ impl MapArea {
             MapType::Framed => {
                 let frame = frame_alloc().unwrap();
                 ppn = frame.ppn;
-                self.data_frames.insert(vpn, frame);
+                self.data_frames.insert(vpn, Arc::new(frame));
             }
+            MapType::Huge => unreachable!("huge areas are mapped a megapage at a time"),
+            MapType::Reserved => unreachable!("reserved areas have nothing to map"),
         }
         let pte_flags = PTEFlags::from_bits(self.map_perm.bits).unwrap();
         page_table.map(vpn, ppn, pte_flags);
     }
 
+    /// Private, writable user area of 4K pages. Other areas may share their
+    /// frames with forked spaces, or have no frames at all.
+    pub fn is_swappable(&self) -> bool {
+        self.map_type == MapType::Framed
+            && !self.shared
+            && self.map_perm.contains(MapPermission::U | MapPermission::W)
+    }
+    /// Copy the page at `vpn` to a new swap slot and free its frame,
+    /// false if it is swapped out already or locked
+    fn swap_out_one(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) -> bool {
+        if self.swapped.contains_key(&vpn) || self.locked.contains(&vpn) {
+            return false;
+        }
+        let slot = swap_alloc().unwrap();
+        let frame = self.data_frames.remove(&vpn).unwrap();
+        slot.bytes().copy_from_slice(frame.ppn.get_bytes_array());
+        page_table.unmap(vpn);
+        self.swapped.insert(vpn, slot);
+        true
+    }
+    /// Map `vpn` to a new frame holding the contents of its swap slot
+    fn swap_in_one(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) {
+        let slot = self.swapped.remove(&vpn).unwrap();
+        self.map_one(page_table, vpn);
+        self.data_frames[&vpn]
+            .ppn
+            .get_bytes_array()
+            .copy_from_slice(slot.bytes());
+    }
+    /// Framed area that user space cannot write
+    pub fn is_read_only(&self) -> bool {
+        self.map_type == MapType::Framed && !self.map_perm.contains(MapPermission::W)
+    }
+    /// Map the frames of `another` instead of allocating new ones
+    pub fn share_frames(&mut self, another: &MapArea, page_table: &mut PageTable) {
+        let pte_flags = PTEFlags::from_bits(self.map_perm.bits).unwrap();
+        for (vpn, frame) in another.data_frames.iter() {
+            page_table.map(*vpn, frame.ppn, pte_flags);
+            self.data_frames.insert(*vpn, frame.clone());
+        }
+    }
     pub fn unmap_one(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) {
+        if self.swapped.remove(&vpn).is_some() {
+            return;
+        }
         #[allow(clippy::single_match)]
         match self.map_type {
             MapType::Framed => {
@@ -346,15 +838,51 @@ impl MapArea {
         page_table.unmap(vpn);
     }
     pub fn map(&mut self, page_table: &mut PageTable) {
+        if self.map_type == MapType::Huge {
+            self.map_huge(page_table);
+            return;
+        }
+        if self.map_type == MapType::Reserved {
+            return;
+        }
         for vpn in self.vpn_range {
             self.map_one(page_table, vpn);
         }
     }
     pub fn unmap(&mut self, page_table: &mut PageTable) {
+        if self.map_type == MapType::Huge {
+            self.unmap_huge(page_table);
+            return;
+        }
+        if self.map_type == MapType::Reserved {
+            return;
+        }
         for vpn in self.vpn_range {
             self.unmap_one(page_table, vpn);
         }
     }
+    /// Back each 2M of the area with one megapage. Its frames are still kept
+    /// one per vpn, so they are freed and copied on fork like any others.
+    fn map_huge(&mut self, page_table: &mut PageTable) {
+        let pte_flags = PTEFlags::from_bits(self.map_perm.bits).unwrap();
+        let mut vpn = self.vpn_range.get_start();
+        while vpn < self.vpn_range.get_end() {
+            let frames = frame_alloc_contiguous(HUGE_PAGE_SIZE / PAGE_SIZE).unwrap();
+            page_table.map_huge(vpn, frames[0].ppn, pte_flags);
+            for frame in frames {
+                self.data_frames.insert(vpn, Arc::new(frame));
+                vpn.step();
+            }
+        }
+    }
+    fn unmap_huge(&mut self, page_table: &mut PageTable) {
+        let mut vpn = self.vpn_range.get_start();
+        while vpn < self.vpn_range.get_end() {
+            page_table.unmap_huge(vpn);
+            vpn = VirtPageNum(vpn.0 + HUGE_PAGE_SIZE / PAGE_SIZE);
+        }
+        self.data_frames.clear();
+    }
     /// data: start-aligned but maybe with shorter length
     /// assume that all frames were cleared before
     pub fn copy_data(&mut self, page_table: &mut PageTable, data: &[u8]) {
@@ -380,10 +908,13 @@ impl MapArea {
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
-/// map type for memory set: identical or framed
+/// map type for memory set: identical, framed, framed with 2M megapages,
+/// or reserved address space with nothing mapped
 pub enum MapType {
     Identical,
     Framed,
+    Huge,
+    Reserved,
 }
 
 bitflags! {
@@ -396,6 +927,28 @@ bitflags! {
     }
 }
 
+/// Size of the user stack an app asks for with a native-endian usize in its
+/// `.stack_size` section, rounded up to whole pages and capped at
+/// `USER_STACK_SIZE_MAX`. `USER_STACK_SIZE` without the section or with 0 in it
+fn elf_stack_size(elf: &xmas_elf::ElfFile) -> usize {
+    const WORD: usize = core::mem::size_of::<usize>();
+    let requested = elf
+        .find_section_by_name(".stack_size")
+        .map(|section| section.raw_data(elf))
+        .filter(|data| data.len() >= WORD)
+        .map(|data| {
+            let mut word = [0u8; WORD];
+            word.copy_from_slice(&data[..WORD]);
+            usize::from_ne_bytes(word)
+        })
+        .unwrap_or(0);
+    if requested == 0 {
+        return USER_STACK_SIZE;
+    }
+    let size = requested.min(USER_STACK_SIZE_MAX);
+    (size + PAGE_SIZE - 1) / PAGE_SIZE * PAGE_SIZE
+}
+
 #[allow(unused)]
 pub fn remap_test() {
     let mut kernel_space = KERNEL_SPACE.exclusive_access();
@@ -419,3 +972,155 @@ pub fn remap_test() {
         .executable());
     info!("remap_test passed!");
 }
+
+/// Check that `child` is a faithful copy of `parent` right after a fork:
+/// the same areas, and every user page mapped in one is mapped in the other
+/// with the same flags and identical contents.
+///
+/// Return the first page that differs.
+#[cfg(feature = "verify-fork")]
+pub fn verify_fork_equivalence(parent: &MemorySet, child: &MemorySet) -> Result<(), VirtPageNum> {
+    if parent.areas.len() != child.areas.len() {
+        let first = parent.areas.first().or(child.areas.first());
+        return Err(first.map_or(VirtPageNum(0), |area| area.vpn_range.get_start()));
+    }
+    for (area, child_area) in parent.areas.iter().zip(child.areas.iter()) {
+        if area.vpn_range.get_start() != child_area.vpn_range.get_start()
+            || area.vpn_range.get_end() != child_area.vpn_range.get_end()
+        {
+            return Err(area.vpn_range.get_start());
+        }
+        for vpn in area.vpn_range {
+            if let Some(slot) = area.swapped.get(&vpn) {
+                match child.translate(vpn).filter(|pte| pte.is_valid()) {
+                    Some(child_pte) if child_pte.ppn().get_bytes_array() == slot.bytes() => continue,
+                    _ => return Err(vpn),
+                }
+            }
+            let pte = parent.translate(vpn).filter(|pte| pte.is_valid());
+            let child_pte = child.translate(vpn).filter(|pte| pte.is_valid());
+            match (pte, child_pte) {
+                (None, None) => {}
+                (Some(pte), Some(child_pte))
+                    if pte.flags() == child_pte.flags()
+                        && pte.ppn().get_bytes_array() == child_pte.ppn().get_bytes_array() => {}
+                _ => return Err(vpn),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fork a user space holding known patterns, check the verifier accepts the
+/// copy, then corrupt one byte of the copy and check it is caught
+#[cfg(feature = "verify-fork")]
+pub fn verify_fork_equivalence_test() {
+    let base: usize = 0x1000_0000;
+    let mut parent = MemorySet::new_bare();
+    parent.insert_framed_area(
+        base.into(),
+        (base + 4 * PAGE_SIZE).into(),
+        MapPermission::R | MapPermission::W | MapPermission::U,
+    );
+    for i in 0..4 {
+        let vpn = VirtAddr::from(base + i * PAGE_SIZE).floor();
+        let bytes = parent.translate(vpn).unwrap().ppn().get_bytes_array();
+        for (offset, byte) in bytes.iter_mut().enumerate() {
+            *byte = (i * 31 + offset) as u8;
+        }
+    }
+    let child = MemorySet::from_existed_user(&parent);
+    assert_eq!(verify_fork_equivalence(&parent, &child), Ok(()));
+    let corrupted = VirtAddr::from(base + 2 * PAGE_SIZE).floor();
+    child.translate(corrupted).unwrap().ppn().get_bytes_array()[123] ^= 0xff;
+    assert_eq!(verify_fork_equivalence(&parent, &child), Err(corrupted));
+    info!("verify_fork_equivalence_test passed!");
+}
+
+/// Check that `translate_va` keeps the page offset of mapped addresses and
+/// yields `None` both next to a mapping and where no page table exists
+#[cfg(feature = "selftest")]
+pub fn translate_va_test() {
+    let base: usize = 0x1000_0000;
+    let mut memory_set = MemorySet::new_bare();
+    memory_set.insert_framed_area(
+        base.into(),
+        (base + 2 * PAGE_SIZE).into(),
+        MapPermission::R | MapPermission::W | MapPermission::U,
+    );
+    let page_table = PageTable::from_token(memory_set.token());
+    for va in [base, base + 0x123, base + 2 * PAGE_SIZE - 1] {
+        let ppn = memory_set.translate(VirtAddr::from(va).floor()).unwrap().ppn();
+        let expected = usize::from(PhysAddr::from(ppn)) + va % PAGE_SIZE;
+        let pa = page_table.translate_va(va.into()).unwrap();
+        assert_eq!(usize::from(pa), expected);
+    }
+    // the leaf table exists but holds an invalid entry
+    assert!(page_table.translate_va((base + 2 * PAGE_SIZE).into()).is_none());
+    assert!(page_table.translate_va((base - 1).into()).is_none());
+    // no table below the root at all
+    assert!(page_table.translate_va((base + (1 << 30)).into()).is_none());
+    info!("translate_va_test passed!");
+}
+
+/// Check that `areas_iter` reports the range and permission of each area
+/// in the order they were added, reserved areas included
+#[cfg(feature = "selftest")]
+pub fn areas_iter_test() {
+    let base: usize = 0x1000_0000;
+    let mut memory_set = MemorySet::new_bare();
+    let rw = MapPermission::R | MapPermission::W | MapPermission::U;
+    let r = MapPermission::R | MapPermission::U;
+    memory_set.insert_framed_area(base.into(), (base + 2 * PAGE_SIZE).into(), rw);
+    memory_set.insert_reserved_area((base + 4 * PAGE_SIZE).into(), (base + 7 * PAGE_SIZE).into());
+    memory_set.insert_shared_area((base + 8 * PAGE_SIZE).into(), (base + 9 * PAGE_SIZE).into(), r);
+    let expected = [(0, 2, rw), (4, 7, MapPermission::U), (8, 9, r)];
+    let areas: Vec<(VPNRange, MapPermission)> = memory_set.areas_iter().collect();
+    assert_eq!(areas.len(), expected.len());
+    for ((range, perm), (start, end, expected_perm)) in areas.into_iter().zip(expected) {
+        assert_eq!(range.get_start(), VirtAddr::from(base + start * PAGE_SIZE).floor());
+        assert_eq!(range.get_end(), VirtAddr::from(base + end * PAGE_SIZE).floor());
+        assert_eq!(perm, expected_perm);
+    }
+    assert!(memory_set.name_area((base + 5 * PAGE_SIZE).into(), String::from("heap")));
+    assert!(!memory_set.name_area((base + 3 * PAGE_SIZE).into(), String::from("gap")));
+    assert_eq!(memory_set.is_resident(VirtAddr::from(base + PAGE_SIZE).floor()), Some(true));
+    assert_eq!(memory_set.is_resident(VirtAddr::from(base + 6 * PAGE_SIZE).floor()), Some(false));
+    assert_eq!(memory_set.is_resident(VirtAddr::from(base + 7 * PAGE_SIZE).floor()), None);
+    info!("areas_iter_test passed!");
+}
+
+/// Check that `translated_byte_buffer` yields every byte of a multi-page
+/// range in order, for both page-aligned and unaligned starts
+#[cfg(feature = "selftest")]
+pub fn translated_byte_buffer_test() {
+    let base: usize = 0x1000_0000;
+    let mut memory_set = MemorySet::new_bare();
+    memory_set.insert_framed_area(
+        base.into(),
+        (base + 5 * PAGE_SIZE).into(),
+        MapPermission::R | MapPermission::W | MapPermission::U,
+    );
+    let pattern = |va: usize| (va ^ (va >> 8)) as u8;
+    for i in 0..5 {
+        let vpn = VirtAddr::from(base + i * PAGE_SIZE).floor();
+        let bytes = memory_set.translate(vpn).unwrap().ppn().get_bytes_array();
+        for (offset, byte) in bytes.iter_mut().enumerate() {
+            *byte = pattern(base + i * PAGE_SIZE + offset);
+        }
+    }
+    for start in [base, base + 0x123] {
+        for len in [PAGE_SIZE, PAGE_SIZE + 1, 3 * PAGE_SIZE + 7] {
+            let buffers = translated_byte_buffer(memory_set.token(), start as *const u8, len);
+            let mut va = start;
+            for buffer in buffers {
+                for byte in buffer.iter() {
+                    assert_eq!(*byte, pattern(va));
+                    va += 1;
+                }
+            }
+            assert_eq!(va, start + len);
+        }
+    }
+    info!("translated_byte_buffer_test passed!");
+}
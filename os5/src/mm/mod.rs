@@ -12,14 +12,27 @@ mod frame_allocator;
 mod heap_allocator;
 mod memory_set;
 mod page_table;
+mod swap;
 
 pub use address::{PhysAddr, PhysPageNum, VirtAddr, VirtPageNum};
 pub use address::{StepByOne, VPNRange};
-pub use frame_allocator::{frame_alloc, FrameTracker};
+pub use frame_allocator::{
+    frame_alloc, frame_alloc_contiguous, frame_contiguous_remaining, frame_remaining, FrameTracker,
+};
+#[cfg(feature = "selftest")]
+pub use frame_allocator::frame_allocator_stress_test;
 pub use memory_set::remap_test;
+#[cfg(feature = "selftest")]
+pub use memory_set::{areas_iter_test, translate_va_test, translated_byte_buffer_test};
+#[cfg(feature = "verify-fork")]
+pub use memory_set::{verify_fork_equivalence, verify_fork_equivalence_test};
 pub use memory_set::{MapPermission, MemorySet, KERNEL_SPACE};
-pub use page_table::{translated_byte_buffer, translated_refmut, translated_str, PageTableEntry};
+pub use page_table::{
+    copy_bytes_to_user, copy_to_user, translated_byte_buffer, translated_str,
+    user_readable, user_writable, PageTableEntry,
+};
 pub use page_table::{PTEFlags, PageTable};
+pub use swap::{swap_alloc, swap_remaining, SwapSlot};
 
 /// initiate heap allocator, frame allocator and kernel space
 pub fn init() {
@@ -4,6 +4,7 @@ use super::{frame_alloc, FrameTracker, PhysAddr, PhysPageNum, StepByOne, VirtAdd
 use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
+use crate::config::{PAGE_SIZE, USER_MMAP_END};
 use bitflags::*;
 
 bitflags! {
@@ -54,6 +55,10 @@ impl PageTableEntry {
     pub fn executable(&self) -> bool {
         (self.flags() & PTEFlags::X) != PTEFlags::empty()
     }
+    /// A valid entry with any of R/W/X maps memory instead of pointing to the next level
+    pub fn is_leaf(&self) -> bool {
+        self.is_valid() && self.flags().intersects(PTEFlags::R | PTEFlags::W | PTEFlags::X)
+    }
 }
 
 /// page table structure
@@ -79,12 +84,17 @@ impl PageTable {
         }
     }
     fn find_pte_create(&mut self, vpn: VirtPageNum) -> Option<&mut PageTableEntry> {
+        self.find_pte_create_at(vpn, 2)
+    }
+    /// Walk down to the entry of `vpn` at `level` (2 for 4K pages, 1 for megapages),
+    /// creating the tables above it
+    fn find_pte_create_at(&mut self, vpn: VirtPageNum, level: usize) -> Option<&mut PageTableEntry> {
         let mut idxs = vpn.indexes();
         let mut ppn = self.root_ppn;
         let mut result: Option<&mut PageTableEntry> = None;
         for (i, idx) in idxs.iter_mut().enumerate() {
             let pte = &mut ppn.get_pte_array()[*idx];
-            if i == 2 {
+            if i == level {
                 result = Some(pte);
                 break;
             }
@@ -97,14 +107,15 @@ impl PageTable {
         }
         result
     }
-    fn find_pte(&self, vpn: VirtPageNum) -> Option<&PageTableEntry> {
+    /// The entry of `vpn` and its level, which is 1 if a megapage maps `vpn`
+    fn find_pte(&self, vpn: VirtPageNum) -> Option<(&PageTableEntry, usize)> {
         let idxs = vpn.indexes();
         let mut ppn = self.root_ppn;
-        let mut result: Option<&PageTableEntry> = None;
+        let mut result: Option<(&PageTableEntry, usize)> = None;
         for (i, idx) in idxs.iter().enumerate() {
             let pte = &ppn.get_pte_array()[*idx];
-            if i == 2 {
-                result = Some(pte);
+            if i == 2 || pte.is_leaf() {
+                result = Some((pte, i));
                 break;
             }
             if !pte.is_valid() {
@@ -126,18 +137,49 @@ impl PageTable {
         assert!(pte.is_valid(), "vpn {:?} is invalid before unmapping", vpn);
         *pte = PageTableEntry::empty();
     }
+    /// Map the megapage starting at `vpn` to the 2M aligned frames at `ppn`
+    pub fn map_huge(&mut self, vpn: VirtPageNum, ppn: PhysPageNum, flags: PTEFlags) {
+        let pte = self.find_pte_create_at(vpn, 1).unwrap();
+        // a table left behind by earlier 4K mappings is only dropped when empty
+        assert!(
+            !pte.is_valid()
+                || !pte.is_leaf() && pte.ppn().get_pte_array().iter().all(|pte| !pte.is_valid()),
+            "vpn {:?} is mapped before mapping",
+            vpn
+        );
+        *pte = PageTableEntry::new(ppn, flags | PTEFlags::V);
+    }
+    pub fn unmap_huge(&mut self, vpn: VirtPageNum) {
+        let pte = self.find_pte_create_at(vpn, 1).unwrap();
+        assert!(pte.is_leaf(), "vpn {:?} is not a megapage before unmapping", vpn);
+        *pte = PageTableEntry::empty();
+    }
+    /// The entry mapping `vpn`. Inside a megapage, it is made up to point at
+    /// the 4K frame of `vpn` so callers need not care about the page size.
     pub fn translate(&self, vpn: VirtPageNum) -> Option<PageTableEntry> {
-        self.find_pte(vpn).copied()
+        self.find_pte(vpn).map(|(pte, level)| {
+            if level == 2 {
+                *pte
+            } else {
+                let offset = vpn.0 & ((1usize << (9 * (2 - level))) - 1);
+                PageTableEntry::new(PhysPageNum(pte.ppn().0 + offset), pte.flags())
+            }
+        })
     }
+    /// Size of the page mapping `vpn`, `None` if it is not mapped
+    pub fn page_size(&self, vpn: VirtPageNum) -> Option<usize> {
+        self.find_pte(vpn)
+            .filter(|(pte, _)| pte.is_valid())
+            .map(|(_, level)| PAGE_SIZE << (9 * (2 - level)))
+    }
+    /// The physical address `va` maps to, `None` unless its page is mapped
     pub fn translate_va(&self, va: VirtAddr) -> Option<PhysAddr> {
-        self.find_pte(va.clone().floor()).map(|pte| {
-            //println!("translate_va:va = {:?}", va);
-            let aligned_pa: PhysAddr = pte.ppn().into();
-            //println!("translate_va:pa_align = {:?}", aligned_pa);
-            let offset = va.page_offset();
-            let aligned_pa_usize: usize = aligned_pa.into();
-            (aligned_pa_usize + offset).into()
-        })
+        self.translate(va.floor())
+            .filter(|pte| pte.is_valid())
+            .map(|pte| {
+                let aligned_pa: PhysAddr = pte.ppn().into();
+                (usize::from(aligned_pa) + va.page_offset()).into()
+            })
     }
     pub fn token(&self) -> usize {
         8usize << 60 | self.root_ppn.0
@@ -167,32 +209,90 @@ pub fn translated_byte_buffer(token: usize, ptr: *const u8, len: usize) -> Vec<&
     v
 }
 
+/// Copy `value` into user space at `ptr`, all or nothing.
+///
+/// Every page the destination touches is checked to be mapped, writable and
+/// user accessible before the first byte is written, so a pointer that runs
+/// into a bad page fails without leaving a partial write behind.
+pub fn copy_to_user<T>(token: usize, ptr: *mut T, value: &T) -> bool {
+    let bytes = unsafe {
+        core::slice::from_raw_parts(value as *const T as *const u8, core::mem::size_of::<T>())
+    };
+    copy_bytes_to_user(token, ptr as *mut u8, bytes)
+}
+
+/// Whether every page of `[start, start + len)` is mapped, writable and user accessible,
+/// false if the range wraps or ends above `USER_MMAP_END`
+pub fn user_writable(token: usize, start: usize, len: usize) -> bool {
+    user_pages_allow(token, start, len, PTEFlags::W | PTEFlags::U)
+}
+
+/// Whether every page of `[start, start + len)` is mapped, readable and user accessible,
+/// false if the range wraps or ends above `USER_MMAP_END`
+pub fn user_readable(token: usize, start: usize, len: usize) -> bool {
+    user_pages_allow(token, start, len, PTEFlags::R | PTEFlags::U)
+}
+
+fn user_pages_allow(token: usize, start: usize, len: usize, flags: PTEFlags) -> bool {
+    // a range that wraps around or leaves the user half is never allowed,
+    // whatever the pages at its ends look like
+    let end = match start.checked_add(len) {
+        Some(end) if end <= USER_MMAP_END => end,
+        _ => return false,
+    };
+    let page_table = PageTable::from_token(token);
+    let end_vpn = VirtAddr::from(end).ceil();
+    let mut vpn = VirtAddr::from(start).floor();
+    while vpn < end_vpn {
+        match page_table.translate(vpn) {
+            Some(pte) if pte.is_valid() && pte.flags().contains(flags) => {}
+            _ => return false,
+        }
+        vpn.step();
+    }
+    true
+}
+
+/// Copy `bytes` into user space at `ptr`, all or nothing like [`copy_to_user`]
+///
+/// The bytes are stored through the kernel's identity mapping of the frames,
+/// not the user's mapping; `trap_return` fences before user mode runs, so the
+/// user reads them fresh. A writer that bypasses this must return the same way.
+pub fn copy_bytes_to_user(token: usize, ptr: *mut u8, bytes: &[u8]) -> bool {
+    let start = ptr as usize;
+    let len = bytes.len();
+    if !user_writable(token, start, len) {
+        return false;
+    }
+    let mut copied = 0;
+    for buffer in translated_byte_buffer(token, start as *const u8, len) {
+        buffer.copy_from_slice(&bytes[copied..copied + buffer.len()]);
+        copied += buffer.len();
+    }
+    true
+}
+
+/// Read the NUL-terminated string at `ptr` out of user space.
+///
+/// Each page is translated once and only pages before the terminating NUL are
+/// touched, so an empty string never looks at the page after `ptr`.
 pub fn translated_str(token: usize, ptr: *const u8) -> String {
     let page_table = PageTable::from_token(token);
     let mut string = String::new();
-    let mut va = ptr as usize;
+    let mut va = VirtAddr::from(ptr as usize);
     loop {
-        let ch: u8 = *(page_table
-            .translate_va(VirtAddr::from(va))
-            .unwrap()
-            .get_mut());
-        if ch == 0 {
-            break;
-        } else {
-            string.push(ch as char);
-            va += 1;
+        let pa = page_table.translate_va(va).unwrap();
+        let bytes = &pa.floor().get_bytes_array()[pa.page_offset()..];
+        match bytes.iter().position(|&ch| ch == 0) {
+            Some(nul) => {
+                string.extend(bytes[..nul].iter().map(|&ch| ch as char));
+                break;
+            }
+            None => {
+                string.extend(bytes.iter().map(|&ch| ch as char));
+                va = VirtAddr::from(usize::from(va) + bytes.len());
+            }
         }
     }
     string
 }
-
-pub fn translated_refmut<T>(token: usize, ptr: *mut T) -> &'static mut T {
-    //println!("into translated_refmut!");
-    let page_table = PageTable::from_token(token);
-    let va = ptr as usize;
-    //println!("translated_refmut: before translate_va");
-    page_table
-        .translate_va(VirtAddr::from(va))
-        .unwrap()
-        .get_mut()
-}
@@ -0,0 +1,77 @@
+//! Implementation of [`SwapSlot`]
+//!
+//! Swapped out user pages are kept in a physical region at the top of memory,
+//! which the frame allocator never hands out, one page-sized slot each.
+
+use super::PhysPageNum;
+use crate::config::{PAGE_SIZE, SWAP_PAGES, SWAP_START};
+use crate::sync::UPSafeCell;
+use alloc::vec::Vec;
+use lazy_static::*;
+
+/// A slot of the swap region holding one page, freed when dropped
+pub struct SwapSlot {
+    index: usize,
+}
+
+impl SwapSlot {
+    /// The page kept in this slot
+    pub fn bytes(&self) -> &'static mut [u8] {
+        PhysPageNum(SWAP_START / PAGE_SIZE + self.index).get_bytes_array()
+    }
+}
+
+impl Drop for SwapSlot {
+    fn drop(&mut self) {
+        SWAP_ALLOCATOR.exclusive_access().dealloc(self.index);
+    }
+}
+
+/// Hands out slot indices like the frame allocator hands out frames
+struct SwapAllocator {
+    current: usize,
+    recycled: Vec<usize>,
+}
+
+impl SwapAllocator {
+    fn alloc(&mut self) -> Option<usize> {
+        if let Some(index) = self.recycled.pop() {
+            Some(index)
+        } else if self.current == SWAP_PAGES {
+            None
+        } else {
+            self.current += 1;
+            Some(self.current - 1)
+        }
+    }
+    fn dealloc(&mut self, index: usize) {
+        assert!(index < self.current, "swap slot {} has not been allocated!", index);
+        self.recycled.push(index);
+    }
+    fn remaining(&self) -> usize {
+        SWAP_PAGES - self.current + self.recycled.len()
+    }
+}
+
+lazy_static! {
+    /// swap slot allocator instance through lazy_static!
+    static ref SWAP_ALLOCATOR: UPSafeCell<SwapAllocator> = unsafe {
+        UPSafeCell::new(SwapAllocator {
+            current: 0,
+            recycled: Vec::new(),
+        })
+    };
+}
+
+/// Take a free slot of the swap region
+pub fn swap_alloc() -> Option<SwapSlot> {
+    SWAP_ALLOCATOR
+        .exclusive_access()
+        .alloc()
+        .map(|index| SwapSlot { index })
+}
+
+/// Number of free slots left in the swap region
+pub fn swap_remaining() -> usize {
+    SWAP_ALLOCATOR.exclusive_access().remaining()
+}
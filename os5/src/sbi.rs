@@ -6,6 +6,10 @@ const SBI_SET_TIMER: usize = 0;
 const SBI_CONSOLE_PUTCHAR: usize = 1;
 const SBI_CONSOLE_GETCHAR: usize = 2;
 const SBI_SHUTDOWN: usize = 8;
+/// System Reset extension ("SRST")
+const SBI_EXT_SRST: usize = 0x53525354;
+const SBI_SRST_RESET: usize = 0;
+const SBI_SRST_COLD_REBOOT: usize = 1;
 
 #[inline(always)]
 /// general sbi call
@@ -24,6 +28,22 @@ fn sbi_call(which: usize, arg0: usize, arg1: usize, arg2: usize) -> usize {
     ret
 }
 
+#[inline(always)]
+/// sbi call to an extension function, following the SBI v0.2+ calling convention
+fn sbi_call_ext(eid: usize, fid: usize, arg0: usize, arg1: usize) -> usize {
+    let mut ret;
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            inlateout("x10") arg0 => ret,
+            in("x11") arg1,
+            in("x16") fid,
+            in("x17") eid,
+        );
+    }
+    ret
+}
+
 /// use sbi call to set timer
 pub fn set_timer(timer: usize) {
     sbi_call(SBI_SET_TIMER, timer, 0, 0);
@@ -44,3 +64,9 @@ pub fn shutdown() -> ! {
     sbi_call(SBI_SHUTDOWN, 0, 0, 0);
     panic!("It should shutdown!");
 }
+
+/// use sbi SRST call to cold reboot the machine
+pub fn reboot() -> ! {
+    sbi_call_ext(SBI_EXT_SRST, SBI_SRST_RESET, SBI_SRST_COLD_REBOOT, 0);
+    panic!("It should reboot!");
+}
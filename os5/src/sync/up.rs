@@ -25,7 +25,14 @@ impl<T> UPSafeCell<T> {
         }
     }
     /// Panic if the data has been borrowed.
+    /// The panic reports the caller, which is the second borrower.
+    #[track_caller]
     pub fn exclusive_access(&self) -> RefMut<'_, T> {
         self.inner.borrow_mut()
     }
+    /// Like `exclusive_access`, but returns `None` instead of panicking
+    /// if the data has already been borrowed.
+    pub fn try_exclusive_access(&self) -> Option<RefMut<'_, T>> {
+        self.inner.try_borrow_mut().ok()
+    }
 }
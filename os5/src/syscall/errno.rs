@@ -0,0 +1,31 @@
+//! Error numbers returned by syscalls
+//!
+//! A failing syscall returns the negated error number, e.g. `-ENOMEM`.
+//! Every failure is still negative, so callers that only test for `< 0`
+//! keep working; only code comparing against `-1` exactly has to be updated.
+//! The values match Linux.
+
+/// Operation not permitted
+pub const EPERM: isize = 1;
+/// No such file or directory (e.g. no app with that name)
+pub const ENOENT: isize = 2;
+/// No such process
+pub const ESRCH: isize = 3;
+/// Interrupted by a signal
+pub const EINTR: isize = 4;
+/// No child processes
+pub const ECHILD: isize = 10;
+/// Out of memory
+pub const ENOMEM: isize = 12;
+/// Bad address
+pub const EFAULT: isize = 14;
+/// Resource busy
+pub const EBUSY: isize = 16;
+/// Region already exists
+pub const EEXIST: isize = 17;
+/// Invalid argument
+pub const EINVAL: isize = 22;
+/// Result does not fit in the buffer
+pub const ERANGE: isize = 34;
+/// Path too long
+pub const ENAMETOOLONG: isize = 36;
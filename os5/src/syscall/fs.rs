@@ -1,8 +1,12 @@
 //! File and filesystem-related syscalls
 
+use crate::console::print_stdout;
 use crate::mm::translated_byte_buffer;
 use crate::sbi::console_getchar;
-use crate::task::{current_user_token, suspend_current_and_run_next};
+use crate::task::processor::current_killed;
+use crate::task::{current_task, current_user_token, suspend_current_and_run_next};
+use super::errno::EINTR;
+use alloc::vec::Vec;
 
 const FD_STDIN: usize = 0;
 const FD_STDOUT: usize = 1;
@@ -11,9 +15,15 @@ pub fn sys_write(fd: usize, buf: *const u8, len: usize) -> isize {
     match fd {
         FD_STDOUT => {
             let buffers = translated_byte_buffer(current_user_token(), buf, len);
+            // lines go out whole, so output of different tasks never interleaves mid-line
+            let task = current_task().unwrap();
+            let mut inner = task.inner_exclusive_access();
+            let mut lines = Vec::new();
             for buffer in buffers {
-                print!("{}", core::str::from_utf8(buffer).unwrap());
+                lines.append(&mut inner.write_stdout(buffer));
             }
+            drop(inner);
+            print_stdout(&lines);
             len as isize
         }
         _ => {
@@ -26,16 +36,29 @@ pub fn sys_read(fd: usize, buf: *const u8, len: usize) -> isize {
     match fd {
         FD_STDIN => {
             assert_eq!(len, 1, "Only support len = 1 in sys_read!");
+            // make a pending prompt visible before waiting for input
+            let pending = current_task().unwrap().inner_exclusive_access().take_stdout();
+            print_stdout(&pending);
             let mut c: usize;
+            let mut waited = false;
             loop {
                 c = console_getchar();
                 if c == 0 {
+                    // a killed task exits on its way back to user mode, which
+                    // waiting for input here would put off forever
+                    if current_killed() {
+                        return -EINTR;
+                    }
+                    waited = true;
                     suspend_current_and_run_next();
                     continue;
                 } else {
                     break;
                 }
             }
+            if waited {
+                current_task().unwrap().inner_exclusive_access().apply_io_boost();
+            }
             let ch = c as u8;
             let mut buffers = translated_byte_buffer(current_user_token(), buf, len);
             unsafe {
@@ -48,3 +71,10 @@ pub fn sys_read(fd: usize, buf: *const u8, len: usize) -> isize {
         }
     }
 }
+
+/// Emit the partial line pending in the current task's stdout buffer
+pub fn sys_flush() -> isize {
+    let pending = current_task().unwrap().inner_exclusive_access().take_stdout();
+    print_stdout(&pending);
+    0
+}
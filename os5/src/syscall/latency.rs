@@ -0,0 +1,86 @@
+//! Per-syscall latency histogram
+//!
+//! Recording is off by default so that normal runs pay nothing but a flag
+//! check. The time of a syscall that switches away (yield, waitpid, sleep)
+//! includes the time other tasks ran before it returned.
+
+use super::errno::{EFAULT, EINVAL};
+use crate::config::MAX_SYSCALL_NUM;
+use crate::mm::copy_to_user;
+use crate::sync::UPSafeCell;
+use crate::task::current_user_token;
+use alloc::vec;
+use alloc::vec::Vec;
+use lazy_static::*;
+
+/// Bucket `i` counts calls that took less than `2^(i+1)` us, the last one everything longer
+pub const LATENCY_BUCKETS: usize = 16;
+
+/// Latency record of one syscall, as copied out to user space
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SyscallLatency {
+    pub count: usize,
+    pub total_us: usize,
+    pub buckets: [usize; LATENCY_BUCKETS],
+}
+
+impl SyscallLatency {
+    const fn zero() -> Self {
+        Self {
+            count: 0,
+            total_us: 0,
+            buckets: [0; LATENCY_BUCKETS],
+        }
+    }
+    fn record(&mut self, us: usize) {
+        self.count += 1;
+        self.total_us += us;
+        // floor(log2(us)), with 0 and 1 us both in the first bucket
+        let bucket = (usize::BITS - (us | 1).leading_zeros() - 1) as usize;
+        self.buckets[bucket.min(LATENCY_BUCKETS - 1)] += 1;
+    }
+}
+
+struct LatencyRecorder {
+    enabled: bool,
+    stats: Vec<SyscallLatency>,
+}
+
+lazy_static! {
+    static ref LATENCY: UPSafeCell<LatencyRecorder> = unsafe {
+        UPSafeCell::new(LatencyRecorder {
+            enabled: false,
+            stats: vec![SyscallLatency::zero(); MAX_SYSCALL_NUM],
+        })
+    };
+}
+
+pub fn latency_enabled() -> bool {
+    LATENCY.exclusive_access().enabled
+}
+
+pub fn record_latency(syscall_id: usize, us: usize) {
+    LATENCY.exclusive_access().stats[syscall_id].record(us);
+}
+
+/// Turn latency recording on or off, return the previous setting
+pub fn sys_enable_syscall_latency(enable: usize) -> isize {
+    let mut recorder = LATENCY.exclusive_access();
+    let previous = recorder.enabled;
+    recorder.enabled = enable != 0;
+    previous as isize
+}
+
+/// Copy the latency record of syscall `num` to `buf`
+pub fn sys_syscall_latency(num: usize, buf: *mut SyscallLatency) -> isize {
+    if num >= MAX_SYSCALL_NUM {
+        return -EINVAL;
+    }
+    let stats = LATENCY.exclusive_access().stats[num];
+    if copy_to_user(current_user_token(), buf, &stats) {
+        0
+    } else {
+        -EFAULT
+    }
+}
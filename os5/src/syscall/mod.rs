@@ -1,11 +1,17 @@
 
 
+const SYSCALL_GETCWD: usize = 17;
+const SYSCALL_CHDIR: usize = 49;
 const SYSCALL_READ: usize = 63;
 const SYSCALL_WRITE: usize = 64;
 const SYSCALL_EXIT: usize = 93;
 const SYSCALL_YIELD: usize = 124;
 const SYSCALL_GET_TIME: usize = 169;
+const SYSCALL_CLOCK_GETRES: usize = 114;
+const SYSCALL_SCHED_SETSCHEDULER: usize = 119;
+const SYSCALL_MEMBARRIER: usize = 283;
 const SYSCALL_GETPID: usize = 172;
+const SYSCALL_GETPPID: usize = 173;
 const SYSCALL_FORK: usize = 220;
 const SYSCALL_EXEC: usize = 221;
 const SYSCALL_WAITPID: usize = 260;
@@ -14,31 +20,194 @@ const SYSCALL_MUNMAP: usize = 215;
 const SYSCALL_MMAP: usize = 222;
 const SYSCALL_SET_PRIORITY: usize = 140;
 const SYSCALL_TASK_INFO: usize = 410;
+const SYSCALL_SET_CPU_BUDGET: usize = 411;
+const SYSCALL_NAME_REGION: usize = 412;
+const SYSCALL_DUMP_REGIONS: usize = 413;
+const SYSCALL_SETPRIORITY: usize = 414;
+const SYSCALL_FLUSH: usize = 415;
+const SYSCALL_REBOOT: usize = 142;
+const SYSCALL_MMAP_APP: usize = 416;
+const SYSCALL_KILL: usize = 129;
+const SYSCALL_TRANSLATE: usize = 417;
+const SYSCALL_SET_IO_BOOST: usize = 418;
+const SYSCALL_SLEEP: usize = 101;
+const SYSCALL_SET_TLS: usize = 419;
+const SYSCALL_GET_TLS: usize = 420;
+const SYSCALL_WAITPID_LIVELOCKS: usize = 421;
+const SYSCALL_ENABLE_SYSCALL_LATENCY: usize = 422;
+const SYSCALL_SYSCALL_LATENCY: usize = 423;
+const SYSCALL_MLOCK: usize = 228;
+const SYSCALL_MUNLOCK: usize = 229;
+const SYSCALL_MINCORE: usize = 232;
+const SYSCALL_GET_LIMIT: usize = 424;
+const SYSCALL_STRIDE_TRACE: usize = 425;
+const SYSCALL_PAGE_SIZE: usize = 426;
+const SYSCALL_PAUSE: usize = 427;
+const SYSCALL_SIGACTION: usize = 134;
+const SYSCALL_SIGRETURN: usize = 139;
+const SYSCALL_LIST_APPS: usize = 428;
+const SYSCALL_EXEC_MEM: usize = 429;
+const SYSCALL_WAITALL: usize = 430;
+const SYSCALL_SETITIMER: usize = 431;
+const SYSCALL_CAS: usize = 432;
+const SYSCALL_SET_TASK_NAME: usize = 433;
+const SYSCALL_GET_TASK_NAME: usize = 434;
+const SYSCALL_SHUTDOWN: usize = 435;
+const SYSCALL_SET_LOG_LEVEL: usize = 436;
+const SYSCALL_TASK_INFO_OF: usize = 437;
+const SYSCALL_RESERVE: usize = 438;
+const SYSCALL_COMMIT: usize = 439;
+const SYSCALL_CHECKPOINT: usize = 440;
+const SYSCALL_RESTORE: usize = 441;
+const SYSCALL_IS_DESCENDANT: usize = 442;
+const SYSCALL_SWAPOUT: usize = 443;
+const SYSCALL_GET_TIMES: usize = 444;
+const SYSCALL_CPU_RELAX: usize = 445;
+const SYSCALL_FORK_SP: usize = 446;
+const SYSCALL_SCHED_SET_DETERMINISTIC: usize = 447;
+const SYSCALL_WAIT3: usize = 448;
+const SYSCALL_RESTRICT_SYSCALLS: usize = 449;
+const SYSCALL_WAITQUEUE_STATS: usize = 450;
+const SYSCALL_DROP_PRIORITY_CEILING: usize = 451;
+const SYSCALL_ZOMBIE_COUNT: usize = 452;
+const SYSCALL_SCHED_EPOCH: usize = 453;
+const SYSCALL_SET_YIELD_MIN_RUNTIME: usize = 454;
+const SYSCALL_ADDRSPACE_CHECKSUM: usize = 455;
+const SYSCALL_SET_AUTO_REAP: usize = 456;
+const SYSCALL_KERNEL_ASSERT: usize = 457;
+const SYSCALL_SCHED_GETINTERVAL: usize = 458;
+const SYSCALL_TASK_INFO_V: usize = 459;
 
+pub mod errno;
 mod fs;
+mod latency;
 mod process;
 
 use fs::*;
+use latency::*;
 use process::*;
-use crate::task::processor::add_current_num;
+use crate::timer::get_time_us;
+use crate::task::processor::note_current_syscall;
+use crate::task::{current_task, exit_current_and_run_next};
+use crate::task::StrideEntry;
+use errno::ESRCH;
 /// handle syscall exception with `syscall_id` and other arguments
+///
+/// Every syscall acts on the calling task. Without a current task, as during
+/// boot or in the idle loop once the last task is gone, there is no caller:
+/// return -ESRCH instead of failing on the first `current_task().unwrap()`.
 pub fn syscall(syscall_id: usize, args: [usize; 3]) -> isize {
-    add_current_num(syscall_id);
-    match syscall_id {
+    if current_task().is_none() {
+        return -ESRCH;
+    }
+    // the user waitpid loop yields between two calls
+    let allowed = note_current_syscall(
+        syscall_id,
+        syscall_id == SYSCALL_WAITPID
+            || syscall_id == SYSCALL_WAIT3
+            || syscall_id == SYSCALL_YIELD
+            || syscall_id == SYSCALL_CPU_RELAX,
+    );
+    if !allowed {
+        println!("[kernel] syscall {} denied by the syscall filter, killed.", syscall_id);
+        // SIGSYS exit code
+        exit_current_and_run_next(-31);
+        unreachable!("a task killed by its syscall filter was scheduled again");
+    }
+    let start_us = latency_enabled().then(get_time_us);
+    let result = match syscall_id {
         SYSCALL_READ => sys_read(args[0], args[1] as *const u8, args[2]),
         SYSCALL_WRITE => sys_write(args[0], args[1] as *const u8, args[2]),
         SYSCALL_EXIT => sys_exit(args[0] as i32),
         SYSCALL_YIELD => sys_yield(),
         SYSCALL_GETPID => sys_getpid(),
+        SYSCALL_GETPPID => sys_getppid(),
         SYSCALL_FORK => sys_fork(),
-        SYSCALL_EXEC => sys_exec(args[0] as *const u8),
-        SYSCALL_WAITPID => sys_waitpid(args[0] as isize, args[1] as *mut i32),
+        SYSCALL_EXEC => sys_exec(args[0] as *const u8, args[2]),
+        SYSCALL_WAITPID => sys_waitpid(args[0] as isize, args[1] as *mut i32, args[2] as *mut usize),
         SYSCALL_GET_TIME => sys_get_time(args[0] as *mut TimeVal, args[1]),
         SYSCALL_MMAP => sys_mmap(args[0], args[1], args[2]),
         SYSCALL_MUNMAP => sys_munmap(args[0], args[1]),
         SYSCALL_SET_PRIORITY => sys_set_priority(args[0] as isize),
         SYSCALL_TASK_INFO => sys_task_info(args[0] as *mut TaskInfo),
         SYSCALL_SPAWN => sys_spawn(args[0] as *const u8),
+        SYSCALL_SET_CPU_BUDGET => sys_set_cpu_budget(args[0]),
+        SYSCALL_NAME_REGION => sys_name_region(args[0], args[1] as *const u8),
+        SYSCALL_DUMP_REGIONS => sys_dump_regions(),
+        SYSCALL_SETPRIORITY => sys_setpriority(args[0], args[1] as isize),
+        SYSCALL_FLUSH => sys_flush(),
+        SYSCALL_REBOOT => sys_reboot(),
+        SYSCALL_MMAP_APP => sys_mmap_app(args[0], args[1] as *const u8),
+        SYSCALL_KILL => sys_kill(args[0], args[1]),
+        SYSCALL_TRANSLATE => sys_translate(args[0]),
+        SYSCALL_SET_IO_BOOST => sys_set_io_boost(args[0]),
+        SYSCALL_SLEEP => sys_sleep(args[0]),
+        SYSCALL_SET_TLS => sys_set_tls(args[0]),
+        SYSCALL_GET_TLS => sys_get_tls(),
+        SYSCALL_WAITPID_LIVELOCKS => sys_waitpid_livelocks(),
+        SYSCALL_ENABLE_SYSCALL_LATENCY => sys_enable_syscall_latency(args[0]),
+        SYSCALL_SYSCALL_LATENCY => sys_syscall_latency(args[0], args[1] as *mut SyscallLatency),
+        SYSCALL_MINCORE => sys_mincore(args[0], args[1], args[2] as *mut u8),
+        SYSCALL_GET_LIMIT => sys_get_limit(args[0]),
+        SYSCALL_STRIDE_TRACE => sys_stride_trace(args[0] as *mut StrideEntry, args[1]),
+        SYSCALL_PAGE_SIZE => sys_page_size(args[0]),
+        SYSCALL_PAUSE => sys_pause(),
+        SYSCALL_SIGACTION => sys_sigaction(args[0], args[1]),
+        SYSCALL_SIGRETURN => sys_sigreturn(),
+        SYSCALL_LIST_APPS => sys_list_apps(args[0] as *mut u8, args[1]),
+        SYSCALL_EXEC_MEM => sys_exec_mem(args[0], args[1]),
+        SYSCALL_WAITALL => sys_waitall(args[0] as *mut WaitResult, args[1]),
+        SYSCALL_SETITIMER => sys_setitimer(args[0], args[1]),
+        SYSCALL_GETCWD => sys_getcwd(args[0] as *mut u8, args[1]),
+        SYSCALL_CHDIR => sys_chdir(args[0] as *const u8),
+        SYSCALL_CAS => sys_cas(args[0] as *mut usize, args[1], args[2]),
+        SYSCALL_SET_TASK_NAME => sys_set_task_name(args[0] as *const u8),
+        SYSCALL_GET_TASK_NAME => sys_get_task_name(args[0] as *mut u8, args[1]),
+        SYSCALL_SHUTDOWN => sys_shutdown(),
+        SYSCALL_SET_LOG_LEVEL => sys_set_log_level(args[0]),
+        SYSCALL_CLOCK_GETRES => sys_clock_getres(args[0], args[1] as *mut TimeVal),
+        SYSCALL_MEMBARRIER => sys_membarrier(),
+        SYSCALL_TASK_INFO_OF => sys_task_info_of(args[0], args[1] as *mut TaskInfo, args[2]),
+        SYSCALL_RESERVE => sys_reserve(args[0], args[1]),
+        SYSCALL_COMMIT => sys_commit(args[0], args[1], args[2]),
+        SYSCALL_CHECKPOINT => sys_checkpoint(args[0] as *mut Checkpoint, args[1]),
+        SYSCALL_RESTORE => sys_restore(args[0] as *const Checkpoint, args[1]),
+        SYSCALL_IS_DESCENDANT => sys_is_descendant(args[0]),
+        SYSCALL_SCHED_SETSCHEDULER => sys_sched_setscheduler(args[0]),
+        SYSCALL_SWAPOUT => sys_swapout(args[0], args[1]),
+        SYSCALL_GET_TIMES => sys_get_times(args[0] as *mut [TimeVal; 2]),
+        SYSCALL_CPU_RELAX => sys_cpu_relax(),
+        SYSCALL_FORK_SP => sys_fork_sp(args[0]),
+        SYSCALL_SCHED_SET_DETERMINISTIC => sys_sched_set_deterministic(args[0]),
+        SYSCALL_WAIT3 => sys_wait3(args[0] as isize, args[1] as *mut i32, args[2] as *mut RUsage),
+        SYSCALL_RESTRICT_SYSCALLS => sys_restrict_syscalls(args[0] as *const u8, args[1]),
+        SYSCALL_WAITQUEUE_STATS => sys_waitqueue_stats(args[0] as *mut WaitQueueStats),
+        SYSCALL_DROP_PRIORITY_CEILING => sys_drop_priority_ceiling(),
+        SYSCALL_ZOMBIE_COUNT => sys_zombie_count(args[0] as *mut usize),
+        SYSCALL_SCHED_EPOCH => sys_sched_epoch(),
+        SYSCALL_SET_YIELD_MIN_RUNTIME => sys_set_yield_min_runtime(args[0]),
+        SYSCALL_ADDRSPACE_CHECKSUM => sys_addrspace_checksum(args[0] as *mut u64),
+        SYSCALL_MLOCK => sys_mlock(args[0], args[1]),
+        SYSCALL_MUNLOCK => sys_munlock(args[0], args[1]),
+        SYSCALL_SET_AUTO_REAP => sys_set_auto_reap(args[0]),
+        SYSCALL_KERNEL_ASSERT => sys_kernel_assert(),
+        SYSCALL_SCHED_GETINTERVAL => sys_sched_getinterval(args[0]),
+        SYSCALL_TASK_INFO_V => sys_task_info_v(args[0] as *mut TaskInfo, args[1]),
         _ => panic!("Unsupported syscall_id: {}", syscall_id),
+    };
+    if let Some(start_us) = start_us {
+        record_latency(syscall_id, get_time_us() - start_us);
+    }
+    result
+}
+
+/// With no task running yet, syscalls fail with -ESRCH instead of panicking,
+/// exit included
+#[cfg(feature = "selftest")]
+pub fn no_current_task_test() {
+    assert!(current_task().is_none());
+    for syscall_id in [SYSCALL_GETPID, SYSCALL_YIELD, SYSCALL_WAITPID, SYSCALL_EXIT] {
+        assert_eq!(syscall(syscall_id, [0; 3]), -ESRCH);
     }
+    info!("no_current_task_test passed!");
 }
@@ -1,18 +1,44 @@
 //! Process management syscalls
 
-use crate::loader::get_app_data_by_name;
-use crate::mm::{translated_refmut, translated_str};
+use crate::console::print_stdout;
+use crate::loader::{app_names, get_app_data_by_name, max_app_size};
+use crate::mm::{
+    copy_bytes_to_user, copy_to_user, frame_remaining, translated_byte_buffer, translated_str,
+    user_readable, user_writable, MemorySet, StepByOne,
+};
 use crate::task::{
-    add_task, current_task, current_user_token, exit_current_and_run_next,
-    suspend_current_and_run_next, TaskStatus,
+    accepting_new_tasks, add_task, begin_shutdown, block_current_and_run_next, blocked_counts,
+    busy_dispatch_count, check_waitpid_livelock, current_task, current_user_token, deterministic,
+    enable_deterministic, exit_current_and_run_next, has_ready_tasks, note_reaped, pid2task,
+    sched_interval,
+    signal_catchable, signal_supported, stride_trace, suspend_current_and_run_next,
+    waitpid_livelock_count, wakeup_task, zombie_count, zombie_warning_count, BlockReason,
+    SchedPolicy, StrideEntry, TaskControlBlock, TaskStatus, INITPROC, SIGKILL,
+};
+use crate::logging::set_level;
+use crate::sbi::reboot;
+use crate::timer::{
+    add_interval_timer, add_timer, clock_resolution_us, get_time_ms, get_time_us, remove_timer,
+    REALTIME_OFFSET_US,
 };
-use crate::task::processor::{get_current_time,get_current_num,};
-use crate::timer::get_time_us;
+use alloc::string::String;
 use alloc::sync::Arc;
+use alloc::vec::Vec;
 use crate::config::MAX_SYSCALL_NUM;
-use crate::mm::{VirtAddr, PhysAddr, PageTable,PhysPageNum,};
-use crate::task::processor::{mmap_malloc,unmap_unalloc};
-use crate::config::BIG_STRIDE;
+use crate::mm::{MapPermission, PageTable, VirtAddr};
+use core::sync::atomic::{fence, AtomicUsize, Ordering as AtomicOrdering};
+use crate::task::processor::{
+    commit_reserved, current_pid, lock_range, mmap_malloc, reserve_range, swap_out_range,
+    unmap_unalloc,
+};
+use crate::config::{
+    BIG_STRIDE, KERNEL_STACK_SIZE, MAX_PATH_LEN, MAX_PROC_DEPTH, MAX_REGION_NAME_LEN,
+    MAX_TASK_NAME_LEN, PAGE_SIZE, USER_MMAP_END, USER_STACK_SIZE, ZOMBIE_WARN_THRESHOLD,
+};
+use super::errno::{
+    EBUSY, ECHILD, EEXIST, EFAULT, EINTR, EINVAL, ENAMETOOLONG, ENOENT, ENOMEM, EPERM, ERANGE,
+    ESRCH,
+};
 #[repr(C)]
 #[derive(Debug)]
 pub struct TimeVal {
@@ -20,6 +46,18 @@ pub struct TimeVal {
     pub usec: usize,
 }
 
+/// One child reaped by [`sys_waitall`]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct WaitResult {
+    pub pid: usize,
+    pub exit_code: i32,
+}
+
+/// Layout version of [`TaskInfo`], bumped whenever its fields change.
+/// Passed by callers of sys_task_info_v and sys_task_info_of
+pub const TASK_INFO_VERSION: usize = 1;
+
 #[derive(Clone, Copy)]
 pub struct TaskInfo {
     pub status: TaskStatus,
@@ -34,18 +72,87 @@ pub fn sys_exit(exit_code: i32) -> ! {
 }
 
 /// current task gives up resources for other tasks
+///
+/// Return 1 if another task ran before the caller got the CPU back, 0 if the
+/// caller was picked again straight away. Tasks spinning in sys_waitpid do not
+/// count, just as for the livelock check, so a yield loop can tell it is the
+/// only task with anything to do.
+///
+/// Under sys_set_yield_min_runtime, a yield that comes too soon after the
+/// caller was switched in returns 0 straight away without switching.
 pub fn sys_yield() -> isize {
+    {
+        let task = current_task().unwrap();
+        let inner = task.inner_exclusive_access();
+        if get_time_us() - inner.last_run_start < inner.yield_min_us {
+            return 0;
+        }
+    }
+    let before = busy_dispatch_count();
     suspend_current_and_run_next();
-    0
+    // the caller's own dispatch accounts for one
+    (busy_dispatch_count() - before > 1) as isize
+}
+
+/// Hint from a spin loop: give the CPU away if any other task is ready,
+/// otherwise return straight away. Return 1 if the caller yielded, 0 if not.
+pub fn sys_cpu_relax() -> isize {
+    if !has_ready_tasks() {
+        return 0;
+    }
+    suspend_current_and_run_next();
+    1
 }
 
+/// Read the pid cached when the task was switched in, borrowing neither the processor nor the task
 pub fn sys_getpid() -> isize {
-    current_task().unwrap().pid.0 as isize
+    current_pid().unwrap() as isize
+}
+
+/// Pid of the parent of the current task.
+/// initproc has no parent and gets 0, orphans have been handed over to initproc.
+pub fn sys_getppid() -> isize {
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    inner
+        .parent
+        .as_ref()
+        .and_then(|parent| parent.upgrade())
+        .map_or(0, |parent| parent.getpid() as isize)
 }
 
 /// Syscall Fork which returns 0 for child process and child_pid for parent process
 pub fn sys_fork() -> isize {
+    fork_current(None)
+}
+
+/// Like sys_fork, the child starts on the user stack ending at `new_sp`
+/// instead of a copy of the caller's. `new_sp` must be 16-byte aligned with
+/// the 16 bytes below it mapped and writable, else -EINVAL or -EFAULT.
+pub fn sys_fork_sp(new_sp: usize) -> isize {
+    if new_sp % 16 != 0 {
+        return -EINVAL;
+    }
+    match new_sp.checked_sub(16) {
+        Some(low) if user_writable(current_user_token(), low, 16) => fork_current(Some(new_sp)),
+        _ => -EFAULT,
+    }
+}
+
+fn fork_current(new_sp: Option<usize>) -> isize {
+    if !accepting_new_tasks() {
+        return -EBUSY;
+    }
     let current_task = current_task().unwrap();
+    if current_task.inner_exclusive_access().depth >= MAX_PROC_DEPTH {
+        return -EPERM;
+    }
+    // the child copies every frame of the parent and needs its own kernel stack
+    let frames_needed =
+        current_task.inner_exclusive_access().memory_set.frame_count() + KERNEL_STACK_SIZE / PAGE_SIZE;
+    if frame_remaining() < frames_needed {
+        return -ENOMEM;
+    }
     let new_task = current_task.fork();
     let new_pid = new_task.pid.0;
     // modify trap context of new_task, because it returns immediately after switching
@@ -53,38 +160,119 @@ pub fn sys_fork() -> isize {
     // we do not have to move to next instruction since we have done it before
     // for child process, fork returns 0
     trap_cx.x[10] = 0;
+    if let Some(sp) = new_sp {
+        trap_cx.set_sp(sp);
+    }
     // add new task to scheduler
     add_task(new_task);
     new_pid as isize
 }
 
-/// Syscall Exec which accepts the elf path
-pub fn sys_exec(path: *const u8) -> isize {
+/// `flags` bit of exec keeping the syscall counters of the old image. A new
+/// image is a new program, so by default its task_info counts start at 0
+pub const EXEC_KEEP_SYSCALL_TIMES: usize = 1;
+
+/// Syscall Exec which accepts the elf path. Return -ENOENT if there is no such
+/// app and -EINVAL for an unknown bit in `flags` or an image that does not load.
+pub fn sys_exec(path: *const u8, flags: usize) -> isize {
+    if flags & !EXEC_KEEP_SYSCALL_TIMES != 0 {
+        return -EINVAL;
+    }
     let token = current_user_token();
     let path = translated_str(token, path);
-    if let Some(data) = get_app_data_by_name(path.as_str()) {
-        let task = current_task().unwrap();
-        task.exec(data);
-        0
-    } else {
-        -1
+    if path.is_empty() {
+        return -ENOENT;
+    }
+    match get_app_data_by_name(path.as_str()) {
+        // a bad image fails before the caller is touched
+        Some(data) if !MemorySet::elf_is_valid(data) => -EINVAL,
+        Some(data) => {
+            let task = current_task().unwrap();
+            task.exec(&path, data, flags & EXEC_KEEP_SYSCALL_TIMES != 0);
+            0
+        }
+        None => -ENOENT,
+    }
+}
+
+/// Exec the ELF image at `[addr, addr + len)` of the caller's own space.
+/// Return -EINVAL if the buffer is empty, larger than the largest embedded app
+/// or does not hold a loadable ELF, -EFAULT if it is not readable and -ENOMEM
+/// if it does not fit in the kernel heap.
+pub fn sys_exec_mem(addr: usize, len: usize) -> isize {
+    let token = current_user_token();
+    if len == 0 || len > max_app_size() {
+        return -EINVAL;
+    }
+    if !user_readable(token, addr, len) {
+        return -EFAULT;
+    }
+    // copied out first, the exec drops the space it lives in
+    let mut data = Vec::new();
+    if data.try_reserve_exact(len).is_err() {
+        return -ENOMEM;
     }
+    for buffer in translated_byte_buffer(token, addr as *const u8, len) {
+        data.extend_from_slice(buffer);
+    }
+    if !MemorySet::elf_is_valid(&data) {
+        return -EINVAL;
+    }
+    // there is no app name to take, keep the current one
+    let task = current_task().unwrap();
+    let name = task.inner_exclusive_access().name.clone();
+    task.exec(&name, &data, false);
+    0
 }
 
-/// If there is not a child process whose pid is same as given, return -1.
+/// If there is not a child process whose pid is same as given, the caller's own
+/// pid included, return -ECHILD.
 /// Else if there is a child process but it is still running, return -2.
-pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
+/// If `cpu_time_ptr` is not null, the CPU time of the reaped child in us is written to it.
+/// A null `exit_code_ptr` skips the exit code. A non-null out pointer that is not
+/// writable fails with -EFAULT and the child stays unreaped.
+pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32, cpu_time_ptr: *mut usize) -> isize {
+    wait_child(pid, exit_code_ptr, cpu_time_ptr, core::ptr::null_mut())
+}
+
+/// Resources used by a reaped child, see [`sys_wait3`]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct RUsage {
+    /// CPU time in us
+    pub cpu_time: usize,
+    /// Peak size of the address space in KiB, page tables included
+    pub max_rss: usize,
+}
+
+/// Like sys_waitpid, reporting the resource usage of the reaped child to
+/// `rusage_ptr` instead of its CPU time alone. Either out pointer may be null.
+pub fn sys_wait3(pid: isize, exit_code_ptr: *mut i32, rusage_ptr: *mut RUsage) -> isize {
+    wait_child(pid, exit_code_ptr, core::ptr::null_mut(), rusage_ptr)
+}
+
+fn wait_child(
+    pid: isize,
+    exit_code_ptr: *mut i32,
+    cpu_time_ptr: *mut usize,
+    rusage_ptr: *mut RUsage,
+) -> isize {
     let task = current_task().unwrap();
+    // a task is never its own child, answer before looking at the children
+    if pid >= 0 && pid as usize == task.getpid() {
+        return -ECHILD;
+    }
     // find a child process
 
     // ---- access current TCB exclusively
     let mut inner = task.inner_exclusive_access();
+    inner.waiting_for = None;
     if !inner
         .children
         .iter()
         .any(|p| pid == -1 || pid as usize == p.getpid())
     {
-        return -1;
+        return -ECHILD;
         // ---- release current PCB
     }
     let pair = inner.children.iter().enumerate().find(|(_, p)| {
@@ -93,79 +281,175 @@ pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
         // ++++ release child PCB
     });
     if let Some((idx, _)) = pair {
+        // check the out pointers before reaping, so a bad one leaves the zombie in place
+        let token = inner.memory_set.token();
+        if !out_ptr_ok(token, exit_code_ptr)
+            || !out_ptr_ok(token, cpu_time_ptr)
+            || !out_ptr_ok(token, rusage_ptr)
+        {
+            return -EFAULT;
+        }
         let child = inner.children.remove(idx);
         // confirm that child will be deallocated after removing from children list
         assert_eq!(Arc::strong_count(&child), 1);
+        note_reaped();
         let found_pid = child.getpid();
         // ++++ temporarily access child TCB exclusively
         let exit_code = child.inner_exclusive_access().exit_code;
+        let cpu_time = child.inner_exclusive_access().cpu_time;
+        let max_rss = child.inner_exclusive_access().max_rss_pages * PAGE_SIZE / 1024;
         // ++++ release child PCB
-        *translated_refmut(inner.memory_set.token(), exit_code_ptr) = exit_code;
+        if !exit_code_ptr.is_null() {
+            copy_to_user(token, exit_code_ptr, &exit_code);
+        }
+        if !cpu_time_ptr.is_null() {
+            copy_to_user(token, cpu_time_ptr, &cpu_time);
+        }
+        if !rusage_ptr.is_null() {
+            copy_to_user(token, rusage_ptr, &RUsage { cpu_time, max_rss });
+        }
         found_pid as isize
     } else {
+        inner.waiting_for = Some(pid);
+        drop(inner);
+        check_waitpid_livelock(&task);
         -2
     }
     // ---- release current PCB lock automatically
 }
 
+/// A null out pointer means the caller does not want the value
+fn out_ptr_ok<T>(token: usize, ptr: *mut T) -> bool {
+    ptr.is_null() || user_writable(token, ptr as usize, core::mem::size_of::<T>())
+}
+
 // YOUR JOB: 引入虚地址后重写 sys_get_time
-pub fn sys_get_time(_ts: *mut TimeVal, _tz: usize) -> isize {
-    
-    let _us = get_time_us();
-    let page_table = PageTable::from_token(current_user_token());
-    let ptr = _ts as usize;
-    let va = VirtAddr::from(ptr);
-    let vpn = va.floor();
-    let ppn = page_table.translate(vpn).unwrap().ppn();
-    let buffers = ppn.get_bytes_array();
-    let offset = va.page_offset();
-    let sec = _us / 1_000_000_000;
-    let usec = _us %1_000_000_000;
-    buffers[0+offset] = (sec&0xff) as u8;
-    buffers[1+offset] = ((sec>>8)&0xff) as u8;
-    buffers[2+offset] = ((sec>>16)&0xff) as u8;
-    buffers[3+offset] = ((sec>>24)&0xff) as u8;
-
-    buffers[8+offset] = (usec&0xff) as u8;
-    buffers[9+offset] = ((usec>>8)&0xff) as u8;
-    buffers[10+offset] = ((usec>>16)&0xff) as u8;
-    buffers[11+offset] = ((usec>>24)&0xff) as u8;
-    
-    0
+/// `tz` is obsolete and must be zero and `ts` must be aligned for `TimeVal`,
+/// otherwise -EINVAL is returned. A `ts` not entirely in writable user memory
+/// fails with -EFAULT. Nothing is written on failure.
+pub fn sys_get_time(ts: *mut TimeVal, tz: usize) -> isize {
+    if tz != 0 || ts as usize % core::mem::align_of::<TimeVal>() != 0 {
+        return -EINVAL;
+    }
+    let us = get_time_us() + REALTIME_OFFSET_US;
+    let time_val = TimeVal {
+        sec: us / 1_000_000,
+        usec: us % 1_000_000,
+    };
+    if copy_to_user(current_user_token(), ts, &time_val) {
+        0
+    } else {
+        -EFAULT
+    }
+}
+
+/// Write `CLOCK_MONOTONIC` and `CLOCK_REALTIME` to `buf`, in that order, both
+/// from a single read of the clock. A bad `buf` fails like in sys_get_time.
+pub fn sys_get_times(buf: *mut [TimeVal; 2]) -> isize {
+    if buf as usize % core::mem::align_of::<TimeVal>() != 0 {
+        return -EINVAL;
+    }
+    let us = get_time_us();
+    let time_val = |us: usize| TimeVal {
+        sec: us / 1_000_000,
+        usec: us % 1_000_000,
+    };
+    let times = [time_val(us), time_val(us + REALTIME_OFFSET_US)];
+    if copy_to_user(current_user_token(), buf, &times) {
+        0
+    } else {
+        -EFAULT
+    }
+}
+
+/// Write the resolution of `clock_id` to `res`. Return -EINVAL for an unknown
+/// clock, a bad `res` fails like in sys_get_time.
+pub fn sys_clock_getres(clock_id: usize, res: *mut TimeVal) -> isize {
+    let us = match clock_resolution_us(clock_id) {
+        Some(us) => us,
+        None => return -EINVAL,
+    };
+    if res as usize % core::mem::align_of::<TimeVal>() != 0 {
+        return -EINVAL;
+    }
+    let time_val = TimeVal {
+        sec: us / 1_000_000,
+        usec: us % 1_000_000,
+    };
+    if copy_to_user(current_user_token(), res, &time_val) {
+        0
+    } else {
+        -EFAULT
+    }
+}
+
+/// Copy the task_info of `task` to `ti` of the current task, false if `ti` is bad
+fn copy_task_info(task: &TaskControlBlock, ti: *mut TaskInfo) -> bool {
+    // a single borrow of the task, the syscall counts are copied once
+    let task_info = {
+        let inner = task.inner_exclusive_access();
+        TaskInfo {
+            status: inner.task_status,
+            syscall_times: inner.call_num,
+            time: inner.run_time_ms(),
+        }
+    };
+    copy_to_user(current_user_token(), ti, &task_info)
 }
 
 // YOUR JOB: 引入虚地址后重写 sys_task_info
 pub fn sys_task_info(ti: *mut TaskInfo) -> isize {
-     
-    let page_table = PageTable::from_token(current_user_token());
-    let ptr = ti as usize;
-    let va = VirtAddr::from(ptr);
-    let vpn = va.floor();
-    let ppn = page_table.translate(vpn).unwrap().ppn();
-    let offset = va.page_offset();
-    let pa:PhysAddr = PhysAddr::from(ppn);
-    unsafe {
-        let task_info = ((pa.0 + offset) as *mut TaskInfo).as_mut().unwrap();
-        let tmp = TaskInfo{
-            status: TaskStatus::Running,
-            syscall_times: get_current_num(),
-            time: get_time_us()/1000 - get_current_time(),
-        };
-        *task_info = tmp;
-    }
-    
-    0
+    if copy_task_info(&current_task().unwrap(), ti) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// sys_task_info for callers that state the `TASK_INFO_VERSION` they were
+/// built against; any other value means their `TaskInfo` has a different
+/// layout, so -EINVAL is returned and nothing is written. A bad `ti` fails
+/// with -EFAULT.
+pub fn sys_task_info_v(ti: *mut TaskInfo, version: usize) -> isize {
+    sys_task_info_of(current_task().unwrap().getpid(), ti, version)
+}
+
+/// Like sys_task_info_v, for the task `pid`: the caller or a task in the
+/// subtree below it, zombies included. Return -EPERM for any other live
+/// task and -ESRCH if there is none.
+pub fn sys_task_info_of(pid: usize, ti: *mut TaskInfo, version: usize) -> isize {
+    if version != TASK_INFO_VERSION {
+        return -EINVAL;
+    }
+    let current = current_task().unwrap();
+    let target = if pid == current.getpid() {
+        current
+    } else {
+        match current.find_descendant(pid) {
+            Some(target) => target,
+            None if pid2task(pid).is_some() => return -EPERM,
+            None => return -ESRCH,
+        }
+    };
+    if copy_task_info(&target, ti) {
+        0
+    } else {
+        -EFAULT
+    }
 }
 
 // YOUR JOB: 实现sys_set_priority，为任务添加优先级
+/// A priority above the ceiling of the task fails with -EPERM
 pub fn sys_set_priority(_prio: isize) -> isize {
     if _prio <=1 {
         return -1
     }
     let task = current_task().unwrap();
     let mut inner = task.inner_exclusive_access();
-    inner.priority = _prio;
-    inner.stride = BIG_STRIDE/(_prio as u32);
+    if !inner.priority_allowed(_prio) {
+        return -EPERM;
+    }
+    inner.set_priority(_prio);
     _prio
 }
 
@@ -178,19 +462,844 @@ pub fn sys_munmap(_start: usize, _len: usize) -> isize {
     unmap_unalloc(_start,_len)
 }
 
+/// Reserve address space without frames, overlapping mmaps then fail
+pub fn sys_reserve(start: usize, len: usize) -> isize {
+    reserve_range(start, len)
+}
+
+/// Back part of a reservation with frames
+pub fn sys_commit(start: usize, len: usize, port: usize) -> isize {
+    commit_reserved(start, len, port)
+}
+
+/// Evict pages to swap space, they are brought back on the next access
+pub fn sys_swapout(start: usize, len: usize) -> isize {
+    swap_out_range(start, len)
+}
+
+/// Keep the pages of `[start, start + len)` resident, swapping them back in if
+/// needed; sys_swapout skips them until sys_munlock
+pub fn sys_mlock(start: usize, len: usize) -> isize {
+    lock_range(start, len, true)
+}
+
+/// Let sys_swapout move the pages of `[start, start + len)` again
+pub fn sys_munlock(start: usize, len: usize) -> isize {
+    lock_range(start, len, false)
+}
+
 //
 // YOUR JOB: 实现 sys_spawn 系统调用
 // ALERT: 注意在实现 SPAWN 时不需要复制父进程地址空间，SPAWN != FORK + EXEC 
 pub fn sys_spawn(_path: *const u8) -> isize {
     let token = current_user_token();
     let path = translated_str(token, _path);
+    if path.is_empty() {
+        return -ENOENT;
+    }
+    if !accepting_new_tasks() {
+        return -EBUSY;
+    }
     if let Some(data) = get_app_data_by_name(path.as_str()) {
         let task = current_task().unwrap();
-        let new_task =  task.spawn(data);
+        if task.inner_exclusive_access().depth >= MAX_PROC_DEPTH {
+            return -EPERM;
+        }
+        let new_task =  task.spawn(&path, data);
         let pid = new_task.pid.0;
         add_task(new_task);
         pid as isize
+    } else {
+        -ENOENT
+    }
+}
+
+/// Limit the current task to `ticks` timer ticks of CPU time, 0 means unlimited.
+/// A task running past its budget is killed by the timer interrupt.
+pub fn sys_set_cpu_budget(ticks: usize) -> isize {
+    let task = current_task().unwrap();
+    task.inner_exclusive_access().cpu_budget = ticks;
+    0
+}
+
+/// Make sys_yield a no-op until the caller has run for `us` since it was last
+/// switched in, so a task yielding in a tight loop does not switch on every
+/// call. 0 turns this off. The timer still preempts the task every tick.
+/// Return the previous minimum.
+pub fn sys_set_yield_min_runtime(us: usize) -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    core::mem::replace(&mut inner.yield_min_us, us) as isize
+}
+
+/// Label the memory area covering `start`, the name is truncated to `MAX_REGION_NAME_LEN` chars
+pub fn sys_name_region(start: usize, name: *const u8) -> isize {
+    let token = current_user_token();
+    let name: String = translated_str(token, name)
+        .chars()
+        .take(MAX_REGION_NAME_LEN)
+        .collect();
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    if inner.memory_set.name_area(VirtAddr::from(start), name) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// Print the memory map of the current task
+pub fn sys_dump_regions() -> isize {
+    let task = current_task().unwrap();
+    println!(
+        "[kernel] memory map of pid {} ({}):",
+        task.getpid(),
+        task.inner_exclusive_access().name
+    );
+    task.inner_exclusive_access().memory_set.dump();
+    0
+}
+
+/// Set the priority of task `pid`, which must be the caller itself or one of its
+/// children, else -EPERM. -EINVAL for a priority below 2, -ESRCH if there is no such task.
+pub fn sys_setpriority(pid: usize, prio: isize) -> isize {
+    if prio <= 1 {
+        return -EINVAL;
+    }
+    let target = match pid2task(pid) {
+        Some(target) => target,
+        None => return -ESRCH,
+    };
+    let current_pid = current_task().unwrap().getpid();
+    let mut inner = target.inner_exclusive_access();
+    let parent_pid = inner
+        .parent
+        .as_ref()
+        .and_then(|parent| parent.upgrade())
+        .map(|parent| parent.getpid());
+    if pid != current_pid && parent_pid != Some(current_pid) {
+        return -EPERM;
+    }
+    if !inner.priority_allowed(prio) {
+        return -EPERM;
+    }
+    inner.set_priority(prio);
+    prio
+}
+
+/// Make the current priority of the caller the highest it and all its future
+/// descendants may ever take. A lower ceiling set earlier stays. Return it.
+pub fn sys_drop_priority_ceiling() -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    let ceiling = inner
+        .priority_ceiling
+        .map_or(inner.priority, |ceiling| ceiling.min(inner.priority));
+    inner.priority_ceiling = Some(ceiling);
+    ceiling
+}
+
+/// 1 if `pid` is in the subtree below the caller, 0 if it is some other task
+/// (the caller included), -1 if no task has that pid
+pub fn sys_is_descendant(pid: usize) -> isize {
+    let task = current_task().unwrap();
+    if task.has_descendant(pid) {
+        1
+    } else if pid == task.getpid() || pid2task(pid).is_some() {
+        0
     } else {
         -1
     }
 }
+
+pub const SCHED_STRIDE: usize = 0;
+pub const SCHED_RR: usize = 1;
+
+/// Move the caller between stride scheduling and the round-robin queue,
+/// see `TaskManager::fetch` for how the two share the CPU
+pub fn sys_sched_setscheduler(policy: usize) -> isize {
+    let policy = match policy {
+        SCHED_STRIDE => SchedPolicy::Stride,
+        SCHED_RR => SchedPolicy::RoundRobin,
+        _ => return -EINVAL,
+    };
+    // the caller is running, it joins its new queue the next time it is suspended
+    current_task().unwrap().inner_exclusive_access().sched_policy = policy;
+    0
+}
+
+/// Reboot the machine, only initproc is allowed to do so
+pub fn sys_reboot() -> isize {
+    let task = current_task().unwrap();
+    if !Arc::ptr_eq(&task, &INITPROC) {
+        return -1;
+    }
+    let pending = task.inner_exclusive_access().take_stdout();
+    print_stdout(&pending);
+    println!("[kernel] Rebooting...");
+    reboot()
+}
+
+/// Map a read-only copy of the ELF image of app `name` at `start`, return its length.
+/// Unlike mmap this does not return `start`: the caller picked it, while the
+/// length is what it needs to use the image and has no other way to learn.
+/// Errors are those of mmap, plus -ENOENT if there is no such app.
+pub fn sys_mmap_app(start: usize, name: *const u8) -> isize {
+    if start % PAGE_SIZE != 0 {
+        return -EINVAL;
+    }
+    let name = translated_str(current_user_token(), name);
+    let data = match get_app_data_by_name(name.as_str()) {
+        Some(data) => data,
+        None => return -ENOENT,
+    };
+    let end = match start.checked_add(data.len()) {
+        Some(end) if end <= USER_MMAP_END => end,
+        _ => return -ENOMEM,
+    };
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    if inner.memory_set.check_va_overlap(start, end) {
+        return -EEXIST;
+    }
+    inner.memory_set.insert_framed_area_with_data(
+        VirtAddr::from(start),
+        MapPermission::R | MapPermission::U,
+        data,
+    );
+    data.len() as isize
+}
+
+/// Send `signal` to task `pid`. Signal 0 only checks that the task exists.
+///
+/// The target is not torn down here but flagged, and exits (or runs its
+/// handler) the next time it is about to return to user mode.
+pub fn sys_kill(pid: usize, signal: usize) -> isize {
+    if !signal_supported(signal) {
+        return -EINVAL;
+    }
+    let target = match pid2task(pid) {
+        Some(target) => target,
+        None => return -ESRCH,
+    };
+    if Arc::ptr_eq(&target, &INITPROC) {
+        return -EPERM;
+    }
+    if signal == 0 {
+        return 0;
+    }
+    if signal == SIGKILL {
+        target.inner_exclusive_access().killed = true;
+    } else {
+        target.inner_exclusive_access().pending_signals |= 1 << signal;
+    }
+    // a paused or sleeping target has to run to see the signal
+    remove_timer(&target);
+    wakeup_task(target);
+    0
+}
+
+/// Debug query: the physical page number `va` is mapped to in the caller's space
+pub fn sys_translate(va: usize) -> isize {
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    match inner.memory_set.translate(VirtAddr::from(va).floor()) {
+        Some(pte) if pte.is_valid() => pte.ppn().0 as isize,
+        _ => -EFAULT,
+    }
+}
+
+/// Turn deterministic scheduling on with a nonzero `enabled`, see
+/// `enable_deterministic`. It is set once for the whole run: turning it off
+/// again returns -EPERM. Return the previous setting.
+pub fn sys_sched_set_deterministic(enabled: usize) -> isize {
+    if enabled != 0 {
+        enable_deterministic() as isize
+    } else if deterministic() {
+        -EPERM
+    } else {
+        0
+    }
+}
+
+/// Enable or disable the I/O wait boost of the current task, return the previous setting
+pub fn sys_set_io_boost(enable: usize) -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    let previous = inner.io_boost;
+    inner.io_boost = enable != 0;
+    previous as isize
+}
+
+/// Block the current task for `sleep_ms` milliseconds.
+/// A signal cuts the sleep short with -EINTR.
+pub fn sys_sleep(sleep_ms: usize) -> isize {
+    let expire_ms = get_time_ms() + sleep_ms;
+    add_timer(expire_ms, current_task().unwrap());
+    block_current_and_run_next(BlockReason::Sleep);
+    if get_time_ms() < expire_ms {
+        -EINTR
+    } else {
+        0
+    }
+}
+
+/// Set the thread pointer of the current task.
+///
+/// `tp` (x4) is saved and restored with the rest of the trap context, so
+/// writing it there keeps it correct across every switch away and back.
+/// The kernel itself never touches `tp`.
+pub fn sys_set_tls(ptr: usize) -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    inner.tls = ptr;
+    inner.get_trap_cx().x[4] = ptr;
+    0
+}
+
+/// Get the thread pointer last set by the current task
+pub fn sys_get_tls() -> isize {
+    current_task().unwrap().inner_exclusive_access().tls as isize
+}
+
+/// Write to `hash` a 64-bit hash of all mapped user pages of the caller, the
+/// address of each page included, so forked spaces hash the same until either
+/// writes. One area is not part of "all mapped user pages": the one holding
+/// the caller's stack pointer, as making the call itself keeps changing it.
+/// Return 0, -EINVAL if `hash` is misaligned or -EFAULT if it is not writable.
+pub fn sys_addrspace_checksum(hash: *mut u64) -> isize {
+    if hash as usize % core::mem::align_of::<u64>() != 0 {
+        return -EINVAL;
+    }
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    let sp = inner.get_trap_cx().x[2];
+    let value = inner.memory_set.checksum(VirtAddr::from(sp).floor());
+    if copy_to_user(inner.memory_set.token(), hash, &value) {
+        0
+    } else {
+        -EFAULT
+    }
+}
+
+/// Number of times the caller has been switched in so far, this run included
+pub fn sys_sched_epoch() -> isize {
+    current_task().unwrap().inner_exclusive_access().sched_epoch as isize
+}
+
+/// Number of waitpid livelocks the kernel has detected so far
+pub fn sys_waitpid_livelocks() -> isize {
+    waitpid_livelock_count() as isize
+}
+
+/// Free children of the caller as soon as they exit instead of leaving zombies
+/// for waitpid, which then reports -ECHILD once all are gone. Children that
+/// are zombies already stay. Return the previous setting.
+pub fn sys_set_auto_reap(enabled: usize) -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    core::mem::replace(&mut inner.auto_reap, enabled != 0) as isize
+}
+
+/// Approximate number of ticks between two runs of task `pid` given the
+/// tasks ready right now, derived from its stride against theirs.
+/// -ESRCH if there is no such task, -EINVAL if it is not stride scheduled.
+pub fn sys_sched_getinterval(pid: usize) -> isize {
+    let current = current_task().unwrap();
+    let target = match pid2task(pid) {
+        Some(target) => target,
+        None => return -ESRCH,
+    };
+    if target.inner_exclusive_access().sched_policy != SchedPolicy::Stride {
+        return -EINVAL;
+    }
+    sched_interval(&target, &current) as isize
+}
+
+/// Fail a kernel assert on purpose, to check what the panic handler dumps.
+/// Only with the `panic-test` feature, -1 otherwise.
+pub fn sys_kernel_assert() -> isize {
+    if cfg!(feature = "panic-test") {
+        panic!("deliberate kernel assert from sys_kernel_assert");
+    }
+    -1
+}
+
+/// Number of exited tasks not reaped yet. If `warnings` is not null, the number
+/// of zombie warnings the kernel has printed so far is written there.
+pub fn sys_zombie_count(warnings: *mut usize) -> isize {
+    let token = current_user_token();
+    if !out_ptr_ok(token, warnings) {
+        return -EFAULT;
+    }
+    if !warnings.is_null() {
+        copy_to_user(token, warnings, &zombie_warning_count());
+    }
+    zombie_count() as isize
+}
+
+/// Write one byte per page of `[start, start + len)` to `vec`: 1 if the page
+/// is backed by a frame, 0 if it is not, as when it is swapped out. Fail with
+/// -EINVAL if the range wraps and -ENOMEM if any page is not mapped at all.
+pub fn sys_mincore(start: usize, len: usize, vec: *mut u8) -> isize {
+    if start % PAGE_SIZE != 0 {
+        return -EINVAL;
+    }
+    let end = match start.checked_add(len) {
+        Some(end) => end,
+        None => return -EINVAL,
+    };
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    let start_vpn = VirtAddr::from(start).floor();
+    let end_vpn = VirtAddr::from(end).ceil();
+    let mut residency = Vec::new();
+    let mut vpn = start_vpn;
+    while vpn < end_vpn {
+        match inner.memory_set.is_resident(vpn) {
+            Some(resident) => residency.push(resident as u8),
+            None => return -ENOMEM,
+        }
+        vpn.step();
+    }
+    if copy_bytes_to_user(inner.get_user_token(), vec, &residency) {
+        0
+    } else {
+        -EFAULT
+    }
+}
+
+/// Limits understood by [`sys_get_limit`]
+pub const LIMIT_MAX_SYSCALL_NUM: usize = 0;
+pub const LIMIT_PAGE_SIZE: usize = 1;
+pub const LIMIT_BIG_STRIDE: usize = 2;
+pub const LIMIT_USER_STACK_SIZE: usize = 3;
+pub const LIMIT_MAX_PROC_DEPTH: usize = 4;
+pub const LIMIT_REALTIME_OFFSET_US: usize = 5;
+pub const LIMIT_ZOMBIE_WARN_THRESHOLD: usize = 6;
+
+/// Query a kernel build constant so user code does not have to hardcode it.
+/// There is no fixed task limit in this kernel, tasks are bounded by memory only.
+pub fn sys_get_limit(which: usize) -> isize {
+    match which {
+        LIMIT_MAX_SYSCALL_NUM => MAX_SYSCALL_NUM as isize,
+        LIMIT_PAGE_SIZE => PAGE_SIZE as isize,
+        LIMIT_BIG_STRIDE => BIG_STRIDE as isize,
+        LIMIT_USER_STACK_SIZE => USER_STACK_SIZE as isize,
+        LIMIT_MAX_PROC_DEPTH => MAX_PROC_DEPTH as isize,
+        LIMIT_REALTIME_OFFSET_US => REALTIME_OFFSET_US as isize,
+        LIMIT_ZOMBIE_WARN_THRESHOLD => ZOMBIE_WARN_THRESHOLD as isize,
+        _ => -EINVAL,
+    }
+}
+
+/// Copy the stride state of up to `max` ready tasks to `buf`. The caller itself
+/// is running and so not part of the trace. Return the number of ready tasks,
+/// which may be more than were copied.
+pub fn sys_stride_trace(buf: *mut StrideEntry, max: usize) -> isize {
+    let entries = stride_trace();
+    let copied = &entries[..entries.len().min(max)];
+    let bytes = unsafe {
+        core::slice::from_raw_parts(
+            copied.as_ptr() as *const u8,
+            copied.len() * core::mem::size_of::<StrideEntry>(),
+        )
+    };
+    if copy_bytes_to_user(current_user_token(), buf as *mut u8, bytes) {
+        entries.len() as isize
+    } else {
+        -EFAULT
+    }
+}
+
+/// Debug query: size of the page mapping `va` in the caller's space, 4K or 2M
+pub fn sys_page_size(va: usize) -> isize {
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    match inner.memory_set.page_size(VirtAddr::from(va).floor()) {
+        Some(size) => size as isize,
+        None => -EFAULT,
+    }
+}
+
+/// Block until a signal arrives. SIGKILL makes the task exit before this
+/// returns to user mode, other signals run their handler first and give -EINTR.
+pub fn sys_pause() -> isize {
+    block_current_and_run_next(BlockReason::Pause);
+    -EINTR
+}
+
+/// Run `handler` when `signum` is delivered, 0 restores the default action.
+/// Return the previous handler.
+pub fn sys_sigaction(signum: usize, handler: usize) -> isize {
+    if !signal_catchable(signum) {
+        return -EINVAL;
+    }
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    let previous = inner.signal_handlers[signum];
+    inner.signal_handlers[signum] = handler;
+    previous as isize
+}
+
+/// Resume the code the innermost running signal handler interrupted,
+/// with every register as it was
+pub fn sys_sigreturn() -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    match inner.signal_stack.pop() {
+        Some(frame) => {
+            *inner.get_trap_cx() = frame.trap_cx;
+            // the syscall result lands in a0, so hand back the interrupted one
+            frame.trap_cx.x[10] as isize
+        }
+        None => -EINVAL,
+    }
+}
+
+/// Saved user registers and pc, the kernel half of the trap context is left out
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Checkpoint {
+    pub x: [usize; 32],
+    pub sepc: usize,
+}
+
+/// Save the caller's registers and pc to `buf` and return 0. Restoring it
+/// later returns from this call a second time, with 1 instead.
+pub fn sys_checkpoint(buf: *mut Checkpoint, len: usize) -> isize {
+    if len != core::mem::size_of::<Checkpoint>() {
+        return -EINVAL;
+    }
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    let trap_cx = inner.get_trap_cx();
+    // sepc is already past the ecall
+    let mut checkpoint = Checkpoint {
+        x: trap_cx.x,
+        sepc: trap_cx.sepc,
+    };
+    checkpoint.x[10] = 1;
+    if copy_to_user(inner.get_user_token(), buf, &checkpoint) {
+        0
+    } else {
+        -EFAULT
+    }
+}
+
+/// Load the registers and pc saved by `sys_checkpoint` from `buf`
+pub fn sys_restore(buf: *const Checkpoint, len: usize) -> isize {
+    if len != core::mem::size_of::<Checkpoint>() {
+        return -EINVAL;
+    }
+    let token = current_user_token();
+    if !user_readable(token, buf as usize, len) {
+        return -EFAULT;
+    }
+    let mut bytes = Vec::with_capacity(len);
+    for buffer in translated_byte_buffer(token, buf as *const u8, len) {
+        bytes.extend_from_slice(buffer);
+    }
+    let checkpoint = unsafe { (bytes.as_ptr() as *const Checkpoint).read_unaligned() };
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    let trap_cx = inner.get_trap_cx();
+    trap_cx.x = checkpoint.x;
+    trap_cx.x[0] = 0;
+    trap_cx.sepc = checkpoint.sepc;
+    // the syscall result lands in a0, so hand back the saved one
+    trap_cx.x[10] as isize
+}
+
+/// Number of blocked tasks by what they wait for, see [`sys_waitqueue_stats`]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct WaitQueueStats {
+    /// In sys_sleep, on the timer queue
+    pub sleep: usize,
+    /// In sys_pause, until a signal arrives
+    pub pause: usize,
+}
+
+/// Copy the number of tasks blocked on each kind of wait to `stats`,
+/// return how many are blocked in all
+pub fn sys_waitqueue_stats(stats: *mut WaitQueueStats) -> isize {
+    let counts = blocked_counts();
+    let value = WaitQueueStats {
+        sleep: counts[BlockReason::Sleep as usize],
+        pause: counts[BlockReason::Pause as usize],
+    };
+    if copy_to_user(current_user_token(), stats, &value) {
+        counts.iter().sum::<usize>() as isize
+    } else {
+        -EFAULT
+    }
+}
+
+/// Allow the caller only the syscalls whose bit is set in the `len` byte
+/// bitmap at `bitmap`, bit `id % 8` of byte `id / 8` standing for syscall
+/// `id`. Any other syscall kills the task. Applied on top of an earlier
+/// filter, so a task can only narrow what it is allowed. -EINVAL if the
+/// bitmap is longer than `MAX_SYSCALL_NUM` bits need, -EFAULT if unreadable.
+pub fn sys_restrict_syscalls(bitmap: *const u8, len: usize) -> isize {
+    let filter_len = (MAX_SYSCALL_NUM + 7) / 8;
+    if len > filter_len {
+        return -EINVAL;
+    }
+    let token = current_user_token();
+    if !user_readable(token, bitmap as usize, len) {
+        return -EFAULT;
+    }
+    let mut filter = Vec::with_capacity(filter_len);
+    for buffer in translated_byte_buffer(token, bitmap, len) {
+        filter.extend_from_slice(buffer);
+    }
+    filter.resize(filter_len, 0);
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    if let Some(previous) = inner.syscall_filter.as_ref() {
+        for (byte, allowed) in filter.iter_mut().zip(previous) {
+            *byte &= allowed;
+        }
+    }
+    inner.syscall_filter = Some(filter);
+    0
+}
+
+/// Write the names of the embedded apps to `buf`, each followed by a NUL.
+/// Only whole names that fit in `len` bytes are written; return how many.
+pub fn sys_list_apps(buf: *mut u8, len: usize) -> isize {
+    let mut names = Vec::new();
+    let mut count = 0;
+    for name in app_names() {
+        if names.len() + name.len() + 1 > len {
+            break;
+        }
+        names.extend_from_slice(name.as_bytes());
+        names.push(0);
+        count += 1;
+    }
+    if copy_bytes_to_user(current_user_token(), buf, &names) {
+        count
+    } else {
+        -EFAULT
+    }
+}
+
+/// Reap up to `max` zombie children in one go, oldest child first, writing
+/// their pids and exit codes to `buf`. Return how many were reaped, 0 if none
+/// is ready, or -ECHILD if there are no children at all.
+pub fn sys_waitall(buf: *mut WaitResult, max: usize) -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    if inner.children.is_empty() {
+        return -ECHILD;
+    }
+    let ready = inner
+        .children
+        .iter()
+        .filter(|p| p.inner_exclusive_access().is_zombie())
+        .count()
+        .min(max);
+    let token = inner.memory_set.token();
+    // check the whole buffer first, so a bad one leaves every zombie in place
+    if !user_writable(token, buf as usize, ready * core::mem::size_of::<WaitResult>()) {
+        return -EFAULT;
+    }
+    let mut results = Vec::with_capacity(ready);
+    let mut idx = 0;
+    while results.len() < ready {
+        if !inner.children[idx].inner_exclusive_access().is_zombie() {
+            idx += 1;
+            continue;
+        }
+        let child = inner.children.remove(idx);
+        // confirm that child will be deallocated after removing from children list
+        assert_eq!(Arc::strong_count(&child), 1);
+        note_reaped();
+        let exit_code = child.inner_exclusive_access().exit_code;
+        results.push(WaitResult {
+            pid: child.getpid(),
+            exit_code,
+        });
+    }
+    let bytes = unsafe {
+        core::slice::from_raw_parts(
+            results.as_ptr() as *const u8,
+            results.len() * core::mem::size_of::<WaitResult>(),
+        )
+    };
+    copy_bytes_to_user(token, buf as *mut u8, bytes);
+    ready as isize
+}
+
+/// Arm the interval timer of the current task: SIGALRM is raised after
+/// `value_ms`, then every `interval_ms` if it is not 0. A `value_ms` of 0
+/// disarms the timer. Return the ms left on the previous timer, 0 if disarmed.
+pub fn sys_setitimer(interval_ms: usize, value_ms: usize) -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    let current_ms = get_time_ms();
+    let previous = if inner.itimer_deadline_ms == 0 {
+        0
+    } else {
+        // an expired deadline has not been seen by check_timer yet, report 1ms
+        inner.itimer_deadline_ms.saturating_sub(current_ms).max(1)
+    };
+    if value_ms == 0 {
+        inner.itimer_deadline_ms = 0;
+        inner.itimer_interval_ms = 0;
+    } else {
+        let deadline = current_ms + value_ms;
+        inner.itimer_deadline_ms = deadline;
+        inner.itimer_interval_ms = interval_ms;
+        drop(inner);
+        add_interval_timer(deadline, &task);
+    }
+    previous as isize
+}
+
+/// Resolve `path` against `cwd` into an absolute path without `.`, `..` or
+/// repeated slashes. `..` at the root stays at the root.
+fn resolve_path(cwd: &str, path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    if !path.starts_with('/') {
+        parts.extend(cwd.split('/').filter(|part| !part.is_empty()));
+    }
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    if parts.is_empty() {
+        return String::from("/");
+    }
+    let mut resolved = String::new();
+    for part in parts {
+        resolved.push('/');
+        resolved.push_str(part);
+    }
+    resolved
+}
+
+/// Change the current working directory. There is no filesystem yet, so any
+/// path is accepted as long as it and its resolved form fit in `MAX_PATH_LEN`.
+pub fn sys_chdir(path: *const u8) -> isize {
+    let path = translated_str(current_user_token(), path);
+    if path.is_empty() {
+        return -ENOENT;
+    }
+    if path.len() > MAX_PATH_LEN {
+        return -ENAMETOOLONG;
+    }
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    let cwd = resolve_path(&inner.cwd, &path);
+    if cwd.len() > MAX_PATH_LEN {
+        return -ENAMETOOLONG;
+    }
+    inner.cwd = cwd;
+    0
+}
+
+/// Copy the current working directory with a trailing NUL to `buf`.
+/// Return the bytes written, NUL included, or -ERANGE if `len` is too small.
+pub fn sys_getcwd(buf: *mut u8, len: usize) -> isize {
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    let mut cwd = inner.cwd.clone().into_bytes();
+    cwd.push(0);
+    if cwd.len() > len {
+        return -ERANGE;
+    }
+    if copy_bytes_to_user(inner.get_user_token(), buf, &cwd) {
+        cwd.len() as isize
+    } else {
+        -EFAULT
+    }
+}
+
+/// Atomically replace the user word at `addr` with `new` if it holds
+/// `expected`, return the value it held. `addr` must be word aligned and
+/// writable, else -EFAULT, which a caller expecting negative values must
+/// tell apart by checking the word itself.
+pub fn sys_cas(addr: *mut usize, expected: usize, new: usize) -> isize {
+    let token = current_user_token();
+    let va = addr as usize;
+    if va % core::mem::size_of::<usize>() != 0
+        || !user_writable(token, va, core::mem::size_of::<usize>())
+    {
+        return -EFAULT;
+    }
+    let pa = PageTable::from_token(token)
+        .translate_va(VirtAddr::from(va))
+        .unwrap();
+    // the kernel maps physical memory identically, so this is the user's word
+    let word: &AtomicUsize = pa.get_mut();
+    match word.compare_exchange(expected, new, AtomicOrdering::SeqCst, AtomicOrdering::SeqCst) {
+        Ok(previous) | Err(previous) => previous as isize,
+    }
+}
+
+/// Rename the current task, the name is truncated to `MAX_TASK_NAME_LEN` chars
+pub fn sys_set_task_name(name: *const u8) -> isize {
+    let name: String = translated_str(current_user_token(), name)
+        .chars()
+        .take(MAX_TASK_NAME_LEN)
+        .collect();
+    if name.is_empty() {
+        return -EINVAL;
+    }
+    current_task().unwrap().inner_exclusive_access().name = name;
+    0
+}
+
+/// Copy the name of the current task with a trailing NUL to `buf`.
+/// Return the bytes written, NUL included, or -ERANGE if `len` is too small.
+pub fn sys_get_task_name(buf: *mut u8, len: usize) -> isize {
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    let mut name = inner.name.clone().into_bytes();
+    name.push(0);
+    if name.len() > len {
+        return -ERANGE;
+    }
+    if copy_bytes_to_user(inner.get_user_token(), buf, &name) {
+        name.len() as isize
+    } else {
+        -EFAULT
+    }
+}
+
+/// Start shutting the machine down: fork and spawn fail from now on, and the
+/// machine powers off once the caller exits, so it can still wait for its
+/// children and flush its output. Fail with -EBUSY if a shutdown is under way.
+pub fn sys_shutdown() -> isize {
+    if begin_shutdown(current_task().unwrap().getpid()) {
+        0
+    } else {
+        -EBUSY
+    }
+}
+
+/// Set the kernel log level, 0 (off) to 5 (trace), return the previous one.
+/// Scheduler switches are traced at level 5.
+pub fn sys_set_log_level(level: usize) -> isize {
+    match set_level(level) {
+        Some(previous) => previous as isize,
+        None => -EINVAL,
+    }
+}
+
+/// Order every memory access of the caller before the call against every
+/// access of any task after it. With a single hart a full fence and a switch
+/// to the next ready task is enough; SMP would also have to fence other harts.
+pub fn sys_membarrier() -> isize {
+    fence(AtomicOrdering::SeqCst);
+    suspend_current_and_run_next();
+    0
+}
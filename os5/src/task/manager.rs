@@ -4,14 +4,36 @@
 //! Other CPU process monitoring functions are in Processor.
 
 
-use super::TaskControlBlock;
+use super::{BlockReason, SchedPolicy, TaskControlBlock};
+use crate::config::{ZOMBIE_WARN_INTERVAL_MS, ZOMBIE_WARN_THRESHOLD};
 use crate::sync::UPSafeCell;
-use alloc::collections::VecDeque;
+use crate::timer::{get_time_ms, timers_pending};
+use alloc::collections::{BTreeMap, VecDeque};
 use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use lazy_static::*;
 use crate::task::TaskStatus;
 pub struct TaskManager {
     ready_queue: VecDeque<Arc<TaskControlBlock>>,
+    /// Ready tasks under `SchedPolicy::RoundRobin`, served in FIFO order
+    rr_queue: VecDeque<Arc<TaskControlBlock>>,
+    /// Whether the next dispatch goes to the round-robin queue
+    rr_turn: bool,
+    /// Whether the current waitpid livelock has already been reported
+    livelock_reported: bool,
+    /// Number of waitpid livelocks detected so far
+    livelock_count: usize,
+}
+
+/// Scheduler state of one ready task, as copied out by sys_stride_trace
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct StrideEntry {
+    pub pid: usize,
+    pub pass: usize,
+    pub stride: usize,
+    pub priority: isize,
 }
 
 // YOUR JOB: FIFO->Stride
@@ -20,14 +42,42 @@ impl TaskManager {
     pub fn new() -> Self {
         Self {
             ready_queue: VecDeque::new(),
+            rr_queue: VecDeque::new(),
+            rr_turn: false,
+            livelock_reported: false,
+            livelock_count: 0,
         }
     }
     /// Add process back to ready queue
     pub fn add(&mut self, task: Arc<TaskControlBlock>) {
-        self.ready_queue.push_back(task);
+        let policy = task.inner_exclusive_access().sched_policy;
+        if deterministic() {
+            self.ready_queue.push_back(task);
+            return;
+        }
+        match policy {
+            SchedPolicy::Stride => self.ready_queue.push_back(task),
+            SchedPolicy::RoundRobin => self.rr_queue.push_back(task),
+        }
     }
-    /// Take a process out of the ready queue
+    /// Take a process out of the ready queue. While both queues hold tasks,
+    /// dispatches alternate between them: the round-robin tasks together get
+    /// half the dispatches and the stride tasks share the other half by pass.
     pub fn fetch(&mut self) -> Option<Arc<TaskControlBlock>> {
+        if deterministic() {
+            // tasks queued before the switch join the stride order
+            let rr_queue = core::mem::take(&mut self.rr_queue);
+            self.ready_queue.extend(rr_queue);
+        }
+        let take_rr =
+            !self.rr_queue.is_empty() && (self.rr_turn || self.ready_queue.is_empty());
+        self.rr_turn = !take_rr;
+        if take_rr {
+            return self.rr_queue.pop_front();
+        }
+        if self.ready_queue.is_empty() {
+            return None;
+        }
         let len = self.ready_queue.len();
         let mut min_pass:u32= u32::MAX;
         let mut index = 0;
@@ -39,7 +89,7 @@ impl TaskManager {
                 index = i;
             }
             else{
-                let diff:i32 = (task.inner_exclusive_access().pass - min_pass) as i32;
+                let diff:i32 = task.inner_exclusive_access().pass.wrapping_sub(min_pass) as i32;
                 if  diff<0{
                     index = i;
                     min_pass = element.unwrap().inner_exclusive_access().pass;
@@ -51,18 +101,287 @@ impl TaskManager {
         ele.unwrap().inner_exclusive_access().add_pass();
         self.ready_queue.remove(index)
     }
+    /// Stride state of every task in the ready queue, in queue order
+    pub fn stride_trace(&self) -> Vec<StrideEntry> {
+        self.ready_queue
+            .iter()
+            .map(|task| {
+                let inner = task.inner_exclusive_access();
+                StrideEntry {
+                    pid: task.getpid(),
+                    pass: inner.pass as usize,
+                    stride: inner.stride as usize,
+                    priority: inner.priority,
+                }
+            })
+            .collect()
+    }
+    /// Approximate number of ticks between two runs of `target` when it
+    /// competes with the stride tasks in the ready queue and `current`:
+    /// its stride times the sum of the inverse strides of all of them.
+    pub fn sched_interval(&self, target: &TaskControlBlock, current: &TaskControlBlock) -> usize {
+        let mut counted: Vec<usize> = Vec::new();
+        let mut share_sum: u128 = 0;
+        let mut count = |task: &TaskControlBlock| {
+            let inner = task.inner_exclusive_access();
+            if inner.sched_policy == SchedPolicy::Stride && !counted.contains(&task.getpid()) {
+                counted.push(task.getpid());
+                // 1 / stride in 32.32 fixed point
+                share_sum += (1u128 << 32) / inner.stride as u128;
+            }
+        };
+        self.ready_queue.iter().for_each(|task| count(task));
+        count(current);
+        count(target);
+        let stride = target.inner_exclusive_access().stride as u128;
+        (((stride * share_sum + (1 << 31)) >> 32) as usize).max(1)
+    }
+    /// Called by `current`, a task about to spin in sys_waitpid. With no task
+    /// blocked and no timer armed nothing can wake a task up; if then every
+    /// ready task spins in sys_waitpid for children that spin there as well or
+    /// are gone, nothing runnable can make progress: report it once, until
+    /// that no longer holds.
+    pub fn check_waitpid_livelock(&mut self, current: &TaskControlBlock) {
+        let stuck = blocked_counts().iter().all(|&count| count == 0)
+            && !timers_pending()
+            && waits_in_vain(current)
+            && self
+                .ready_queue
+                .iter()
+                .chain(self.rr_queue.iter())
+                .all(|task| waits_in_vain(task));
+        if !stuck {
+            self.livelock_reported = false;
+            return;
+        }
+        if self.livelock_reported {
+            return;
+        }
+        self.livelock_reported = true;
+        self.livelock_count += 1;
+        println!(
+            "[kernel] waitpid livelock: pid {} waits for {:?}, all other ready tasks wait in vain too",
+            current.getpid(),
+            current.inner_exclusive_access().waiting_for
+        );
+        for task in self.ready_queue.iter().chain(self.rr_queue.iter()) {
+            println!(
+                "[kernel]   pid {} waits for {:?}",
+                task.getpid(),
+                task.inner_exclusive_access().waiting_for
+            );
+        }
+    }
+}
+
+/// Whether `task` spins in sys_waitpid for nothing: every child it waits for
+/// spins there as well, and none is a zombie to reap. A waited-for pid with no
+/// such child left, say one reaped automatically, counts as nothing too.
+fn waits_in_vain(task: &TaskControlBlock) -> bool {
+    let inner = task.inner_exclusive_access();
+    let pid = match inner.waiting_for {
+        Some(pid) => pid,
+        None => return false,
+    };
+    inner
+        .children
+        .iter()
+        .filter(|child| pid == -1 || child.getpid() == pid as usize)
+        .all(|child| {
+            let child_inner = child.inner_exclusive_access();
+            !child_inner.is_zombie() && child_inner.waiting_for.is_some()
+        })
 }
 
 lazy_static! {
     /// TASK_MANAGER instance through lazy_static!
     pub static ref TASK_MANAGER: UPSafeCell<TaskManager> =
         unsafe { UPSafeCell::new(TaskManager::new()) };
+    /// PID2TCB instance (map of pid to alive tasks) through lazy_static!
+    pub static ref PID2TCB: UPSafeCell<BTreeMap<usize, Arc<TaskControlBlock>>> =
+        unsafe { UPSafeCell::new(BTreeMap::new()) };
+}
+
+/// `SHUTDOWN_INITIATOR` while no shutdown has been started
+const NO_SHUTDOWN: usize = usize::MAX;
+
+/// Pid of the task that started the shutdown, see [`begin_shutdown`]
+static SHUTDOWN_INITIATOR: AtomicUsize = AtomicUsize::new(NO_SHUTDOWN);
+
+/// Start a shutdown on behalf of `pid`: no new tasks are created from now on,
+/// and the machine powers off once `pid` exits. Fail if one is already under way.
+pub fn begin_shutdown(pid: usize) -> bool {
+    SHUTDOWN_INITIATOR
+        .compare_exchange(NO_SHUTDOWN, pid, Ordering::SeqCst, Ordering::SeqCst)
+        .is_ok()
+}
+
+/// Whether fork and spawn may create tasks, false once a shutdown has started
+pub fn accepting_new_tasks() -> bool {
+    SHUTDOWN_INITIATOR.load(Ordering::SeqCst) == NO_SHUTDOWN
+}
+
+/// Pid of the task whose exit completes the shutdown, if one has started
+pub fn shutdown_initiator() -> Option<usize> {
+    match SHUTDOWN_INITIATOR.load(Ordering::SeqCst) {
+        NO_SHUTDOWN => None,
+        pid => Some(pid),
+    }
+}
+
+/// Set once deterministic scheduling is on, see [`enable_deterministic`]
+static DETERMINISTIC: AtomicBool = AtomicBool::new(false);
+
+/// Schedule every task by stride alone, ties going to the task queued first:
+/// round-robin policies and I/O boosts are ignored from now on. There is no
+/// way back, so a run started in this mode stays reproducible to the end.
+/// Return whether the mode was already on.
+pub fn enable_deterministic() -> bool {
+    DETERMINISTIC.swap(true, Ordering::SeqCst)
+}
+
+/// Whether deterministic scheduling is on
+pub fn deterministic() -> bool {
+    DETERMINISTIC.load(Ordering::SeqCst)
+}
+
+/// Number of kinds of [`BlockReason`]
+pub const BLOCK_REASONS: usize = 2;
+
+/// Number of tasks blocked for each [`BlockReason`]
+static BLOCKED: [AtomicUsize; BLOCK_REASONS] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+
+/// Count a task blocking for `reason`. Blocked tasks are still alive, so
+/// there can never be more of them than live tasks; more means a count leaked.
+pub fn note_blocked(reason: BlockReason) {
+    BLOCKED[reason as usize].fetch_add(1, Ordering::SeqCst);
+    debug_assert!(
+        blocked_counts().iter().sum::<usize>() <= PID2TCB.exclusive_access().len(),
+        "more tasks blocked than alive"
+    );
+}
+
+/// Count a task woken up from `reason`
+pub fn note_unblocked(reason: BlockReason) {
+    BLOCKED[reason as usize].fetch_sub(1, Ordering::SeqCst);
 }
 
+/// Number of tasks blocked for each reason, indexed by `BlockReason as usize`
+pub fn blocked_counts() -> [usize; BLOCK_REASONS] {
+    [
+        BLOCKED[BlockReason::Sleep as usize].load(Ordering::SeqCst),
+        BLOCKED[BlockReason::Pause as usize].load(Ordering::SeqCst),
+    ]
+}
+
+/// Number of exited tasks their parent has not reaped yet
+static ZOMBIES: AtomicUsize = AtomicUsize::new(0);
+/// Number of zombie warnings printed so far
+static ZOMBIE_WARNINGS: AtomicUsize = AtomicUsize::new(0);
+/// When the last zombie warning was printed, in ms
+static LAST_ZOMBIE_WARNING_MS: AtomicUsize = AtomicUsize::new(0);
+
+/// Count a task that has exited and waits for its parent
+pub fn note_zombie() {
+    ZOMBIES.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Count a zombie taken off its parent's children list
+pub fn note_reaped() {
+    ZOMBIES.fetch_sub(1, Ordering::SeqCst);
+}
+
+pub fn zombie_count() -> usize {
+    ZOMBIES.load(Ordering::SeqCst)
+}
+
+pub fn zombie_warning_count() -> usize {
+    ZOMBIE_WARNINGS.load(Ordering::SeqCst)
+}
+
+/// Run on every timer tick. Zombies piling up past the threshold usually mean
+/// some parent forgets to waitpid, so say so, but not more than once per interval.
+pub fn check_zombie_leak() {
+    let zombies = zombie_count();
+    if zombies < ZOMBIE_WARN_THRESHOLD {
+        return;
+    }
+    let now = get_time_ms();
+    if zombie_warning_count() > 0
+        && now - LAST_ZOMBIE_WARNING_MS.load(Ordering::SeqCst) < ZOMBIE_WARN_INTERVAL_MS
+    {
+        return;
+    }
+    LAST_ZOMBIE_WARNING_MS.store(now, Ordering::SeqCst);
+    ZOMBIE_WARNINGS.fetch_add(1, Ordering::SeqCst);
+    println!(
+        "[kernel] {} zombie tasks are waiting to be reaped, is a waitpid missing?",
+        zombies
+    );
+}
+
+lazy_static! {
+    /// Auto-reaped tasks, still running on their kernel stack when they exited
+    static ref REAPED: UPSafeCell<Vec<Arc<TaskControlBlock>>> =
+        unsafe { UPSafeCell::new(Vec::new()) };
+}
+
+/// Keep an exited task nobody waits for until `free_reaped`; the exit path
+/// cannot free it while it is still on the task's kernel stack
+pub fn defer_free(task: Arc<TaskControlBlock>) {
+    REAPED.exclusive_access().push(task);
+}
+
+/// Free the tasks handed to `defer_free`, from off their kernel stacks
+pub fn free_reaped() {
+    let reaped = core::mem::take(&mut *REAPED.exclusive_access());
+    drop(reaped);
+}
+
+/// Make `task` ready, it was registered under its pid when it was created
 pub fn add_task(task: Arc<TaskControlBlock>) {
     TASK_MANAGER.exclusive_access().add(task);
 }
 
+/// Look up an alive task by pid
+pub fn pid2task(pid: usize) -> Option<Arc<TaskControlBlock>> {
+    let map = PID2TCB.exclusive_access();
+    map.get(&pid).map(Arc::clone)
+}
+
+/// Forget an exited task
+pub fn remove_from_pid2task(pid: usize) {
+    let mut map = PID2TCB.exclusive_access();
+    if map.remove(&pid).is_none() {
+        panic!("cannot find pid {} in pid2task!", pid);
+    }
+}
+
+pub fn check_waitpid_livelock(current: &TaskControlBlock) {
+    TASK_MANAGER.exclusive_access().check_waitpid_livelock(current);
+}
+
+/// Number of waitpid livelocks detected so far
+pub fn waitpid_livelock_count() -> usize {
+    TASK_MANAGER.exclusive_access().livelock_count
+}
+
+/// Whether any task is waiting in a ready queue
+pub fn has_ready_tasks() -> bool {
+    let manager = TASK_MANAGER.exclusive_access();
+    !manager.ready_queue.is_empty() || !manager.rr_queue.is_empty()
+}
+
+pub fn stride_trace() -> Vec<StrideEntry> {
+    TASK_MANAGER.exclusive_access().stride_trace()
+}
+
+/// Ticks between two runs of `target` given the current ready set,
+/// see `TaskManager::sched_interval`
+pub fn sched_interval(target: &TaskControlBlock, current: &TaskControlBlock) -> usize {
+    TASK_MANAGER.exclusive_access().sched_interval(target, current)
+}
+
 pub fn fetch_task() -> Option<Arc<TaskControlBlock>> {
     TASK_MANAGER.exclusive_access().fetch()
 }
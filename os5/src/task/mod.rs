@@ -4,22 +4,39 @@ mod context;
 mod manager;
 mod pid;
 pub mod processor;
+mod signal;
 mod switch;
 #[allow(clippy::module_inception)]
 mod task;
 
+use crate::console::print_stdout;
 use crate::loader::get_app_data_by_name;
+use crate::sbi::shutdown;
 use alloc::sync::Arc;
 use lazy_static::*;
-use manager::fetch_task;
+use manager::{
+    defer_free, fetch_task, free_reaped, note_blocked, note_unblocked, note_zombie,
+};
 use switch::__switch;
-pub use task::{TaskControlBlock, TaskStatus};
+pub use task::{BlockReason, SchedPolicy, TaskControlBlock, TaskStatus};
 
 pub use context::TaskContext;
-pub use manager::add_task;
+pub use manager::{
+    accepting_new_tasks, add_task, begin_shutdown, blocked_counts, check_waitpid_livelock,
+    check_zombie_leak, deterministic, enable_deterministic, has_ready_tasks, note_reaped,
+    pid2task, remove_from_pid2task, sched_interval, shutdown_initiator, stride_trace,
+    waitpid_livelock_count, zombie_count, zombie_warning_count, StrideEntry, BLOCK_REASONS,
+};
+#[cfg(feature = "selftest")]
+pub use pid::kernel_stack_pool_test;
 pub use pid::{pid_alloc, KernelStack, PidHandle};
+pub use signal::{
+    handle_current_signals, signal_catchable, signal_supported, SignalFrame, MAX_SIG, SIGALRM,
+    SIGKILL,
+};
 pub use processor::{
-    current_task, current_trap_cx, current_user_token, run_tasks, schedule, take_current_task,
+    busy_dispatch_count, current_task, current_trap_cx, current_user_token, run_tasks, schedule,
+    take_current_task,
 };
 
 /// Make current task suspended and switch to the next task
@@ -32,6 +49,7 @@ pub fn suspend_current_and_run_next() {
     let task_cx_ptr = &mut task_inner.task_cx as *mut TaskContext;
     // Change status to Ready
     task_inner.task_status = TaskStatus::Ready;
+    task_inner.stop_cpu_clock();
     drop(task_inner);
     // ---- release current PCB
 
@@ -41,16 +59,59 @@ pub fn suspend_current_and_run_next() {
     schedule(task_cx_ptr);
 }
 
+/// Make current task blocked for `reason` and switch to the next task.
+/// It stays out of the ready queue until [`wakeup_task`] is called on it.
+pub fn block_current_and_run_next(reason: BlockReason) {
+    let task = take_current_task().unwrap();
+    let mut task_inner = task.inner_exclusive_access();
+    let task_cx_ptr = &mut task_inner.task_cx as *mut TaskContext;
+    task_inner.task_status = TaskStatus::Blocked;
+    task_inner.blocked_on = Some(reason);
+    note_blocked(reason);
+    task_inner.stop_cpu_clock();
+    drop(task_inner);
+    schedule(task_cx_ptr);
+}
+
+/// Make a blocked task ready again, tasks that are not blocked are left alone
+pub fn wakeup_task(task: Arc<TaskControlBlock>) {
+    let mut task_inner = task.inner_exclusive_access();
+    if task_inner.task_status != TaskStatus::Blocked {
+        return;
+    }
+    task_inner.task_status = TaskStatus::Ready;
+    if let Some(reason) = task_inner.blocked_on.take() {
+        note_unblocked(reason);
+    }
+    drop(task_inner);
+    add_task(task);
+}
+
 /// Exit current task, recycle process resources and switch to the next task
 pub fn exit_current_and_run_next(exit_code: i32) {
     // take from Processor
     let task = take_current_task().unwrap();
+    if Arc::ptr_eq(&task, &INITPROC) {
+        // nobody is left to adopt the orphans, and reparenting them below
+        // would borrow initproc a second time
+        println!("[kernel] initproc exited with code {}, shutting down.", exit_code);
+        shutdown();
+    }
+    remove_from_pid2task(task.getpid());
+    // emit the unterminated tail of the last line, before the TCB is borrowed
+    let pending = task.inner_exclusive_access().take_stdout();
+    print_stdout(&pending);
     // **** access current TCB exclusively
     let mut inner = task.inner_exclusive_access();
     // Change status to Zombie
     inner.task_status = TaskStatus::Zombie;
+    inner.stop_cpu_clock();
     // Record exit code
     inner.exit_code = exit_code;
+    if shutdown_initiator() == Some(task.getpid()) {
+        println!("[kernel] pid {} exited, completing its shutdown.", task.getpid());
+        shutdown();
+    }
     // do not move to its parent but under initproc
 
     // ++++++ access initproc TCB exclusively
@@ -64,12 +125,33 @@ pub fn exit_current_and_run_next(exit_code: i32) {
     // ++++++ release parent PCB
 
     inner.children.clear();
+    // an auto-reaping parent never waits, so leave it nothing to collect
+    let auto_reaped = inner
+        .parent
+        .as_ref()
+        .and_then(|parent| parent.upgrade())
+        .map_or(false, |parent| {
+            let mut parent_inner = parent.inner_exclusive_access();
+            if parent_inner.auto_reap {
+                parent_inner.children.retain(|child| !Arc::ptr_eq(child, &task));
+            }
+            parent_inner.auto_reap
+        });
+    if !auto_reaped {
+        note_zombie();
+    }
     // deallocate user space
+    inner.sample_rss();
     inner.memory_set.recycle_data_pages();
     drop(inner);
     // **** release current PCB
     // drop task manually to maintain rc correctly
-    drop(task);
+    if auto_reaped {
+        // the last reference, which would free the stack we are running on
+        defer_free(task);
+    } else {
+        drop(task);
+    }
     // we do not have to save task context
     let mut _unused = TaskContext::zero_init();
     schedule(&mut _unused as *mut _);
@@ -80,9 +162,10 @@ lazy_static! {
     ///
     /// the name "initproc" may be changed to any other app name like "usertests",
     /// but we have user_shell, so we don't need to change it.
-    pub static ref INITPROC: Arc<TaskControlBlock> = Arc::new(TaskControlBlock::new(
+    pub static ref INITPROC: Arc<TaskControlBlock> = TaskControlBlock::new(
+        "ch5b_initproc",
         get_app_data_by_name("ch5b_initproc").unwrap()
-    ));
+    );
 }
 
 pub fn add_initproc() {
@@ -2,11 +2,22 @@
 //!
 //! Assign PID to the process here. At the same time, the position of the application KernelStack
 //! is determined according to the PID.
+//!
+//! A dropped KernelStack stays mapped in a small pool. Freed pids are handed
+//! out again last in first out, so a fork right after an exit usually gets a
+//! pid whose stack is still in the pool and skips mapping a new one.
 
-use crate::config::{KERNEL_STACK_SIZE, PAGE_SIZE, TRAMPOLINE};
+use super::manager::PID2TCB;
+use super::TaskControlBlock;
+use crate::config::{KERNEL_STACK_POOL_SIZE, KERNEL_STACK_SIZE, PAGE_SIZE, TRAMPOLINE};
+#[cfg(feature = "selftest")]
+use crate::mm::frame_remaining;
 use crate::mm::{MapPermission, VirtAddr, KERNEL_SPACE};
 use crate::sync::UPSafeCell;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::cell::RefMut;
 use lazy_static::*;
 
 /// Process identifier allocator using stack allocation
@@ -47,6 +58,9 @@ lazy_static! {
     /// Pid allocator instance through lazy_static!
     static ref PID_ALLOCATOR: UPSafeCell<PidAllocator> =
         unsafe { UPSafeCell::new(PidAllocator::new()) };
+    /// Pids whose kernel stack is still mapped though no task uses it, oldest first
+    static ref KERNEL_STACK_POOL: UPSafeCell<VecDeque<usize>> =
+        unsafe { UPSafeCell::new(VecDeque::new()) };
 }
 
 /// Abstract structure of PID
@@ -59,8 +73,30 @@ impl Drop for PidHandle {
     }
 }
 
-pub fn pid_alloc() -> PidHandle {
-    PID_ALLOCATOR.exclusive_access().alloc()
+/// The pid registry, held from the allocation of a pid until its task is registered
+pub struct PidRegistration {
+    registry: RefMut<'static, BTreeMap<usize, Arc<TaskControlBlock>>>,
+    pid: usize,
+}
+
+impl PidRegistration {
+    /// Register `task` under the pid, which it must hold, and release the registry
+    pub fn register(mut self, task: &Arc<TaskControlBlock>) {
+        assert_eq!(task.getpid(), self.pid);
+        self.registry.insert(self.pid, Arc::clone(task));
+    }
+}
+
+/// Allocate a pid and keep the pid registry held until the task built for it
+/// is registered, so both happen in one critical section. The registry keeps
+/// its tasks alive, their pids are never recycled, so a fresh pid that is
+/// already registered is a bug.
+pub fn pid_alloc() -> (PidHandle, PidRegistration) {
+    let registry = PID2TCB.exclusive_access();
+    let pid_handle = PID_ALLOCATOR.exclusive_access().alloc();
+    let pid = pid_handle.0;
+    assert!(!registry.contains_key(&pid), "pid {} is already live", pid);
+    (pid_handle, PidRegistration { registry, pid })
 }
 
 /// Return (bottom, top) of a kernel stack in kernel space.
@@ -79,11 +115,21 @@ impl KernelStack {
     pub fn new(pid_handle: &PidHandle) -> Self {
         let pid = pid_handle.0;
         let (kernel_stack_bottom, kernel_stack_top) = kernel_stack_position(pid);
-        KERNEL_SPACE.exclusive_access().insert_framed_area(
-            kernel_stack_bottom.into(),
-            kernel_stack_top.into(),
-            MapPermission::R | MapPermission::W,
-        );
+        let mut pool = KERNEL_STACK_POOL.exclusive_access();
+        if let Some(idx) = pool.iter().position(|&pooled| pooled == pid) {
+            pool.remove(idx);
+            // whatever the last owner left there must not reach the new task
+            unsafe {
+                core::slice::from_raw_parts_mut(kernel_stack_bottom as *mut u8, KERNEL_STACK_SIZE)
+                    .fill(0);
+            }
+        } else {
+            KERNEL_SPACE.exclusive_access().insert_framed_area(
+                kernel_stack_bottom.into(),
+                kernel_stack_top.into(),
+                MapPermission::R | MapPermission::W,
+            );
+        }
         KernelStack { pid: pid_handle.0 }
     }
     #[allow(unused)]
@@ -107,10 +153,46 @@ impl KernelStack {
 
 impl Drop for KernelStack {
     fn drop(&mut self) {
-        let (kernel_stack_bottom, _) = kernel_stack_position(self.pid);
-        let kernel_stack_bottom_va: VirtAddr = kernel_stack_bottom.into();
-        KERNEL_SPACE
-            .exclusive_access()
-            .remove_area_with_start_vpn(kernel_stack_bottom_va.into());
+        let mut pool = KERNEL_STACK_POOL.exclusive_access();
+        pool.push_back(self.pid);
+        if pool.len() > KERNEL_STACK_POOL_SIZE {
+            let evicted = pool.pop_front().unwrap();
+            let (kernel_stack_bottom, _) = kernel_stack_position(evicted);
+            let kernel_stack_bottom_va: VirtAddr = kernel_stack_bottom.into();
+            KERNEL_SPACE
+                .exclusive_access()
+                .remove_area_with_start_vpn(kernel_stack_bottom_va.into());
+        }
+    }
+}
+
+/// Create and drop kernel stacks over and over: once the pool is warm no new
+/// frames are taken, a reused stack comes back zeroed, and the pool stays bounded
+#[cfg(feature = "selftest")]
+pub fn kernel_stack_pool_test() {
+    // stacks without tasks, nothing to register
+    let pid_alloc = || PID_ALLOCATOR.exclusive_access().alloc();
+    let cycle = || {
+        let pid = pid_alloc();
+        let stack = KernelStack::new(&pid);
+        let top = stack.get_top() as *const usize;
+        assert_eq!(unsafe { *top.sub(1) }, 0);
+        stack.push_on_top(0x5a5a_5a5a_usize);
+    };
+    cycle();
+    let frames = frame_remaining();
+    for _ in 0..64 {
+        cycle();
+        assert_eq!(frame_remaining(), frames);
+    }
+    // more live stacks than the pool holds, then all of them dropped
+    let pids: Vec<PidHandle> = (0..2 * KERNEL_STACK_POOL_SIZE).map(|_| pid_alloc()).collect();
+    let stacks: Vec<KernelStack> = pids.iter().map(KernelStack::new).collect();
+    drop(stacks);
+    assert!(KERNEL_STACK_POOL.exclusive_access().len() <= KERNEL_STACK_POOL_SIZE);
+    // free pid 0 last, so that initproc still gets it
+    for pid in pids.into_iter().rev() {
+        drop(pid);
     }
+    info!("kernel_stack_pool_test passed!");
 }
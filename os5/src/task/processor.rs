@@ -6,36 +6,65 @@
 
 
 use super::__switch;
-use super::{fetch_task, TaskStatus};
+use super::{fetch_task, free_reaped, TaskStatus};
 use super::{TaskContext, TaskControlBlock};
 use crate::sync::UPSafeCell;
 use crate::trap::TrapContext;
-use crate::timer::get_time_us;
+use crate::timer::{check_timer, get_time_us, set_next_trigger, update_vdso};
 use alloc::sync::Arc;
+use core::sync::atomic::{AtomicUsize, Ordering};
 use lazy_static::*;
-use crate::config::MAX_SYSCALL_NUM;
-use crate::mm::{MapPermission,VirtAddr,VirtPageNum};
-use crate::mm::address::StepByOne;
+use crate::mm::{
+    frame_contiguous_remaining, frame_remaining, swap_remaining, MapPermission, VirtAddr, VirtPageNum,
+};
+use crate::config::{HUGE_PAGE_SIZE, PAGE_SIZE, USER_MMAP_END};
+use crate::syscall::errno::{EEXIST, EINVAL, ENOMEM};
 use crate::mm::address::VPNRange;
+use riscv::register::{scause, stval};
+/// Frames kept back for the page tables of a new mmap area
+const MMAP_PAGE_TABLE_RESERVE: usize = 3;
+/// `port` bit of mmap asking for 2M megapages, `start` and `len` must then be 2M aligned
+pub const MMAP_HUGE: usize = 1 << 3;
+/// `port` bit of mmap sharing the area with forked children instead of copying it
+pub const MMAP_SHARED: usize = 1 << 4;
+/// `port` bit of mmap for a stack: only the last page is backed and a fault
+/// below it extends the area down, up to the page above `start`, which stays
+/// unmapped as a guard. `len` must then be at least 2 pages.
+pub const MMAP_GROWSDOWN: usize = 1 << 6;
+/// `port` bit of mmap asking for `start` back on success instead of 0,
+/// so the result can be used as a pointer
+pub const MMAP_RETURN_BASE: usize = 1 << 7;
+
 /// Processor management structure
 pub struct Processor {
     /// The task currently executing on the current processor
     current: Option<Arc<TaskControlBlock>>,
     /// The basic control flow of each core, helping to select and switch process
     idle_task_cx: TaskContext,
+    /// Number of switches to a task that was not spinning in sys_waitpid
+    busy_dispatches: usize,
 }
 
+/// `CURRENT_PID` while no task is running
+const NO_PID: usize = usize::MAX;
+
+/// Pid of `PROCESSOR.current`, set when it is switched in. A pid never changes,
+/// so reading it needs neither the processor, the task nor its lock
+static CURRENT_PID: AtomicUsize = AtomicUsize::new(NO_PID);
+
 impl Processor {
     pub fn new() -> Self {
         Self {
             current: None,
             idle_task_cx: TaskContext::zero_init(),
+            busy_dispatches: 0,
         }
     }
     fn get_idle_task_cx_ptr(&mut self) -> *mut TaskContext {
         &mut self.idle_task_cx as *mut _
     }
     pub fn take_current(&mut self) -> Option<Arc<TaskControlBlock>> {
+        CURRENT_PID.store(NO_PID, Ordering::SeqCst);
         self.current.take()
     }
     pub fn current(&self) -> Option<Arc<TaskControlBlock>> {
@@ -54,6 +83,8 @@ lazy_static! {
 /// and switch the process through __switch
 pub fn run_tasks() {
     loop {
+        // back on the idle stack, no auto-reaped task is running any more
+        free_reaped();
         let mut processor = PROCESSOR.exclusive_access();
         if let Some(task) = fetch_task() {
             let idle_task_cx_ptr = processor.get_idle_task_cx_ptr();
@@ -61,21 +92,51 @@ pub fn run_tasks() {
             let mut task_inner = task.inner_exclusive_access();
             let next_task_cx_ptr = &task_inner.task_cx as *const TaskContext;
             task_inner.task_status = TaskStatus::Running;
-            if task_inner.call_time ==0{
-                task_inner.call_time = get_time_us()/1000;
+            task_inner
+                .start_time_ms
+                .get_or_insert_with(|| get_time_us() / 1000);
+            task_inner.last_run_start = get_time_us();
+            task_inner.sched_epoch += 1;
+            if task_inner.waiting_for.is_none() {
+                processor.busy_dispatches += 1;
             }
             drop(task_inner);
             // release coming task TCB manually
+            trace!("[kernel] switch to pid {}", task.getpid());
+            CURRENT_PID.store(task.getpid(), Ordering::SeqCst);
             processor.current = Some(task);
             // release processor manually
             drop(processor);
             unsafe {
                 __switch(idle_task_cx_ptr, next_task_cx_ptr);
             }
+        } else {
+            drop(processor);
+            idle();
         }
     }
 }
 
+/// What the processor does when no task is ready: sleep until the next
+/// timer interrupt, then wake up the tasks whose timers have expired.
+///
+/// Interrupts stay disabled in the kernel, `wfi` still returns once the
+/// timer interrupt is pending, and re-arming the timer clears it.
+fn idle() {
+    unsafe {
+        riscv::asm::wfi();
+    }
+    set_next_trigger();
+    update_vdso();
+    check_timer();
+}
+
+/// Number of switches so far to a task that was not spinning in sys_waitpid,
+/// see `sys_yield`
+pub fn busy_dispatch_count() -> usize {
+    PROCESSOR.exclusive_access().busy_dispatches
+}
+
 /// Get current task through take, leaving a None in its place
 pub fn take_current_task() -> Option<Arc<TaskControlBlock>> {
     PROCESSOR.exclusive_access().take_current()
@@ -86,6 +147,14 @@ pub fn current_task() -> Option<Arc<TaskControlBlock>> {
     PROCESSOR.exclusive_access().current()
 }
 
+/// Pid of the current task, cached when it was switched in
+pub fn current_pid() -> Option<usize> {
+    match CURRENT_PID.load(Ordering::SeqCst) {
+        NO_PID => None,
+        pid => Some(pid),
+    }
+}
+
 /// Get token of the address space of current task
 pub fn current_user_token() -> usize {
     let task = current_task().unwrap();
@@ -112,33 +181,73 @@ pub fn schedule(switched_task_cx_ptr: *mut TaskContext) {
 }
 
 
-//lab 3
-pub fn get_current_time() -> usize{
-    let task = current_task();
-    task.unwrap().inner_exclusive_access().call_time
+/// Count a syscall of the current task, and unless it is part of a waitpid
+/// loop, note that the task is not just spinning in sys_waitpid. Return
+/// whether the syscall filter of the task allows the syscall.
+/// One borrow of the task for all of it, as this runs on every syscall.
+pub fn note_current_syscall(syscall_id: usize, waitpid_loop: bool) -> bool {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    inner.call_num[syscall_id] += 1;
+    if !waitpid_loop {
+        inner.waiting_for = None;
+    }
+    inner.syscall_allowed(syscall_id)
 }
-pub fn get_current_num() -> [u32;MAX_SYSCALL_NUM]{
-    let task = current_task();
-    task.unwrap().inner_exclusive_access().call_num
+
+/// Charge one timer tick to the current task,
+/// return true if it has run past its CPU budget
+pub fn charge_current_tick() -> bool {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    inner.run_ticks += 1;
+    inner.sample_rss();
+    inner.cpu_budget != 0 && inner.run_ticks > inner.cpu_budget
 }
-pub fn add_current_num(syscall_id: usize){
-    let task = current_task();
-    task.unwrap().inner_exclusive_access().call_num[syscall_id]+=1;
+
+/// The current task does something other than spinning in sys_waitpid
+pub fn clear_current_waiting() {
+    current_task().unwrap().inner_exclusive_access().waiting_for = None;
 }
 
+/// Whether the current task has been killed and must not return to user mode
+pub fn current_killed() -> bool {
+    current_task().unwrap().inner_exclusive_access().killed
+}
+
+/// Map `[start, start + len)` and return 0, or `start` with `MMAP_RETURN_BASE`;
+/// errors are negative errno values.
 pub fn mmap_malloc(_start: usize, _len: usize, _port: usize) -> isize{
-    
+    let success = if _port & MMAP_RETURN_BASE != 0 { _start as isize } else { 0 };
     if _len ==0{
-        return 0;
+        return success;
     }
     if _start%4096 !=0{
-        return -1;
+        return -EINVAL;
     }
-    if _port & (!0x7) != 0{
-        return -1;
+    // start + len must neither wrap nor leave the user half of the address space
+    if _start.checked_add(_len).map_or(true, |end| end > USER_MMAP_END) {
+        return -ENOMEM;
+    }
+    let flags = MMAP_HUGE | MMAP_SHARED | MMAP_GROWSDOWN | MMAP_RETURN_BASE;
+    if _port & !(0x7 | flags) != 0{
+        return -EINVAL;
+    }
+    let huge = _port & MMAP_HUGE != 0;
+    let shared = _port & MMAP_SHARED != 0;
+    let grows_down = _port & MMAP_GROWSDOWN != 0;
+    if grows_down && (huge || shared || _len < 2 * PAGE_SIZE) {
+        return -EINVAL;
+    }
+    // forks share frames a 4K page at a time
+    if huge && shared {
+        return -EINVAL;
+    }
+    if huge && (_start % HUGE_PAGE_SIZE != 0 || _len % HUGE_PAGE_SIZE != 0) {
+        return -EINVAL;
     }
     if _port & 0x7 ==0{
-        return -1;
+        return -EINVAL;
     }
     //let mut inner = self.inner.exclusive_access();
     let binding = current_task().unwrap();
@@ -147,12 +256,28 @@ pub fn mmap_malloc(_start: usize, _len: usize, _port: usize) -> isize{
     let start: VirtAddr  = VirtAddr(_start).floor().into(); 
     let end_vpn:VirtAddr  = VirtAddr::from(_start+_len).ceil().into();
     if memory_set.check_va_overlap(start.into(), end_vpn.into()){
-        return -1;
+        return -EEXIST;
+    }
+    // leave room for the page table frames the new area may need
+    let pages = if grows_down { 1 } else { (end_vpn.0 - start.0) / PAGE_SIZE };
+    if frame_remaining() < pages + MMAP_PAGE_TABLE_RESERVE {
+        return -ENOMEM;
     }
-    let mut permission = MapPermission::from_bits((_port as u8) << 1).unwrap();
+    if huge && frame_contiguous_remaining(HUGE_PAGE_SIZE / PAGE_SIZE) < pages {
+        return -ENOMEM;
+    }
+    let mut permission = MapPermission::from_bits(((_port & 0x7) as u8) << 1).unwrap();
     permission.set(MapPermission::U, true);
-    memory_set.insert_framed_area(start.into(),end_vpn.into(),permission);
-    0
+    if huge {
+        memory_set.insert_huge_area(start.into(), end_vpn.into(), permission);
+    } else if grows_down {
+        memory_set.insert_grows_down_area(start, end_vpn, permission);
+    } else if shared {
+        memory_set.insert_shared_area(start.into(), end_vpn.into(), permission);
+    } else {
+        memory_set.insert_framed_area(start.into(),end_vpn.into(),permission);
+    }
+    success
 
 }
 pub fn unmap_unalloc(_start: usize, _len: usize) -> isize{
@@ -162,30 +287,203 @@ pub fn unmap_unalloc(_start: usize, _len: usize) -> isize{
     if _start%4096 !=0{
         return -1;
     }
-    //let mut inner = self.inner.exclusive_access();
+    let end = match _start.checked_add(_len) {
+        Some(end) if end <= USER_MMAP_END => end,
+        _ => return -1,
+    };
+    let binding = current_task().unwrap();
+    let mut current = binding.inner_exclusive_access();
+    // validated as a whole before any page is touched
+    if current
+        .memory_set
+        .unmap_range(VirtAddr::from(_start).floor(), VirtAddr::from(end).ceil())
+    {
+        0
+    } else {
+        -1
+    }
+}
+/// Reserve `[start, start + len)` so no later mmap can land there,
+/// without backing any of it with frames. -EINVAL for a bad `start` or `len`,
+/// -ENOMEM past `USER_MMAP_END`, -EEXIST if a page of it is already in use.
+pub fn reserve_range(_start: usize, _len: usize) -> isize {
+    if _len == 0 || _start % PAGE_SIZE != 0 {
+        return -EINVAL;
+    }
+    if _start.checked_add(_len).map_or(true, |end| end > USER_MMAP_END) {
+        return -ENOMEM;
+    }
     let binding = current_task().unwrap();
     let mut current = binding.inner_exclusive_access();
     let memory_set = &mut current.memory_set;
-    let mut start = _start; 
-    let end = start+_len;
-    while start <end{
-        let start_va = VirtAddr::from(start);
-        let mut vpn = VirtAddr::from(start).floor();
-        vpn.step();
-        let mut end_va: VirtAddr = vpn.into();
-        end_va = end_va.min(VirtAddr::from(end));
-        let pte = memory_set.translate(vpn);
-        match Some(pte){
-            None => return -1,
-            _ => (),
-        }
+    let start: VirtAddr = VirtAddr(_start);
+    let end: VirtAddr = VirtAddr::from(_start + _len).ceil().into();
+    if memory_set.check_va_overlap(start.into(), end.into()) {
+        return -EEXIST;
+    }
+    memory_set.insert_reserved_area(start, end);
+    0
+}
+
+/// Back `[start, start + len)` of an earlier reservation with frames,
+/// -EINVAL if the range is not inside a single reservation, -ENOMEM past
+/// `USER_MMAP_END` or when frames are short.
+pub fn commit_reserved(_start: usize, _len: usize, _port: usize) -> isize {
+    if _len == 0 || _start % PAGE_SIZE != 0 {
+        return -EINVAL;
+    }
+    if _port & !0x7 != 0 || _port & 0x7 == 0 {
+        return -EINVAL;
+    }
+    let end = match _start.checked_add(_len) {
+        Some(end) if end <= USER_MMAP_END => end,
+        _ => return -ENOMEM,
+    };
+    let start_vpn = VirtAddr::from(_start).floor();
+    let end_vpn = VirtAddr::from(end).ceil();
+    if frame_remaining() < end_vpn.0 - start_vpn.0 + MMAP_PAGE_TABLE_RESERVE {
+        return -ENOMEM;
+    }
+    let mut permission = MapPermission::from_bits(((_port & 0x7) as u8) << 1).unwrap();
+    permission.set(MapPermission::U, true);
+    let binding = current_task().unwrap();
+    let mut current = binding.inner_exclusive_access();
+    if current.memory_set.commit_reserved(start_vpn, end_vpn, permission) {
+        0
+    } else {
+        -EINVAL
+    }
+}
 
-        let mut index =0;
-        let success =  memory_set.unmap(start_va,end_va);
-        if success ==false{
-            return -1;
+/// Move the frames of `[start, start + len)` to swap space, return how many
+/// pages were moved. The range must lie in private, writable mmap or data
+/// areas, else -EINVAL; -ENOMEM if it leaves user space or swap space is short.
+pub fn swap_out_range(_start: usize, _len: usize) -> isize {
+    if _start % PAGE_SIZE != 0 {
+        return -EINVAL;
+    }
+    let end = match _start.checked_add(_len) {
+        Some(end) if end <= USER_MMAP_END => end,
+        _ => return -ENOMEM,
+    };
+    let start_vpn = VirtAddr::from(_start).floor();
+    let end_vpn = VirtAddr::from(end).ceil();
+    let binding = current_task().unwrap();
+    let mut current = binding.inner_exclusive_access();
+    match current.memory_set.swappable_pages(start_vpn, end_vpn) {
+        None => -EINVAL,
+        Some(pages) if pages > swap_remaining() => -ENOMEM,
+        Some(_) => current.memory_set.swap_out(start_vpn, end_vpn) as isize,
+    }
+}
+
+/// Lock `[start, start + len)` of the current task against swap out, or
+/// unlock it. -ENOMEM unless the whole range is mapped, or if the swapped out
+/// pages cannot be brought back for lack of frames.
+pub fn lock_range(_start: usize, _len: usize, locked: bool) -> isize {
+    if _start % PAGE_SIZE != 0 {
+        return -EINVAL;
+    }
+    let end = match _start.checked_add(_len) {
+        Some(end) if end <= USER_MMAP_END => end,
+        _ => return -ENOMEM,
+    };
+    let binding = current_task().unwrap();
+    let mut current = binding.inner_exclusive_access();
+    let (start_vpn, end_vpn) = (VirtAddr::from(_start).floor(), VirtAddr::from(end).ceil());
+    if current.memory_set.set_locked(start_vpn, end_vpn, locked) {
+        0
+    } else {
+        -ENOMEM
+    }
+}
+
+/// Extend the grow-down area of the current task above `va` down to it,
+/// false if `va` is not in the room such an area has left to grow
+pub fn grow_down_current(va: usize) -> bool {
+    let vpn = VirtAddr::from(va).floor();
+    current_task()
+        .unwrap()
+        .inner_exclusive_access()
+        .memory_set
+        .grow_down(vpn)
+}
+
+/// Whether `sp` can be the user stack pointer of the current task: the word
+/// below it must lie in one of its stacks, see `MemorySet::in_stack`
+pub fn user_sp_ok(sp: usize) -> bool {
+    sp > 0
+        && sp <= USER_MMAP_END
+        && current_task()
+            .unwrap()
+            .inner_exclusive_access()
+            .memory_set
+            .in_stack(VirtAddr::from(sp - 1))
+}
+
+/// Bring the page at `va` of the current task back from swap space,
+/// false if it is not swapped out
+pub fn swap_in_current(va: usize) -> bool {
+    let vpn = VirtAddr::from(va).floor();
+    current_task()
+        .unwrap()
+        .inner_exclusive_access()
+        .memory_set
+        .swap_in(vpn)
+}
+
+/// Number of words printed from the top of the kernel stack by `dump_current_task`
+const DUMP_STACK_WORDS: usize = 8;
+
+/// Print the state of the current task for the panic handler: pid, name,
+/// trap context and the top of its kernel stack.
+/// Never panics itself, a lock held by the panicking code is reported instead.
+pub fn dump_current_task() {
+    let task = match PROCESSOR.try_exclusive_access() {
+        Some(processor) => processor.current(),
+        None => {
+            println!("[kernel] processor is busy, no task state available");
+            return;
+        }
+    };
+    let task = match task {
+        Some(task) => task,
+        None => {
+            println!("[kernel] panic with no current task");
+            return;
+        }
+    };
+    println!("[kernel] panic in pid {}", task.getpid());
+    match task.try_inner_exclusive_access() {
+        Some(inner) => {
+            let trap_cx = inner.get_trap_cx();
+            println!("[kernel] name: {}", inner.name);
+            println!(
+                "[kernel] sepc: {:#x} scause: {:#x} stval: {:#x}",
+                trap_cx.sepc,
+                scause::read().bits(),
+                stval::read()
+            );
+            for (row, regs) in trap_cx.x.chunks(4).enumerate() {
+                println!(
+                    "[kernel] x{:<2} {:#018x} {:#018x} {:#018x} {:#018x}",
+                    row * 4,
+                    regs[0],
+                    regs[1],
+                    regs[2],
+                    regs[3]
+                );
+            }
+        }
+        None => {
+            println!("[kernel] task is locked by the panicking code, no trap context");
         }
-        start = end_va.into();
     }
-    0
-}
\ No newline at end of file
+    let top = task.kernel_stack.get_top();
+    println!("[kernel] kernel stack top {:#x}:", top);
+    for i in 1..=DUMP_STACK_WORDS {
+        let addr = top - i * core::mem::size_of::<usize>();
+        let word = unsafe { (addr as *const usize).read_volatile() };
+        println!("[kernel]   {:#x}: {:#018x}", addr, word);
+    }
+}
@@ -0,0 +1,94 @@
+//! Signals sent by sys_kill
+//!
+//! A signal other than SIGKILL is recorded as pending on the target and
+//! delivered the next time the target returns to user mode: its trap context
+//! is saved and it resumes in the registered handler instead, with the signal
+//! number in `a0`. The handler must finish with sys_sigreturn, which puts the
+//! saved context back. A pending signal without a handler terminates the task.
+//!
+//! Saved contexts form a per-task stack, so a handler can be interrupted by
+//! another signal. A signal stays blocked while its own handler runs and is
+//! delivered once that handler has returned.
+
+use super::{current_task, exit_current_and_run_next};
+use crate::mm::user_writable;
+use crate::trap::TrapContext;
+
+/// Terminate the target, cannot be caught
+pub const SIGKILL: usize = 9;
+/// User defined signal 1
+pub const SIGUSR1: usize = 10;
+/// User defined signal 2
+pub const SIGUSR2: usize = 12;
+/// Interval timer expired, see sys_setitimer
+pub const SIGALRM: usize = 14;
+/// Signal numbers are below this
+pub const MAX_SIG: usize = 32;
+
+/// Whether `signum` can be sent, 0 included as the existence probe
+pub fn signal_supported(signum: usize) -> bool {
+    matches!(signum, 0 | SIGKILL | SIGUSR1 | SIGUSR2 | SIGALRM)
+}
+
+/// Whether a handler can be registered for `signum`
+pub fn signal_catchable(signum: usize) -> bool {
+    matches!(signum, SIGUSR1 | SIGUSR2 | SIGALRM)
+}
+
+/// A running signal handler
+#[derive(Clone, Copy)]
+pub struct SignalFrame {
+    pub signum: usize,
+    /// What the handler interrupted, put back by sys_sigreturn
+    pub trap_cx: TrapContext,
+}
+
+/// Deliver the lowest pending signal of the current task whose handler is
+/// not already running, if any
+pub fn handle_current_signals() {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    let blocked = inner
+        .signal_stack
+        .iter()
+        .fold(0u32, |mask, frame| mask | 1 << frame.signum);
+    let deliverable = inner.pending_signals & !blocked;
+    if deliverable == 0 {
+        return;
+    }
+    let signum = deliverable.trailing_zeros() as usize;
+    inner.pending_signals &= !(1 << signum);
+    let handler = inner.signal_handlers[signum];
+    if handler == 0 {
+        drop(inner);
+        drop(task);
+        // terminated by the signal, exit code like SIGKILL
+        exit_current_and_run_next(-(signum as i32));
+        return;
+    }
+    let trap_cx = inner.get_trap_cx();
+    // the handler runs on the interrupted sp, which may be any stack the task
+    // set up, see sys_fork_sp: only the 16 bytes below it must be writable
+    let sp = trap_cx.x[2];
+    let stack_ok = sp
+        .checked_sub(16)
+        .map_or(false, |low| user_writable(inner.memory_set.token(), low, 16));
+    if !stack_ok {
+        println!(
+            "[kernel] bad user sp = {:#x} for signal {} handler, killed.",
+            sp, signum
+        );
+        drop(inner);
+        drop(task);
+        // page fault exit code
+        exit_current_and_run_next(-2);
+        return;
+    }
+    let frame = SignalFrame {
+        signum,
+        trap_cx: *trap_cx,
+    };
+    trap_cx.sepc = handler;
+    trap_cx.x[10] = signum;
+    inner.signal_stack.push(frame);
+}
@@ -1,15 +1,28 @@
 
 use super::TaskContext;
-use super::{pid_alloc, KernelStack, PidHandle};
+use super::{deterministic, pid_alloc, KernelStack, PidHandle, SignalFrame, MAX_SIG};
 use crate::config::TRAP_CONTEXT;
 use crate::mm::{MemorySet, PhysPageNum, VirtAddr, KERNEL_SPACE};
+#[cfg(feature = "verify-fork")]
+use crate::mm::verify_fork_equivalence;
 use crate::sync::UPSafeCell;
+use crate::timer::get_time_us;
 use crate::trap::{trap_handler, TrapContext};
+use alloc::string::String;
 use alloc::sync::{Arc, Weak};
 use alloc::vec::Vec;
 use core::cell::RefMut;
 use crate::config::MAX_SYSCALL_NUM;
 use crate::config::BIG_STRIDE;
+use crate::config::CONSOLE_LINE_BUFFER_SIZE;
+bitflags! {
+    /// How a child created by [`TaskControlBlock::do_clone`] differs from a copy of its parent
+    pub struct CloneFlags: u32 {
+        /// Load a new program instead of copying the parent's address space (spawn)
+        const NEW_ADDRESS_SPACE = 1 << 0;
+    }
+}
+
 /// Task control block structure
 ///
 /// Directly save the contents that will not change during running
@@ -46,11 +59,69 @@ pub struct TaskControlBlockInner {
     pub children: Vec<Arc<TaskControlBlock>>,
     /// It is set when active exit or execution error occurs
     pub exit_code: i32,
-    pub call_time:usize,  //lab3
+    /// When the task was first switched in, in ms; None until then, so the
+    /// time it spent queued after creation is not counted
+    pub start_time_ms: Option<usize>,
+    /// Syscalls made by this task alone, kept by value so a fork child
+    /// starts from zero and never shares the parent's counts
     pub call_num:[u32;MAX_SYSCALL_NUM],
     pub pass:u32,  
     pub stride:u32,
     pub priority:isize,
+    /// CPU budget in timer ticks, 0 means unlimited
+    pub cpu_budget: usize,
+    /// Timer ticks charged to this task so far
+    pub run_ticks: usize,
+    /// A sys_yield less than this many us after being switched in keeps
+    /// running instead, 0 turns the policy off. Inherited across fork
+    pub yield_min_us: usize,
+    /// Pending stdout bytes, emitted a whole line at a time
+    pub stdout_buffer: Vec<u8>,
+    /// CPU time consumed so far in us
+    pub cpu_time: usize,
+    /// Most frames the address space has held, sampled every timer tick and at exit
+    pub max_rss_pages: usize,
+    /// When the task was last switched in, in us
+    pub last_run_start: usize,
+    /// Number of times the task has been switched in, starting at 0 for a new task
+    pub sched_epoch: usize,
+    /// Set by sys_kill, the task exits before returning to user mode
+    pub killed: bool,
+    /// Number of ancestors, initproc being at depth 0
+    pub depth: usize,
+    /// Children exiting while this is set are freed at once instead of
+    /// turning into zombies. Not inherited across fork
+    pub auto_reap: bool,
+    /// Whether waking up from an I/O wait earns a scheduling boost
+    pub io_boost: bool,
+    /// Thread pointer, loaded into `tp` whenever the task returns to user mode
+    pub tls: usize,
+    /// The pid (or -1 for any child) the task is spinning on in sys_waitpid
+    pub waiting_for: Option<isize>,
+    /// Why the task is blocked, `None` unless it is
+    pub blocked_on: Option<BlockReason>,
+    /// Bit `i` is set while signal `i` is waiting to be delivered
+    pub pending_signals: u32,
+    /// User handler address of each signal, 0 for the default action
+    pub signal_handlers: [usize; MAX_SIG],
+    /// Handlers being run, innermost last, each with the trap context it interrupted
+    pub signal_stack: Vec<SignalFrame>,
+    /// When the interval timer next raises SIGALRM in ms, 0 while disarmed
+    pub itimer_deadline_ms: usize,
+    /// Period of the interval timer in ms, 0 for a one-shot timer
+    pub itimer_interval_ms: usize,
+    /// Absolute, normalized current working directory
+    pub cwd: String,
+    /// Shown in debug output, the app name unless renamed by sys_set_task_name
+    pub name: String,
+    /// Which ready queue the task waits in, inherited across fork
+    pub sched_policy: SchedPolicy,
+    /// Highest priority the task may take, set by sys_drop_priority_ceiling
+    /// and inherited by every descendant; `None` leaves it unbounded
+    pub priority_ceiling: Option<isize>,
+    /// Syscalls the task may make, one bit per id; `None` allows all.
+    /// Inherited across fork, dropped with the image on exec and spawn
+    pub syscall_filter: Option<Vec<u8>>,
 }
 
 /// Simple access to its internal fields
@@ -72,21 +143,83 @@ impl TaskControlBlockInner {
     pub fn is_zombie(&self) -> bool {
         self.get_status() == TaskStatus::Zombie
     }
+    /// Passes wrap around, the scheduler compares them by their difference
     pub fn add_pass(&mut self){
-        self.pass += BIG_STRIDE/self.priority as u32;
+        self.pass = self.pass.wrapping_add(self.stride);
+    }
+    /// Set the priority and the matching stride, `prio` is at least 2 so the
+    /// stride never exceeds half the pass range
+    pub fn set_priority(&mut self, prio: isize) {
+        self.priority = prio;
+        // divided at full width, a prio above u32::MAX would truncate; a stride
+        // of 0 would never move the pass, so the task would run for ever
+        self.stride = (BIG_STRIDE as usize / prio as usize).max(1) as u32;
+    }
+    /// Push bytes written to stdout, return the lines they complete. The caller
+    /// prints them once it has released the task, the console is slow
+    pub fn write_stdout(&mut self, bytes: &[u8]) -> Vec<u8> {
+        let mut lines = Vec::new();
+        for &byte in bytes {
+            self.stdout_buffer.push(byte);
+            if byte == b'\n' || self.stdout_buffer.len() >= CONSOLE_LINE_BUFFER_SIZE {
+                lines.append(&mut self.stdout_buffer);
+            }
+        }
+        lines
+    }
+    /// Take whatever is pending in the stdout buffer, to be printed by the caller
+    pub fn take_stdout(&mut self) -> Vec<u8> {
+        core::mem::take(&mut self.stdout_buffer)
+    }
+    /// Move the task up in the stride order after it waited for I/O,
+    /// by at most the stride of the default priority
+    pub fn apply_io_boost(&mut self) {
+        if self.io_boost && !deterministic() {
+            self.pass = self.pass.wrapping_sub(self.stride.min(BIG_STRIDE / 16));
+        }
+    }
+    /// Charge the time since the task was last switched in
+    pub fn stop_cpu_clock(&mut self) {
+        self.cpu_time += get_time_us() - self.last_run_start;
+    }
+    /// Time since the task first ran in ms, 0 if it has not run yet
+    pub fn run_time_ms(&self) -> usize {
+        self.start_time_ms
+            .map_or(0, |start| get_time_us() / 1000 - start)
+    }
+    /// Whether the priority ceiling lets the task take priority `prio`
+    pub fn priority_allowed(&self, prio: isize) -> bool {
+        self.priority_ceiling.map_or(true, |ceiling| prio <= ceiling)
+    }
+    /// Whether the syscall filter lets the task make syscall `syscall_id`
+    pub fn syscall_allowed(&self, syscall_id: usize) -> bool {
+        self.syscall_filter.as_ref().map_or(true, |filter| {
+            filter
+                .get(syscall_id / 8)
+                .map_or(false, |byte| byte & (1 << (syscall_id % 8)) != 0)
+        })
+    }
+    /// Fold the current size of the address space into `max_rss_pages`
+    pub fn sample_rss(&mut self) {
+        self.max_rss_pages = self.max_rss_pages.max(self.memory_set.frame_count());
     }
 }
 
 impl TaskControlBlock {
     /// Get the mutex to get the RefMut TaskControlBlockInner
+    #[track_caller]
     pub fn inner_exclusive_access(&self) -> RefMut<'_, TaskControlBlockInner> {
         self.inner.exclusive_access()
     }
+    /// `None` if the inner is already borrowed, for code that must not panic
+    pub fn try_inner_exclusive_access(&self) -> Option<RefMut<'_, TaskControlBlockInner>> {
+        self.inner.try_exclusive_access()
+    }
     
     /// Create a new process
     ///
     /// At present, it is only used for the creation of initproc
-    pub fn new(elf_data: &[u8]) -> Self {
+    pub fn new(name: &str, elf_data: &[u8]) -> Arc<Self> {
         // memory_set with elf program headers/trampoline/trap context/user stack
         let (memory_set, user_sp, entry_point) = MemorySet::from_elf(elf_data);
         let trap_cx_ppn = memory_set
@@ -94,11 +227,11 @@ impl TaskControlBlock {
             .unwrap()
             .ppn();
         // alloc a pid and a kernel stack in kernel space
-        let pid_handle = pid_alloc();
+        let (pid_handle, registration) = pid_alloc();
         let kernel_stack = KernelStack::new(&pid_handle);
         let kernel_stack_top = kernel_stack.get_top();
         // push a task context which goes to trap_return to the top of kernel stack
-        let task_control_block = Self {
+        let task_control_block = Arc::new(Self {
             pid: pid_handle,
             kernel_stack,
             inner: unsafe {
@@ -111,14 +244,40 @@ impl TaskControlBlock {
                     parent: None,
                     children: Vec::new(),
                     exit_code: 0,
-                    call_time:0,
+                    start_time_ms: None,
                     call_num:[0;MAX_SYSCALL_NUM],
                     pass:0,
                     stride:BIG_STRIDE/16,
                     priority:16,
+                    cpu_budget: 0,
+                    yield_min_us: 0,
+                    run_ticks: 0,
+                    stdout_buffer: Vec::with_capacity(CONSOLE_LINE_BUFFER_SIZE),
+                    cpu_time: 0,
+                    max_rss_pages: 0,
+                    last_run_start: 0,
+                    sched_epoch: 0,
+                    killed: false,
+                    depth: 0,
+                    auto_reap: false,
+                    io_boost: false,
+                    tls: 0,
+                    waiting_for: None,
+                    blocked_on: None,
+                    pending_signals: 0,
+                    signal_handlers: [0; MAX_SIG],
+                    signal_stack: Vec::new(),
+                    itimer_deadline_ms: 0,
+                    itimer_interval_ms: 0,
+                    cwd: String::from("/"),
+                    name: String::from(name),
+                    sched_policy: SchedPolicy::Stride,
+                    priority_ceiling: None,
+                    syscall_filter: None,
                 })
             },
-        };
+        });
+        registration.register(&task_control_block);
         // prepare TrapContext in user space
         let trap_cx = task_control_block.inner_exclusive_access().get_trap_cx();
         *trap_cx = TrapContext::app_init_context(
@@ -130,8 +289,10 @@ impl TaskControlBlock {
         );
         task_control_block
     }
-    /// Load a new elf to replace the original application address space and start execution
-    pub fn exec(&self, elf_data: &[u8]) {
+    /// Load a new elf to replace the original application address space and start execution,
+    /// the task is renamed to `name`. The syscall counters start over for the
+    /// new image unless `keep_syscall_times` is set
+    pub fn exec(&self, name: &str, elf_data: &[u8], keep_syscall_times: bool) {
         // memory_set with elf program headers/trampoline/trap context/user stack
         let (memory_set, user_sp, entry_point) = MemorySet::from_elf(elf_data);
         let trap_cx_ppn = memory_set
@@ -145,6 +306,15 @@ impl TaskControlBlock {
         inner.memory_set = memory_set;
         // update trap_cx ppn
         inner.trap_cx_ppn = trap_cx_ppn;
+        // the new image starts with a zeroed tp and default signal actions
+        inner.tls = 0;
+        inner.signal_handlers = [0; MAX_SIG];
+        inner.signal_stack.clear();
+        inner.syscall_filter = None;
+        inner.name = String::from(name);
+        if !keep_syscall_times {
+            inner.call_num = [0; MAX_SYSCALL_NUM];
+        }
         // initialize trap_cx
         let trap_cx = inner.get_trap_cx();
         *trap_cx = TrapContext::app_init_context(
@@ -158,16 +328,56 @@ impl TaskControlBlock {
     }
     /// Fork from parent to child
     pub fn fork(self: &Arc<TaskControlBlock>) -> Arc<TaskControlBlock> {
+        self.do_clone(CloneFlags::empty(), "", &[])
+    }
+    /// Create a child named `name` running `elf_data`, without copying the parent's address space
+    pub fn spawn(
+        self: &Arc<TaskControlBlock>,
+        name: &str,
+        elf_data: &[u8],
+    ) -> Arc<TaskControlBlock> {
+        self.do_clone(CloneFlags::NEW_ADDRESS_SPACE, name, elf_data)
+    }
+    /// Create a child of `self`, the common part of fork and spawn.
+    ///
+    /// With `NEW_ADDRESS_SPACE` the child is named `name` and starts `elf_data`
+    /// from its entry point, otherwise it is a copy of the parent, name included,
+    /// and both are unused.
+    fn do_clone(
+        self: &Arc<TaskControlBlock>,
+        flags: CloneFlags,
+        name: &str,
+        elf_data: &[u8],
+    ) -> Arc<TaskControlBlock> {
+        let new_space = flags.contains(CloneFlags::NEW_ADDRESS_SPACE);
         // ---- access parent PCB exclusively
         let mut parent_inner = self.inner_exclusive_access();
-        // copy user space(include trap context)
-        let memory_set = MemorySet::from_existed_user(&parent_inner.memory_set);
+        // memory_set with elf program headers/trampoline/trap context/user stack,
+        // or a copy of the parent's user space (include trap context)
+        let (memory_set, entry) = if new_space {
+            let (memory_set, user_sp, entry_point) = MemorySet::from_elf(elf_data);
+            (memory_set, Some((user_sp, entry_point)))
+        } else {
+            let memory_set = MemorySet::from_existed_user(&parent_inner.memory_set);
+            #[cfg(feature = "verify-fork")]
+            if let Err(vpn) = verify_fork_equivalence(&parent_inner.memory_set, &memory_set) {
+                panic!("[kernel] fork copied page {:?} of pid {} wrong", vpn, self.pid.0);
+            }
+            (memory_set, None)
+        };
         let trap_cx_ppn = memory_set
             .translate(VirtAddr::from(TRAP_CONTEXT).into())
             .unwrap()
             .ppn();
+        // handler addresses only make sense in the parent's image
+        let (signal_handlers, signal_stack) = if new_space {
+            ([0; MAX_SIG], Vec::new())
+        } else {
+            // a fork inside a handler returns from it in both tasks
+            (parent_inner.signal_handlers, parent_inner.signal_stack.clone())
+        };
         // alloc a pid and a kernel stack in kernel space
-        let pid_handle = pid_alloc();
+        let (pid_handle, registration) = pid_alloc();
         let kernel_stack = KernelStack::new(&pid_handle);
         let kernel_stack_top = kernel_stack.get_top();
         let task_control_block = Arc::new(TaskControlBlock {
@@ -183,81 +393,99 @@ impl TaskControlBlock {
                     parent: Some(Arc::downgrade(self)),
                     children: Vec::new(),
                     exit_code: 0,
-                    call_time:0,
+                    // the child's first dispatch starts its clock, its counts start empty
+                    start_time_ms: None,
                     call_num:[0;MAX_SYSCALL_NUM],
                     pass:0,
                     stride:BIG_STRIDE/16,
                     priority:16,
+                    cpu_budget: parent_inner.cpu_budget,
+                    yield_min_us: parent_inner.yield_min_us,
+                    run_ticks: 0,
+                    stdout_buffer: Vec::with_capacity(CONSOLE_LINE_BUFFER_SIZE),
+                    cpu_time: 0,
+                    max_rss_pages: 0,
+                    last_run_start: 0,
+                    sched_epoch: 0,
+                    killed: false,
+                    depth: parent_inner.depth + 1,
+                    auto_reap: false,
+                    io_boost: parent_inner.io_boost,
+                    tls: parent_inner.tls,
+                    waiting_for: None,
+                    blocked_on: None,
+                    pending_signals: 0,
+                    signal_handlers,
+                    signal_stack,
+                    itimer_deadline_ms: 0,
+                    itimer_interval_ms: 0,
+                    cwd: parent_inner.cwd.clone(),
+                    name: if new_space {
+                        String::from(name)
+                    } else {
+                        parent_inner.name.clone()
+                    },
+                    sched_policy: parent_inner.sched_policy,
+                    priority_ceiling: parent_inner.priority_ceiling,
+                    syscall_filter: if new_space {
+                        None
+                    } else {
+                        parent_inner.syscall_filter.clone()
+                    },
                 })
             },
         });
+        registration.register(&task_control_block);
+        // the default priority may be above the ceiling the child inherits
+        if let Some(ceiling) = parent_inner.priority_ceiling {
+            let mut child_inner = task_control_block.inner_exclusive_access();
+            if child_inner.priority > ceiling {
+                child_inner.set_priority(ceiling);
+            }
+        }
         // add child
         parent_inner.children.push(task_control_block.clone());
-        // modify kernel_sp in trap_cx
         // **** access children PCB exclusively
         let trap_cx = task_control_block.inner_exclusive_access().get_trap_cx();
-        trap_cx.kernel_sp = kernel_stack_top;
-        // return
+        match entry {
+            // initialize trap_cx
+            Some((user_sp, entry_point)) => {
+                *trap_cx = TrapContext::app_init_context(
+                    entry_point,
+                    user_sp,
+                    KERNEL_SPACE.exclusive_access().token(),
+                    kernel_stack_top,
+                    trap_handler as usize,
+                );
+            }
+            // modify kernel_sp in the copied trap_cx
+            None => trap_cx.kernel_sp = kernel_stack_top,
+        }
         task_control_block
         // ---- release parent PCB automatically
         // **** release children PCB automatically
     }
-    pub fn spawn(self: &Arc<TaskControlBlock>,elf_data: &[u8])-> Arc<TaskControlBlock>{
-        // memory_set with elf program headers/trampoline/trap context/user stack
-        let (memory_set, user_sp, entry_point) = MemorySet::from_elf(elf_data);
-        let trap_cx_ppn = memory_set
-            .translate(VirtAddr::from(TRAP_CONTEXT).into())
-            .unwrap()
-            .ppn();
-        // ---- access parent PCB exclusively
-        let mut parent_inner = self.inner_exclusive_access();
-        // alloc a pid and a kernel stack in kernel space
-        let pid_handle = pid_alloc();
-        let kernel_stack = KernelStack::new(&pid_handle);
-        let kernel_stack_top = kernel_stack.get_top();
-        let task_control_block = Arc::new(TaskControlBlock {
-            pid: pid_handle,
-            kernel_stack,
-            inner: unsafe {
-                UPSafeCell::new(TaskControlBlockInner {
-                    trap_cx_ppn,
-                    base_size: parent_inner.base_size,
-                    task_cx: TaskContext::goto_trap_return(kernel_stack_top),
-                    task_status: TaskStatus::Ready,
-                    memory_set,
-                    parent: Some(Arc::downgrade(self)),
-                    children: Vec::new(),
-                    exit_code: 0,
-                    call_time:0,
-                    call_num:[0;MAX_SYSCALL_NUM],
-                    pass:0,
-                    stride:BIG_STRIDE/16,
-                    priority:16,
-                })
-            },
-        });
-        // add child
-        parent_inner.children.push(task_control_block.clone());
-        // modify kernel_sp in trap_cx
-        // **** access children PCB exclusively
-        //let trap_cx = task_control_block.inner_exclusive_access().get_trap_cx();
-        //trap_cx.kernel_sp = kernel_stack_top;
-        
-        // initialize trap_cx
-        let trap_cx = task_control_block.inner_exclusive_access().get_trap_cx();
-        *trap_cx = TrapContext::app_init_context(
-            entry_point,
-            user_sp,
-            KERNEL_SPACE.exclusive_access().token(),
-            task_control_block.kernel_stack.get_top(),
-            trap_handler as usize,
-        );
-        task_control_block
-    }
 
     pub fn getpid(&self) -> usize {
         self.pid.0
     }
+    /// Whether `pid` is a child of this task or, recursively, of one of its
+    /// children; zombies not yet reaped count too. The task itself does not.
+    pub fn has_descendant(&self, pid: usize) -> bool {
+        self.find_descendant(pid).is_some()
+    }
+    /// The task `pid` if [`has_descendant`](Self::has_descendant) holds for it
+    pub fn find_descendant(&self, pid: usize) -> Option<Arc<TaskControlBlock>> {
+        let mut pending: Vec<Arc<TaskControlBlock>> =
+            self.inner_exclusive_access().children.clone();
+        while let Some(task) = pending.pop() {
+            if task.getpid() == pid {
+                return Some(task);
+            }
+            pending.extend(task.inner_exclusive_access().children.iter().cloned());
+        }
+        None
+    }
 }
 
 #[derive(Copy, Clone, PartialEq)]
@@ -267,4 +495,19 @@ pub enum TaskStatus {
     Ready,
     Running,
     Zombie,
+    Blocked,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+/// what a blocked task is waiting for, its index in the blocked counts
+pub enum BlockReason {
+    Sleep,
+    Pause,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+/// scheduling policy: stride by priority, or plain FIFO round-robin
+pub enum SchedPolicy {
+    Stride,
+    RoundRobin,
 }
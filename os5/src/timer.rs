@@ -1,11 +1,20 @@
 //! RISC-V timer-related functionality
 
+use core::cmp::Ordering;
+
 use crate::config::CLOCK_FREQ;
 use crate::sbi::set_timer;
+use crate::sync::UPSafeCell;
+use crate::task::{wakeup_task, TaskControlBlock, SIGALRM};
+use alloc::collections::BinaryHeap;
+use alloc::sync::{Arc, Weak};
+use core::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use lazy_static::*;
 use riscv::register::time;
 
 const TICKS_PER_SEC: usize = 100;
 const MICRO_PER_SEC: usize = 1_000_000;
+const MSEC_PER_SEC: usize = 1000;
 
 /// read the `mtime` register
 pub fn get_time() -> usize {
@@ -17,7 +26,219 @@ pub fn get_time_us() -> usize {
     time::read() / (CLOCK_FREQ / MICRO_PER_SEC)
 }
 
+/// get current time in milliseconds
+pub fn get_time_ms() -> usize {
+    time::read() / (CLOCK_FREQ / MSEC_PER_SEC)
+}
+
+/// The clock read by sys_get_time
+pub const CLOCK_REALTIME: usize = 0;
+/// `CLOCK_REALTIME` minus `CLOCK_MONOTONIC` in us. There is no RTC to read
+/// the date from, so boot is taken to be late December 2023. The offset is a
+/// whole number of 65536s: the user lib's `get_time` keeps 16 bits of the
+/// seconds, and wraps exactly as it would if boot were the epoch.
+pub const REALTIME_OFFSET_US: usize = 26_000 * 65_536 * MICRO_PER_SEC;
+/// Time since boot, never jumps
+pub const CLOCK_MONOTONIC: usize = 1;
+/// The clock published in the vDSO page, advancing once per timer tick
+pub const CLOCK_MONOTONIC_COARSE: usize = 6;
+
+/// Granularity of `clock_id` in us, `None` for an unknown clock
+pub fn clock_resolution_us(clock_id: usize) -> Option<usize> {
+    match clock_id {
+        // read straight from `mtime`, rounded down to whole us
+        CLOCK_REALTIME | CLOCK_MONOTONIC => Some(1),
+        CLOCK_MONOTONIC_COARSE => Some(MICRO_PER_SEC / TICKS_PER_SEC),
+        _ => None,
+    }
+}
+
+/// The page mapped read-only at `VDSO` in every user space,
+/// so user code can read the time without a syscall
+#[repr(C, align(4096))]
+pub struct VdsoPage {
+    /// Time in us, as of the last timer interrupt
+    pub time_us: AtomicUsize,
+}
+
+pub static VDSO_PAGE: VdsoPage = VdsoPage {
+    time_us: AtomicUsize::new(0),
+};
+
+/// Publish the current time to the vDSO page
+pub fn update_vdso() {
+    VDSO_PAGE.time_us.store(get_time_us(), AtomicOrdering::Relaxed);
+}
+
+/// Clock cycles between two timer interrupts
+const TICK_INTERVAL: usize = CLOCK_FREQ / TICKS_PER_SEC;
+
+/// The comparator value of the last armed timer interrupt, 0 before the first
+static NEXT_TRIGGER: AtomicUsize = AtomicUsize::new(0);
+
 /// set the next timer interrupt
+///
+/// The comparator advances by exactly one interval from its previous value
+/// rather than from the current time, so handler latency does not add up into
+/// drift. Ticks missed entirely are skipped while staying on the same grid, and
+/// a call before the armed tick has fired (an early `wfi` return) keeps it armed.
 pub fn set_next_trigger() {
-    set_timer(get_time() + CLOCK_FREQ / TICKS_PER_SEC);
+    let now = get_time();
+    let prev = NEXT_TRIGGER.load(AtomicOrdering::Relaxed);
+    let next = if prev == 0 {
+        now + TICK_INTERVAL
+    } else if now < prev {
+        prev
+    } else {
+        prev + ((now - prev) / TICK_INTERVAL + 1) * TICK_INTERVAL
+    };
+    NEXT_TRIGGER.store(next, AtomicOrdering::Relaxed);
+    set_timer(next);
+}
+
+/// A blocked task to be woken up at `expire_ms`
+pub struct TimerCondVar {
+    pub expire_ms: usize,
+    pub task: Arc<TaskControlBlock>,
+}
+
+impl PartialEq for TimerCondVar {
+    fn eq(&self, other: &Self) -> bool {
+        self.expire_ms == other.expire_ms
+    }
+}
+impl Eq for TimerCondVar {}
+impl PartialOrd for TimerCondVar {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TimerCondVar {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // reversed, so that the BinaryHeap pops the earliest timer first
+        other.expire_ms.cmp(&self.expire_ms)
+    }
+}
+
+/// An interval timer of `task` due at `expire_ms`.
+///
+/// The task is held weakly so an armed timer does not keep an exited task
+/// alive, and an entry whose `expire_ms` no longer matches the task's deadline
+/// was superseded by sys_setitimer and is dropped when it comes up.
+pub struct IntervalTimer {
+    pub expire_ms: usize,
+    pub task: Weak<TaskControlBlock>,
+}
+
+impl PartialEq for IntervalTimer {
+    fn eq(&self, other: &Self) -> bool {
+        self.expire_ms == other.expire_ms
+    }
+}
+impl Eq for IntervalTimer {}
+impl PartialOrd for IntervalTimer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for IntervalTimer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.expire_ms.cmp(&self.expire_ms)
+    }
+}
+
+lazy_static! {
+    /// Sleeping tasks ordered by wake-up time
+    static ref TIMERS: UPSafeCell<BinaryHeap<TimerCondVar>> =
+        unsafe { UPSafeCell::new(BinaryHeap::<TimerCondVar>::new()) };
+    /// Armed interval timers ordered by expiry
+    static ref INTERVAL_TIMERS: UPSafeCell<BinaryHeap<IntervalTimer>> =
+        unsafe { UPSafeCell::new(BinaryHeap::<IntervalTimer>::new()) };
+}
+
+/// Wake `task` up at `expire_ms`
+pub fn add_timer(expire_ms: usize, task: Arc<TaskControlBlock>) {
+    let mut timers = TIMERS.exclusive_access();
+    // a task sleeps once at a time, a second entry would be a leaked one
+    debug_assert!(
+        !timers.iter().any(|timer| Arc::ptr_eq(&timer.task, &task)),
+        "pid {} already has a timer",
+        task.getpid()
+    );
+    timers.push(TimerCondVar { expire_ms, task });
+}
+
+/// Drop the timer of `task`, if it is sleeping
+pub fn remove_timer(task: &Arc<TaskControlBlock>) {
+    let mut timers = TIMERS.exclusive_access();
+    let rest: BinaryHeap<TimerCondVar> = timers
+        .drain()
+        .filter(|timer| !Arc::ptr_eq(&timer.task, task))
+        .collect();
+    *timers = rest;
+}
+
+/// Raise SIGALRM on `task` at `expire_ms`, which must be its itimer deadline
+pub fn add_interval_timer(expire_ms: usize, task: &Arc<TaskControlBlock>) {
+    let mut timers = INTERVAL_TIMERS.exclusive_access();
+    timers.push(IntervalTimer {
+        expire_ms,
+        task: Arc::downgrade(task),
+    });
+}
+
+/// Whether a sleeping task or an interval timer is still to be woken up
+pub fn timers_pending() -> bool {
+    !TIMERS.exclusive_access().is_empty() || !INTERVAL_TIMERS.exclusive_access().is_empty()
+}
+
+/// Put every task whose timer has expired back into the ready queue,
+/// and raise SIGALRM on every task whose interval timer has expired
+pub fn check_timer() {
+    let current_ms = get_time_ms();
+    let mut timers = TIMERS.exclusive_access();
+    while let Some(timer) = timers.peek() {
+        if timer.expire_ms <= current_ms {
+            wakeup_task(Arc::clone(&timer.task));
+            timers.pop();
+        } else {
+            break;
+        }
+    }
+    drop(timers);
+    loop {
+        let mut interval_timers = INTERVAL_TIMERS.exclusive_access();
+        match interval_timers.peek() {
+            Some(timer) if timer.expire_ms <= current_ms => {}
+            _ => break,
+        }
+        let timer = interval_timers.pop().unwrap();
+        drop(interval_timers);
+        if let Some(task) = timer.task.upgrade() {
+            fire_interval_timer(timer.expire_ms, current_ms, task);
+        }
+    }
+}
+
+fn fire_interval_timer(expire_ms: usize, current_ms: usize, task: Arc<TaskControlBlock>) {
+    let mut inner = task.inner_exclusive_access();
+    if inner.is_zombie() || inner.itimer_deadline_ms != expire_ms {
+        return;
+    }
+    inner.pending_signals |= 1 << SIGALRM;
+    let interval = inner.itimer_interval_ms;
+    if interval == 0 {
+        inner.itimer_deadline_ms = 0;
+    } else {
+        // expirations missed while the timer could not run are folded into this one
+        let next = expire_ms + ((current_ms - expire_ms) / interval + 1) * interval;
+        inner.itimer_deadline_ms = next;
+        add_interval_timer(next, &task);
+    }
+    drop(inner);
+    // like sys_kill, a paused or sleeping task has to run to see the signal
+    remove_timer(&task);
+    wakeup_task(task);
 }
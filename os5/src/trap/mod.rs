@@ -16,10 +16,15 @@ mod context;
 
 use crate::config::{TRAMPOLINE, TRAP_CONTEXT};
 use crate::syscall::syscall;
+use crate::task::processor::{
+    charge_current_tick, clear_current_waiting, current_killed, grow_down_current,
+    swap_in_current, user_sp_ok,
+};
 use crate::task::{
-    current_trap_cx, current_user_token, exit_current_and_run_next, suspend_current_and_run_next,
+    check_zombie_leak, current_trap_cx, current_user_token, exit_current_and_run_next,
+    handle_current_signals, suspend_current_and_run_next,
 };
-use crate::timer::set_next_trigger;
+use crate::timer::{check_timer, set_next_trigger, update_vdso};
 use riscv::register::{
     mtvec::TrapMode,
     scause::{self, Exception, Interrupt, Trap},
@@ -72,14 +77,29 @@ pub fn trap_handler() -> ! {
         | Trap::Exception(Exception::InstructionPageFault)
         | Trap::Exception(Exception::LoadFault)
         | Trap::Exception(Exception::LoadPageFault) => {
-            println!(
-                "[kernel] {:?} in application, bad addr = {:#x}, bad instruction = {:#x}, core dumped.",
-                scause.cause(),
-                stval,
-                current_trap_cx().sepc,
-            );
-            // page fault exit code
-            exit_current_and_run_next(-2);
+            // a swapped out page is mapped again and the access retried,
+            // as is one a grow-down stack extends to
+            if !swap_in_current(stval) && !grow_down_current(stval) {
+                // a wild sp is the likelier culprit, name it
+                let user_sp = current_trap_cx().x[2];
+                if !user_sp_ok(user_sp) {
+                    println!(
+                        "[kernel] bad user sp = {:#x} at instruction = {:#x}, killed.",
+                        user_sp,
+                        current_trap_cx().sepc,
+                    );
+                    // page fault exit code
+                    exit_current_and_run_next(-2);
+                }
+                println!(
+                    "[kernel] {:?} in application, bad addr = {:#x}, bad instruction = {:#x}, core dumped.",
+                    scause.cause(),
+                    stval,
+                    current_trap_cx().sepc,
+                );
+                // page fault exit code
+                exit_current_and_run_next(-2);
+            }
         }
         Trap::Exception(Exception::IllegalInstruction) => {
             println!("[kernel] IllegalInstruction in application, core dumped.");
@@ -88,7 +108,18 @@ pub fn trap_handler() -> ! {
         }
         Trap::Interrupt(Interrupt::SupervisorTimer) => {
             set_next_trigger();
-            suspend_current_and_run_next();
+            update_vdso();
+            check_timer();
+            check_zombie_leak();
+            if charge_current_tick() {
+                println!("[kernel] CPU budget exhausted in application, killed.");
+                // cpu budget exit code
+                exit_current_and_run_next(-4);
+            } else {
+                // preempted while running user code, so not just spinning in waitpid
+                clear_current_waiting();
+                suspend_current_and_run_next();
+            }
         }
         _ => {
             panic!(
@@ -103,6 +134,11 @@ pub fn trap_handler() -> ! {
 
 #[no_mangle]
 pub fn trap_return() -> ! {
+    if current_killed() {
+        // killed exit code
+        exit_current_and_run_next(-9);
+    }
+    handle_current_signals();
     set_user_trap_entry();
     let trap_cx_ptr = TRAP_CONTEXT;
     let user_satp = current_user_token();
@@ -111,8 +147,12 @@ pub fn trap_return() -> ! {
         fn __restore();
     }
     let restore_va = __restore as usize - __alltraps as usize + TRAMPOLINE;
+    // syscalls write user memory through the kernel's identity mapping of its
+    // frames: `fence rw, rw` orders those stores before anything user mode
+    // reads through its own mapping, `fence.i` does the same for fetches
     unsafe {
         core::arch::asm!(
+            "fence rw, rw",
             "fence.i",
             "jr {restore_va}",
             restore_va = in(reg) restore_va,
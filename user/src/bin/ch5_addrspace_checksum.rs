@@ -0,0 +1,47 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::{EFAULT, EINVAL};
+use user_lib::{addrspace_checksum, exit, fork, mmap, sys_addrspace_checksum, waitpid};
+
+/// 程序行为：fork 前后父子进程的地址空间校验和相同（不含正在使用的栈）；子进程
+/// 写了一页私有内存后自己的校验和改变，父进程的不变。64 位校验和经指针写回，
+/// 指针未对齐或不可写时分别返回 -EINVAL 与 -EFAULT。
+
+/// 理想输出：
+/// Test addrspace checksum OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    assert_eq!(mmap(start, 4096 * 2, 3), 0);
+    let before = addrspace_checksum();
+    // nothing outside the stack changed, so asking again gives the same hash
+    assert_eq!(addrspace_checksum(), before);
+    let pid = fork();
+    if pid == 0 {
+        if addrspace_checksum() != before {
+            exit(1);
+        }
+        unsafe { ((start + 4096) as *mut u8).write_volatile(1) };
+        if addrspace_checksum() == before {
+            exit(2);
+        }
+        exit(0);
+    }
+    assert_eq!(addrspace_checksum(), before);
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    // the child wrote its own copy of the page
+    assert_eq!(addrspace_checksum(), before);
+    let misaligned = unsafe { &mut *((start + 1) as *mut u64) };
+    assert_eq!(sys_addrspace_checksum(misaligned), -EINVAL);
+    let unmapped = unsafe { &mut *(0x30000000 as *mut u64) };
+    assert_eq!(sys_addrspace_checksum(unmapped), -EFAULT);
+    println!("Test addrspace checksum OK!");
+    0
+}
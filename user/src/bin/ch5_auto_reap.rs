@@ -0,0 +1,44 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::ECHILD;
+use user_lib::{exit, fork, set_auto_reap, wait, yield_, zombie_count};
+
+/// 程序行为：父进程开启自动回收后 fork 许多立即退出的子进程，期间僵尸进程数
+/// 从不增加；子进程全部退出后 wait 没有可回收的子进程，返回 -ECHILD。关闭自动
+/// 回收后退出的子进程又会变成僵尸，需要 wait 回收。
+
+/// 理想输出：
+/// Test auto reap OK!
+
+const CHILDREN: usize = 32;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let zombies = zombie_count(None);
+    assert_eq!(set_auto_reap(true), 0);
+    for _ in 0..CHILDREN {
+        let pid = fork();
+        if pid == 0 {
+            exit(7);
+        }
+        assert!(pid > 0);
+        yield_();
+        assert_eq!(zombie_count(None), zombies);
+    }
+    // waits while children are alive, finds none left once they have exited
+    let mut exit_code: i32 = 0;
+    assert_eq!(wait(&mut exit_code), -ECHILD);
+    assert_eq!(zombie_count(None), zombies);
+    assert_eq!(set_auto_reap(false), 1);
+    if fork() == 0 {
+        exit(7);
+    }
+    assert!(wait(&mut exit_code) > 0);
+    assert_eq!(exit_code, 7);
+    println!("Test auto reap OK!");
+    0
+}
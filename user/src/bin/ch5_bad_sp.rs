@@ -0,0 +1,53 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::arch::asm;
+use user_lib::{exit, fork, waitpid};
+
+/// 程序行为：子进程把 sp 改成非法值后向栈上写入，内核处理这次缺页时发现 sp 不在
+/// 任何用户栈内，打印诊断信息并以退出码 -2 结束该子进程；父进程不受影响。
+/// 另一个子进程把 sp 暂作普通寄存器使用并跨越若干次时钟中断，之后恢复，不应被杀死。
+
+/// 理想输出：
+/// [kernel] bad user sp = 0xdeadbeef0 at instruction = ..., killed.
+/// Test bad sp OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        unsafe {
+            asm!(
+                "li sp, 0xdeadbeef0",
+                "sd zero, -8(sp)",
+                "1: j 1b",
+                options(noreturn),
+            );
+        }
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -2);
+    let pid = fork();
+    if pid == 0 {
+        unsafe {
+            asm!(
+                "mv {saved}, sp",
+                "li sp, 0xdeadbeef0",
+                "2: addi {count}, {count}, -1",
+                "bnez {count}, 2b",
+                "mv sp, {saved}",
+                saved = out(reg) _,
+                count = inout(reg) 50_000_000usize => _,
+            );
+        }
+        exit(0);
+    }
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    println!("Test bad sp OK!");
+    0
+}
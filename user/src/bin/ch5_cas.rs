@@ -0,0 +1,50 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::EFAULT;
+use user_lib::{cas, exit, fork, mmap, waitpid, MMAP_SHARED};
+
+/// 程序行为：映射一页 fork 后父子进程共享的内存，父子进程各自用 cas
+/// 对其中的计数器做 N 次自增，时钟中断会让两者交错执行，最终计数应恰为 2N；
+/// 未对齐或未映射的地址返回 -EFAULT。
+
+/// 理想输出：
+/// Test cas OK!
+
+const N: usize = 2000;
+
+fn increment(counter: *mut usize) {
+    loop {
+        let current = unsafe { counter.read_volatile() };
+        if cas(counter, current, current + 1) == current as isize {
+            return;
+        }
+    }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    assert_eq!(mmap(start, 4096, 3 | MMAP_SHARED), 0);
+    let counter = start as *mut usize;
+    assert_eq!(cas(counter, 0, 0), 0);
+    assert_eq!(cas((start + 1) as *mut usize, 0, 1), -EFAULT);
+    assert_eq!(cas(0x20000000 as *mut usize, 0, 1), -EFAULT);
+
+    let pid = fork();
+    for _ in 0..N {
+        increment(counter);
+    }
+    if pid == 0 {
+        exit(0);
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert_eq!(unsafe { counter.read_volatile() }, 2 * N);
+    println!("Test cas OK!");
+    0
+}
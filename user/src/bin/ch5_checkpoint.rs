@@ -0,0 +1,65 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::arch::asm;
+use core::mem::size_of;
+use user_lib::{checkpoint, syscall, Checkpoint, SYSCALL_CHECKPOINT, SYSCALL_RESTORE};
+
+/// 程序行为：在 s2、s3 中放入已知值后 checkpoint，随后改写这两个寄存器再 restore，
+/// 执行回到 checkpoint 之后且返回 1，s2、s3 恢复为 checkpoint 时的值。
+/// 长度不符的缓冲区返回 -EINVAL。
+
+/// 理想输出：
+/// Test checkpoint OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut buf = Checkpoint::default();
+    let ptr = &mut buf as *mut Checkpoint as usize;
+    assert_eq!(syscall(SYSCALL_CHECKPOINT, [ptr, size_of::<Checkpoint>() - 1, 0]), -22);
+    assert_eq!(syscall(SYSCALL_RESTORE, [ptr, size_of::<Checkpoint>() + 1, 0]), -22);
+    assert_eq!(checkpoint(&mut buf), 0);
+    assert_eq!(buf.x[10], 1);
+    assert!(buf.sepc != 0);
+
+    let ret: isize;
+    let s2: usize;
+    let s3: usize;
+    unsafe {
+        // 整个过程都在同一段汇编里，栈没有变化
+        asm!(
+            "li s2, 0x1111",
+            "li s3, 0x3333",
+            "mv a0, t0",
+            "mv a1, t1",
+            "mv a7, t2",
+            "ecall",
+            "bnez a0, 1f",
+            "li s2, 0x2222",
+            "li s3, 0x4444",
+            "mv a0, t0",
+            "mv a1, t1",
+            "mv a7, t3",
+            "ecall",
+            "1:",
+            in("t0") ptr,
+            in("t1") size_of::<Checkpoint>(),
+            in("t2") SYSCALL_CHECKPOINT,
+            in("t3") SYSCALL_RESTORE,
+            out("a0") ret,
+            out("a1") _,
+            out("a7") _,
+            out("s2") s2,
+            out("s3") s3,
+        );
+    }
+    assert_eq!(ret, 1);
+    assert_eq!(s2, 0x1111);
+    assert_eq!(s3, 0x3333);
+    assert_eq!(buf.x[18], 0x1111);
+    println!("Test checkpoint OK!");
+    0
+}
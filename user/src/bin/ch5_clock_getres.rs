@@ -0,0 +1,41 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::EINVAL;
+use user_lib::{
+    clock_getres, vdso_time_us, TimeVal, CLOCK_MONOTONIC, CLOCK_MONOTONIC_COARSE, CLOCK_REALTIME,
+};
+
+/// 程序行为：get_time 所用时钟的精度为 1us；vDSO 中的粗粒度时钟精度等于一个时钟中断周期
+/// （每秒 100 次，即 10ms），且其相邻两次更新的间隔与之一致；未知时钟返回 -EINVAL。
+
+/// 理想输出：
+/// Test clock_getres OK!
+
+fn res_us(clock_id: usize) -> usize {
+    let mut res = TimeVal::new();
+    assert_eq!(clock_getres(clock_id, &mut res), 0);
+    res.sec * 1_000_000 + res.usec
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(res_us(CLOCK_REALTIME), 1);
+    assert_eq!(res_us(CLOCK_MONOTONIC), 1);
+    let tick_us = res_us(CLOCK_MONOTONIC_COARSE);
+    assert_eq!(tick_us, 10_000);
+    let mut res = TimeVal::new();
+    assert_eq!(clock_getres(3, &mut res), -EINVAL);
+    // 粗粒度时钟每次前进约一个周期
+    let mut last = vdso_time_us();
+    while vdso_time_us() == last {}
+    last = vdso_time_us();
+    while vdso_time_us() == last {}
+    let step = vdso_time_us() - last;
+    assert!(step + 2_000 >= tick_us, "coarse clock stepped {}us", step);
+    println!("Test clock_getres OK!");
+    0
+}
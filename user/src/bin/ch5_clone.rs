@@ -0,0 +1,49 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{exit, fork, getpid, getppid, kill, sigaction, spawn, waitpid, SIGUSR1};
+
+/// 程序行为：fork 与 spawn 共用同一套创建子进程的逻辑后，行为应与之前一致。
+/// fork 的子进程得到父进程数据的副本并继承信号处理函数，其修改不影响父进程；
+/// spawn 的子进程运行新程序，正常退出并返回该程序的退出码。
+
+/// 理想输出：
+/// Test clone OK!
+
+static VALUE: AtomicUsize = AtomicUsize::new(0);
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+fn on_usr1(_signum: usize) {
+    HANDLED.fetch_add(1, Ordering::SeqCst);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let parent = getpid();
+    VALUE.store(42, Ordering::SeqCst);
+    assert_eq!(sigaction(SIGUSR1, Some(on_usr1)), 0);
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(getppid(), parent);
+        assert_eq!(VALUE.load(Ordering::SeqCst), 42);
+        VALUE.store(7, Ordering::SeqCst);
+        assert_eq!(kill(getpid() as usize, SIGUSR1), 0);
+        exit(HANDLED.load(Ordering::SeqCst) as i32);
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 1);
+    assert_eq!(VALUE.load(Ordering::SeqCst), 42);
+    assert_eq!(HANDLED.load(Ordering::SeqCst), 0);
+
+    let pid = spawn("ch5_exit0\0");
+    assert!(pid > 0);
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 66778);
+    println!("Test clone OK!");
+    0
+}
@@ -0,0 +1,39 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{fork, get_time, set_cpu_budget, waitpid};
+
+/// 程序行为：子进程设置 5 个时钟周期的 CPU 预算后陷入死循环，应被内核以 -4 杀死。
+
+/// 理想输出：
+/// Test cpu budget OK!
+
+const BUDGET: usize = 5;
+/// 每个时钟周期 10ms
+const TICK_MS: isize = 10;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start = get_time();
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(set_cpu_budget(BUDGET), 0);
+        let mut i: usize = 0;
+        loop {
+            i = i.wrapping_add(1);
+            unsafe {
+                core::ptr::write_volatile(&mut i as *mut usize, i);
+            }
+        }
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -4);
+    // 子进程至少运行了 BUDGET 个时钟周期
+    assert!(get_time() - start >= (BUDGET as isize - 1) * TICK_MS);
+    println!("Test cpu budget OK!");
+    0
+}
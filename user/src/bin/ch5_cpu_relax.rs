@@ -0,0 +1,51 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{cpu_relax, fork, get_time, mmap, waitpid_cpu_time, MMAP_SHARED};
+
+/// 程序行为：子进程做一段纯计算，父进程用 cpu_relax 自旋等它在共享页上置完成标志。
+/// 有别的任务就绪时 cpu_relax 会让出 CPU，所以计算进程几乎独占 CPU：
+/// 它的 CPU 时间至少占这段时间的 80%，而父进程的自旋次数远少于计算循环次数。
+
+/// 理想输出：
+/// Test cpu relax OK!
+
+const WORK: usize = 20_000_000;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    assert_eq!(mmap(start, 4096, 3 | MMAP_SHARED), 0);
+    let done = start as *mut usize;
+    let begin = get_time();
+    let pid = fork();
+    if pid == 0 {
+        let mut acc: usize = 0;
+        for i in 0..WORK {
+            acc = acc.wrapping_mul(31).wrapping_add(i);
+            unsafe { core::ptr::write_volatile(&mut acc, acc) };
+        }
+        unsafe { done.write_volatile(1) };
+        return 0;
+    }
+    let mut spins: usize = 0;
+    let mut yielded: usize = 0;
+    while unsafe { done.read_volatile() } == 0 {
+        spins += 1;
+        yielded += cpu_relax() as usize;
+    }
+    let elapsed_us = (get_time() - begin) as usize * 1000;
+    let mut exit_code: i32 = -1;
+    let mut cpu_time: usize = 0;
+    assert_eq!(waitpid_cpu_time(pid as usize, &mut exit_code, &mut cpu_time), pid);
+    assert_eq!(exit_code, 0);
+    // 计算进程一直就绪，每次自旋都该让出 CPU
+    assert_eq!(yielded, spins);
+    assert!(spins < WORK / 1000);
+    assert!(cpu_time * 10 >= elapsed_us * 8);
+    println!("Test cpu relax OK!");
+    0
+}
@@ -0,0 +1,52 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::{ENAMETOOLONG, ERANGE};
+use user_lib::{chdir, exit, fork, getcwd, sleep_blocking, waitpid};
+
+/// 程序行为：设置当前工作目录后 fork，子进程继承父进程的工作目录；
+/// 父进程随后修改自己的工作目录，子进程的工作目录不受影响。
+/// 相对路径、`.` 与 `..` 会被规范化，过长的路径与过小的缓冲区被拒绝。
+
+/// 理想输出：
+/// Test cwd OK!
+
+fn cwd_is(expected: &str) -> bool {
+    let mut buf = [0u8; 64];
+    let len = getcwd(&mut buf);
+    len == expected.len() as isize + 1 && &buf[..expected.len()] == expected.as_bytes()
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert!(cwd_is("/"));
+    assert_eq!(chdir("/home//user/./docs/..\0"), 0);
+    assert!(cwd_is("/home/user"));
+    assert_eq!(chdir("src\0"), 0);
+    assert!(cwd_is("/home/user/src"));
+    let mut small = [0u8; 4];
+    assert_eq!(getcwd(&mut small), -ERANGE);
+    let long = [b'a'; 300];
+    let mut path = [0u8; 301];
+    path[..300].copy_from_slice(&long);
+    assert_eq!(chdir(core::str::from_utf8(&path).unwrap()), -ENAMETOOLONG);
+    assert!(cwd_is("/home/user/src"));
+
+    let pid = fork();
+    if pid == 0 {
+        assert!(cwd_is("/home/user/src"));
+        // 等父进程修改完自己的工作目录
+        sleep_blocking(100);
+        exit(if cwd_is("/home/user/src") { 0 } else { 1 });
+    }
+    assert_eq!(chdir("/tmp\0"), 0);
+    assert!(cwd_is("/tmp"));
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    println!("Test cwd OK!");
+    0
+}
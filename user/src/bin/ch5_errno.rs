@@ -0,0 +1,98 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::{ECHILD, EEXIST, EFAULT, EINVAL, ENOENT, ENOMEM, EPERM, ESRCH};
+use user_lib::{
+    clock_getres, exec, exec_mem, fork, getpid, kill, mmap, mmap_app, setpriority, spawn,
+    sys_get_time, sys_task_info_of, waitpid, yield_, TaskInfo, TimeVal, TASK_INFO_VERSION,
+};
+
+/// 程序行为：针对 fork、mmap、exec、kill、waitpid 以及 exec_mem、spawn、get_time、clock_getres、
+/// setpriority、mmap_app、task_info_of 的各类失败情形，检查返回的具体错误码；
+/// 被 kill 的子进程以 -9 退出。
+
+/// 理想输出：
+/// Test errno OK!
+
+const SIGKILL: usize = 9;
+const CHUNK: usize = 0x100000;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    // exec
+    assert_eq!(exec("no_such_app\0", &[core::ptr::null::<u8>()]), -ENOENT);
+    assert_eq!(exec("\0", &[core::ptr::null::<u8>()]), -ENOENT);
+    // exec_mem
+    assert_eq!(exec_mem(0x30000000, 4096), -EFAULT);
+    assert_eq!(exec_mem(0x30000000, 0), -EINVAL);
+    // spawn
+    assert_eq!(spawn("no_such_app\0"), -ENOENT);
+    // get_time 与 clock_getres
+    let time = TimeVal::new();
+    assert_eq!(sys_get_time(&time, 1), -EINVAL);
+    assert_eq!(sys_get_time(unsafe { &*(0x30000000 as *const TimeVal) }, 0), -EFAULT);
+    let mut res = TimeVal::new();
+    assert_eq!(clock_getres(3, &mut res), -EINVAL);
+    // task_info_of
+    let mut info = TaskInfo::new();
+    let me = getpid() as usize;
+    assert_eq!(sys_task_info_of(me, &mut info, TASK_INFO_VERSION + 1), -EINVAL);
+    assert_eq!(sys_task_info_of(99999, &mut info, TASK_INFO_VERSION), -ESRCH);
+    let bad_info = unsafe { &mut *(0x30000000 as *mut TaskInfo) };
+    assert_eq!(sys_task_info_of(me, bad_info, TASK_INFO_VERSION), -EFAULT);
+    // setpriority
+    assert_eq!(setpriority(me, 1), -EINVAL);
+    assert_eq!(setpriority(99999, 8), -ESRCH);
+    assert_eq!(setpriority(0, 8), -EPERM);
+    // waitpid
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(99999, &mut exit_code), -ECHILD);
+    // kill
+    assert_eq!(kill(99999, SIGKILL), -ESRCH);
+    assert_eq!(kill(0, SIGKILL), -EPERM);
+    let pid = fork();
+    if pid == 0 {
+        loop {
+            yield_();
+        }
+    }
+    assert_eq!(kill(pid as usize, 5), -EINVAL);
+    assert_eq!(kill(pid as usize, 0), 0);
+    assert_eq!(kill(pid as usize, SIGKILL), 0);
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -9);
+    assert_eq!(kill(pid as usize, 0), -ESRCH);
+    // mmap
+    let start: usize = 0x20000000;
+    assert_eq!(mmap(start + 1, 4096, 3), -EINVAL);
+    assert_eq!(mmap(start, 4096, 0), -EINVAL);
+    assert_eq!(mmap(start, 1 << 30, 3), -ENOMEM);
+    assert_eq!(mmap(start, usize::MAX - start + 1, 3), -ENOMEM);
+    // mmap_app
+    let image: usize = 0x10000000;
+    assert_eq!(mmap_app(image + 1, "ch5b_initproc\0"), -EINVAL);
+    assert_eq!(mmap_app(image, "no_such_app\0"), -ENOENT);
+    assert_eq!(mmap_app(usize::MAX & !0xfff, "ch5b_initproc\0"), -ENOMEM);
+    assert!(mmap_app(image, "ch5b_initproc\0") > 0);
+    assert_eq!(mmap_app(image, "ch5b_initproc\0"), -EEXIST);
+    // 耗尽物理内存后 fork 也应失败
+    let mut end = start;
+    loop {
+        match mmap(end, CHUNK, 3) {
+            0 => end += CHUNK,
+            ret => {
+                assert_eq!(ret, -ENOMEM);
+                break;
+            }
+        }
+    }
+    assert!(end > start);
+    assert_eq!(mmap(start, CHUNK, 3), -EEXIST);
+    assert_eq!(fork(), -ENOMEM);
+    // 映射的内存在进程退出时统一回收
+    println!("Test errno OK!");
+    0
+}
@@ -0,0 +1,87 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::arch::asm;
+use user_lib::errno::EINVAL;
+use user_lib::{mmap, mmap_app, SYSCALL_EXEC_MEM};
+
+/// 程序行为：把 ch5_exit0 的 ELF 复制到可写缓冲区后改坏：截断的映像，以及两个
+/// LOAD 段落在同一页上的映像。对它们调用 exec_mem 都返回 -EINVAL，内核不会 panic，
+/// 调用前放进 s2~s4 的值和栈上的数据在失败后保持不变，进程继续正常执行。
+
+/// 理想输出：
+/// Test exec corrupt OK!
+
+const IMAGE: usize = 0x10000000;
+const COPY: usize = 0x20000000;
+const PT_LOAD: u32 = 1;
+
+fn read<T: Copy>(addr: usize) -> T {
+    unsafe { (addr as *const T).read_unaligned() }
+}
+
+/// 第二个 LOAD 程序头改成和第一个一样
+fn overlap_segments(elf: usize) {
+    let phoff: u64 = read(elf + 0x20);
+    let phentsize: u16 = read(elf + 0x36);
+    let phnum: u16 = read(elf + 0x38);
+    let mut loads = (0..phnum as usize)
+        .map(|i| elf + phoff as usize + i * phentsize as usize)
+        .filter(|&ph| read::<u32>(ph) == PT_LOAD);
+    let first = loads.next().unwrap();
+    let second = loads.next().unwrap();
+    unsafe {
+        core::ptr::copy_nonoverlapping(first as *const u8, second as *mut u8, phentsize as usize);
+    }
+}
+
+/// 带着已知的 s2~s4 调用 exec_mem，返回结果和调用后的 s2~s4
+fn exec_with_known_regs(addr: usize, len: usize) -> (isize, [usize; 3]) {
+    let ret: isize;
+    let (s2, s3, s4): (usize, usize, usize);
+    unsafe {
+        asm!(
+            "li s2, 0x1234",
+            "li s3, 0x5678",
+            "li s4, 0x9abc",
+            "ecall",
+            inlateout("a0") addr => ret,
+            in("a1") len,
+            in("a7") SYSCALL_EXEC_MEM,
+            out("s2") s2,
+            out("s3") s3,
+            out("s4") s4,
+        );
+    }
+    (ret, [s2, s3, s4])
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let len = mmap_app(IMAGE, "ch5_exit0\0");
+    assert!(len > 0);
+    let len = len as usize;
+    assert_eq!(mmap(COPY, (len + 4095) / 4096 * 4096, 3), 0);
+    unsafe {
+        core::ptr::copy_nonoverlapping(IMAGE as *const u8, COPY as *mut u8, len);
+    }
+    overlap_segments(COPY);
+
+    let mut stack = [0usize; 64];
+    for (i, word) in stack.iter_mut().enumerate() {
+        unsafe { core::ptr::write_volatile(word, i * 0x1111) };
+    }
+    for image_len in [len, 64] {
+        let (ret, regs) = exec_with_known_regs(COPY, image_len);
+        assert_eq!(ret, -EINVAL);
+        assert_eq!(regs, [0x1234, 0x5678, 0x9abc]);
+    }
+    for (i, word) in stack.iter().enumerate() {
+        assert_eq!(unsafe { core::ptr::read_volatile(word) }, i * 0x1111);
+    }
+    println!("Test exec corrupt OK!");
+    0
+}
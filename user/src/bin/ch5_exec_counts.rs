@@ -0,0 +1,20 @@
+#![no_std]
+#![no_main]
+
+extern crate user_lib;
+
+use user_lib::{exit, task_info, TaskInfo, SYSCALL_GETPID};
+
+/// 程序行为：供 ch5_exec_syscall_times 通过 exec 载入，以本进程已记录的
+/// getpid 次数作为退出码，自身不调用 getpid。单独运行时退出码为 0。
+
+/// 理想输出：无
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let info = TaskInfo::new();
+    if task_info(&info) != 0 {
+        exit(-1);
+    }
+    info.syscall_times[SYSCALL_GETPID] as i32
+}
@@ -0,0 +1,30 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::ENOENT;
+use user_lib::{exec, mmap, spawn};
+
+/// 程序行为：以空字符串作为路径调用 exec 与 spawn，两者都应直接返回 -ENOENT 且不触发异常；
+/// 空路径位于已映射页的最后一个字节时，内核不应访问其后未映射的下一页。
+
+/// 理想输出：
+/// Test exec empty path OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(exec("\0", &[core::ptr::null::<u8>()]), -ENOENT);
+    assert_eq!(spawn("\0"), -ENOENT);
+    let start: usize = 0x10000000;
+    assert_eq!(mmap(start, 4096, 3), 0);
+    // 新映射的页全为 0，最后一个字节即是空字符串
+    let path = unsafe {
+        core::str::from_utf8_unchecked(core::slice::from_raw_parts((start + 4095) as *const u8, 1))
+    };
+    assert_eq!(exec(path, &[core::ptr::null::<u8>()]), -ENOENT);
+    assert_eq!(spawn(path), -ENOENT);
+    println!("Test exec empty path OK!");
+    0
+}
@@ -0,0 +1,38 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::{EFAULT, EINVAL};
+use user_lib::{exec_mem, fork, mmap, mmap_app, waitpid};
+
+/// 程序行为：子进程用 mmap_app 把 ch5_exit0 的 ELF 映射进自己的地址空间，
+/// 再通过 exec_mem 从内存执行它，父进程应收到退出码 66778；
+/// 未映射的缓冲区返回 -EFAULT，内容不是 ELF 的缓冲区与过长的缓冲区返回 -EINVAL。
+
+/// 理想输出：
+/// Test exec_mem OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let image: usize = 0x10000000;
+    let garbage: usize = 0x20000000;
+    assert_eq!(exec_mem(0x30000000, 4096), -EFAULT);
+    assert_eq!(mmap(garbage, 4096, 3), 0);
+    assert_eq!(exec_mem(garbage, 4096), -EINVAL);
+    // 长度远超任何 app 的缓冲区在分配内核内存前就被拒绝
+    assert_eq!(exec_mem(garbage, usize::MAX - garbage), -EINVAL);
+    let pid = fork();
+    if pid == 0 {
+        let len = mmap_app(image, "ch5_exit0\0");
+        assert!(len > 0);
+        exec_mem(image, len as usize);
+        panic!("exec_mem should not return");
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 66778);
+    println!("Test exec_mem OK!");
+    0
+}
@@ -0,0 +1,45 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::EINVAL;
+use user_lib::{exec, exec_flags, fork, getpid, waitpid, EXEC_KEEP_SYSCALL_TIMES};
+
+/// 程序行为：子进程先调用若干次 getpid 再 exec 到 ch5_exec_counts，
+/// 后者以新镜像中看到的 getpid 次数作为退出码。默认的 exec 会清零计数，
+/// 带 EXEC_KEEP_SYSCALL_TIMES 的 exec 则保留旧镜像的计数。
+
+/// 理想输出：
+/// Test exec syscall_times OK!
+
+const CALLS: usize = 5;
+
+fn run(keep: bool) -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        for _ in 0..CALLS {
+            getpid();
+        }
+        if keep {
+            let args = [core::ptr::null::<u8>()];
+            exec_flags("ch5_exec_counts\0", &args, EXEC_KEEP_SYSCALL_TIMES);
+        } else {
+            exec("ch5_exec_counts\0", &[core::ptr::null::<u8>()]);
+        }
+        panic!("exec failed");
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    exit_code
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(run(false), 0);
+    assert_eq!(run(true), CALLS as i32);
+    assert_eq!(exec_flags("ch5_exec_counts\0", &[core::ptr::null::<u8>()], 2), -EINVAL);
+    println!("Test exec syscall_times OK!");
+    0
+}
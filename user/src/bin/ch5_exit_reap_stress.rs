@@ -0,0 +1,49 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, wait, yield_};
+
+/// 程序行为：父进程不断 fork 子进程，子进程再 fork 孙进程后立即退出，
+/// 孙进程被 initproc 收养；父进程在 yield 之间交替回收子进程。
+/// 用于压测 exit / waitpid / 收养路径上对 TCB 的借用，不应出现 already borrowed panic。
+
+/// 理想输出：
+/// Test exit/reap stress OK!
+
+const ROUNDS: usize = 8;
+const CHILDREN: usize = 8;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    for _ in 0..ROUNDS {
+        for i in 0..CHILDREN {
+            let pid = fork();
+            assert!(pid >= 0);
+            if pid == 0 {
+                if i % 2 == 0 && fork() == 0 {
+                    yield_();
+                    exit(0);
+                }
+                for _ in 0..i {
+                    yield_();
+                }
+                exit(i as i32);
+            }
+            yield_();
+        }
+        let mut reaped = 0;
+        let mut exit_code: i32 = 0;
+        while reaped < CHILDREN {
+            assert!(wait(&mut exit_code) > 0);
+            assert!((exit_code as usize) < CHILDREN);
+            reaped += 1;
+            yield_();
+        }
+        assert!(wait(&mut exit_code) < 0);
+    }
+    println!("Test exit/reap stress OK!");
+    0
+}
@@ -0,0 +1,41 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::EPERM;
+use user_lib::{fork, waitpid};
+
+/// 程序行为：每个进程都 fork 一个子进程，形成一条进程链。
+/// 链的深度达到内核配置的上限（16）后 fork 返回 -EPERM，整条链随之逐层退出。
+
+/// 理想输出：
+/// chain length = ...
+/// Test fork depth OK!
+
+const MAX_PROC_DEPTH: i32 = 16;
+
+/// 返回以当前进程为根还能继续向下 fork 的层数
+fn chain() -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        user_lib::exit(chain() + 1);
+    }
+    if pid < 0 {
+        assert_eq!(pid, -EPERM);
+        return 0;
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    exit_code
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let length = chain();
+    println!("chain length = {}", length);
+    assert!(length > 0 && length < MAX_PROC_DEPTH);
+    println!("Test fork depth OK!");
+    0
+}
@@ -0,0 +1,36 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{fork, translate, waitpid};
+
+/// 程序行为：fork 之后，子进程只读的代码段与父进程共享同一物理页，
+/// 可写的栈页则各自独立。
+
+/// 理想输出：
+/// Test fork share OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let text_va = main as usize;
+    let stack_var: usize = 0;
+    let stack_va = &stack_var as *const usize as usize;
+    let text_ppn = translate(text_va);
+    let stack_ppn = translate(stack_va);
+    assert!(text_ppn > 0 && stack_ppn > 0);
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(translate(text_va), text_ppn);
+        assert_ne!(translate(stack_va), stack_ppn);
+        user_lib::exit(0);
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    // 子进程退出后共享页仍属于父进程
+    assert_eq!(translate(text_va), text_ppn);
+    println!("Test fork share OK!");
+    0
+}
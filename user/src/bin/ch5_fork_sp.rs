@@ -0,0 +1,49 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{checkpoint, exit, fork_on_stack, mmap, waitpid, Checkpoint, MMAP_SHARED};
+
+/// 程序行为：在共享映射上准备一段栈，fork_on_stack 创建的子进程在这段栈上运行：
+/// 它 checkpoint 得到的 sp 落在这段栈内，并把 sp 写到共享页上供父进程检查。
+/// sp 未按 16 字节对齐返回 -EINVAL，指向未映射或只读内存返回 -EFAULT。
+
+/// 理想输出：
+/// Test fork sp OK!
+
+const STACK: usize = 0x10000000;
+const STACK_SIZE: usize = 4 * 4096;
+const REPORT: usize = 0x10010000;
+const READONLY: usize = 0x10020000;
+
+fn child() -> ! {
+    let mut buf = Checkpoint::default();
+    assert_eq!(checkpoint(&mut buf), 0);
+    unsafe { (REPORT as *mut usize).write_volatile(buf.x[2]) };
+    exit(0);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(mmap(STACK, STACK_SIZE, 3 | MMAP_SHARED), 0);
+    assert_eq!(mmap(REPORT, 4096, 3 | MMAP_SHARED), 0);
+    assert_eq!(mmap(READONLY, 4096, 1), 0);
+    let top = STACK + STACK_SIZE;
+    assert_eq!(fork_on_stack(top - 8, child), -22);
+    assert_eq!(fork_on_stack(0x20000000, child), -14);
+    assert_eq!(fork_on_stack(READONLY + 4096, child), -14);
+    assert_eq!(fork_on_stack(8, child), -22);
+    assert_eq!(fork_on_stack(0, child), -14);
+
+    let pid = fork_on_stack(top, child);
+    assert!(pid > 0);
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    let sp = unsafe { (REPORT as *const usize).read_volatile() };
+    assert!(sp >= STACK && sp < top);
+    println!("Test fork sp OK!");
+    0
+}
@@ -0,0 +1,49 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{
+    exit, fork_on_stack, kill, mmap, sigaction, sleep_blocking, waitpid, yield_, MMAP_SHARED,
+    SIGUSR1,
+};
+
+/// 程序行为：fork_on_stack 创建的子进程运行在一段普通的共享映射栈上，
+/// 父进程向它发送 SIGUSR1，处理函数在这段栈上正常运行，子进程不会因 sp 不在用户栈内而被杀死。
+
+/// 理想输出：
+/// Test fork sp signal OK!
+
+const STACK: usize = 0x10000000;
+const STACK_SIZE: usize = 4 * 4096;
+
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+fn on_usr1(signum: usize) {
+    assert_eq!(signum, SIGUSR1);
+    HANDLED.fetch_add(1, Ordering::SeqCst);
+}
+
+fn child() -> ! {
+    while HANDLED.load(Ordering::SeqCst) == 0 {
+        yield_();
+    }
+    exit(HANDLED.load(Ordering::SeqCst) as i32);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(sigaction(SIGUSR1, Some(on_usr1)), 0);
+    assert_eq!(mmap(STACK, STACK_SIZE, 3 | MMAP_SHARED), 0);
+    let pid = fork_on_stack(STACK + STACK_SIZE, child);
+    assert!(pid > 0);
+    sleep_blocking(50);
+    assert_eq!(kill(pid as usize, SIGUSR1), 0);
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 1);
+    println!("Test fork sp signal OK!");
+    0
+}
@@ -0,0 +1,29 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::EINVAL;
+use user_lib::{
+    get_limit, LIMIT_BIG_STRIDE, LIMIT_MAX_PROC_DEPTH, LIMIT_MAX_SYSCALL_NUM, LIMIT_PAGE_SIZE,
+    LIMIT_USER_STACK_SIZE,
+};
+
+/// 程序行为：通过 get_limit 查询内核编译期常量，应与已知的构建配置一致；
+/// 未知的查询项返回 -EINVAL。
+
+/// 理想输出：
+/// Test get_limit OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(get_limit(LIMIT_PAGE_SIZE), 4096);
+    assert_eq!(get_limit(LIMIT_MAX_SYSCALL_NUM), 500);
+    assert_eq!(get_limit(LIMIT_USER_STACK_SIZE), 4096 * 4);
+    assert!(get_limit(LIMIT_BIG_STRIDE) > 0);
+    assert!(get_limit(LIMIT_MAX_PROC_DEPTH) > 0);
+    assert_eq!(get_limit(100), -EINVAL);
+    println!("Test get_limit OK!");
+    0
+}
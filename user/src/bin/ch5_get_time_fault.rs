@@ -0,0 +1,38 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::{EFAULT, EINVAL};
+use user_lib::{mmap, sys_get_time, TimeVal};
+
+/// 程序行为：sys_get_time 对非零 tz 与未对齐的指针返回 -EINVAL，对第二页未映射的跨页指针返回 -EFAULT，
+/// 且不写入任何字节。
+
+/// 理想输出：
+/// Test get_time fault OK!
+
+const PAGE_SIZE: usize = 4096;
+const FILL: u8 = 0x5a;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let time = TimeVal::new();
+    assert_eq!(sys_get_time(&time, 1), -EINVAL);
+    assert_eq!(sys_get_time(&time, 0), 0);
+
+    let start: usize = 0x10000000;
+    assert_eq!(mmap(start, PAGE_SIZE, 3), 0);
+    let page = unsafe { core::slice::from_raw_parts_mut(start as *mut u8, PAGE_SIZE) };
+    page.fill(FILL);
+    // 跨页：后 8 字节落在未映射的页中
+    let crossing = unsafe { &*((start + PAGE_SIZE - 8) as *const TimeVal) };
+    assert_eq!(sys_get_time(crossing, 0), -EFAULT);
+    // 未对齐
+    let unaligned = unsafe { &*((start + 1) as *const TimeVal) };
+    assert_eq!(sys_get_time(unaligned, 0), -EINVAL);
+    assert!(page.iter().all(|&byte| byte == FILL));
+    println!("Test get_time fault OK!");
+    0
+}
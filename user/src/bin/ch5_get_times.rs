@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    get_limit, get_times, mmap, munmap, sys_get_time, TimeVal, LIMIT_REALTIME_OFFSET_US,
+};
+
+/// 程序行为：get_times 一次读取时钟，同时给出 monotonic 与 realtime 时间，
+/// 二者之差恰好等于内核配置的非零 realtime 偏移，且在 monotonic 时间前进后保持不变；
+/// get_time 读到的是 realtime 时间。结果缓冲区跨越页边界时也能正确写入。
+
+/// 理想输出：
+/// Test get times OK!
+
+fn to_us(time: &TimeVal) -> usize {
+    time.sec * 1_000_000 + time.usec
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let offset = get_limit(LIMIT_REALTIME_OFFSET_US);
+    assert!(offset > 0);
+    let mut times = [TimeVal::default(), TimeVal::default()];
+    assert_eq!(get_times(&mut times), 0);
+    let first = to_us(&times[0]);
+    let mut last = first;
+    // 读到 monotonic 时间前进 10ms 以上为止，偏移始终不变
+    while last < first + 10_000 {
+        assert_eq!(get_times(&mut times), 0);
+        assert_eq!(to_us(&times[1]) - to_us(&times[0]), offset as usize);
+        assert!(to_us(&times[0]) >= last);
+        last = to_us(&times[0]);
+    }
+    // sys_get_time 给出的 realtime 时间夹在前后两次 get_times 之间
+    let realtime = TimeVal::new();
+    assert_eq!(sys_get_time(&realtime, 0), 0);
+    assert!(to_us(&realtime) >= to_us(&times[1]));
+    assert_eq!(get_times(&mut times), 0);
+    assert!(to_us(&realtime) <= to_us(&times[1]));
+    last = to_us(&times[0]);
+    // 两个 TimeVal 分别落在相邻的两页上
+    let start: usize = 0x10000000;
+    assert_eq!(mmap(start, 8192, 3), 0);
+    let split = unsafe { &mut *((start + 4096 - 16) as *mut [TimeVal; 2]) };
+    assert_eq!(get_times(split), 0);
+    assert_eq!(to_us(&split[1]) - to_us(&split[0]), offset as usize);
+    assert!(to_us(&split[0]) >= last);
+    assert_eq!(munmap(start, 8192), 0);
+    println!("Test get times OK!");
+    0
+}
@@ -0,0 +1,74 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    enable_syscall_latency, exit, fork, getpid, getppid, syscall_latency, waitpid, yield_,
+    SyscallLatency,
+};
+
+/// 程序行为：开启系统调用耗时统计，分别连续调用 getpid 和 getppid 若干次。
+/// getpid 只读取切换时缓存的 pid，不借用处理器与任务；用户态无法观察借用本身，
+/// 这里只检查其内核耗时不超过需要借用处理器、当前任务及其父任务的 getppid。
+/// 多个子进程在反复让出 CPU 前后读到的 pid 始终是自己的。
+
+/// 理想输出：
+/// Test getpid cached OK!
+
+const SYSCALL_GETPID: usize = 172;
+const SYSCALL_GETPPID: usize = 173;
+const ROUNDS: usize = 5000;
+const CHILDREN: usize = 4;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = getpid();
+    // the records are global and cumulative, compare what these loops add
+    let mut getpid_before = SyscallLatency::new();
+    let mut getppid_before = SyscallLatency::new();
+    assert_eq!(syscall_latency(SYSCALL_GETPID, &mut getpid_before), 0);
+    assert_eq!(syscall_latency(SYSCALL_GETPPID, &mut getppid_before), 0);
+    let was_enabled = enable_syscall_latency(true);
+    for _ in 0..ROUNDS {
+        assert_eq!(getpid(), pid);
+    }
+    for _ in 0..ROUNDS {
+        getppid();
+    }
+    enable_syscall_latency(was_enabled != 0);
+    let mut getpid_after = SyscallLatency::new();
+    let mut getppid_after = SyscallLatency::new();
+    assert_eq!(syscall_latency(SYSCALL_GETPID, &mut getpid_after), 0);
+    assert_eq!(syscall_latency(SYSCALL_GETPPID, &mut getppid_after), 0);
+    assert!(getpid_after.count - getpid_before.count >= ROUNDS);
+    // times are whole us, allow for a few calls rounded the other way
+    assert!(
+        getpid_after.total_us - getpid_before.total_us
+            <= getppid_after.total_us - getppid_before.total_us + ROUNDS / 100
+    );
+    // the cached pid follows every switch
+    let mut children = [0; CHILDREN];
+    for child in children.iter_mut() {
+        *child = fork();
+        if *child == 0 {
+            let me = getpid();
+            for _ in 0..100 {
+                yield_();
+                if getpid() != me {
+                    exit(1);
+                }
+            }
+            exit(me as i32);
+        }
+    }
+    for child in children {
+        let mut exit_code: i32 = -1;
+        assert_eq!(waitpid(child as usize, &mut exit_code), child);
+        assert_eq!(exit_code as isize, child);
+    }
+    assert_eq!(getpid(), pid);
+    println!("Test getpid cached OK!");
+    0
+}
@@ -0,0 +1,43 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{fork, getpid, getppid, sleep_blocking, waitpid};
+
+/// 程序行为：子进程的 getppid 返回父进程 pid；子进程先于孙进程退出后，
+/// 孤儿孙进程被 initproc 收养，getppid 返回 initproc 的 pid（0）。
+/// 孙进程由 initproc 回收，其结果体现在稍后的输出中。
+/// initproc 自身没有父进程，getppid 同样返回 0（需在 initproc 中手动验证）。
+
+/// 理想输出：
+/// Test getppid OK!
+/// orphan getppid OK!
+
+const INITPROC_PID: isize = 0;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let parent = getpid();
+    assert!(getppid() >= 0);
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(getppid(), parent);
+        let child = getpid();
+        if fork() == 0 {
+            assert_eq!(getppid(), child);
+            // 等待父进程退出后成为孤儿
+            sleep_blocking(100);
+            assert_eq!(getppid(), INITPROC_PID);
+            println!("orphan getppid OK!");
+            user_lib::exit(0);
+        }
+        user_lib::exit(0);
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    println!("Test getppid OK!");
+    0
+}
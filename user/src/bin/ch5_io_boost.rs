@@ -0,0 +1,32 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{fork, set_io_boost, waitpid};
+
+/// 程序行为：I/O 等待提升默认关闭，开关调用返回之前的设置，且 fork 出的子进程继承该设置。
+/// 提升对交互响应的影响需要在控制台输入时手动对比（例如在忙循环进程运行时于 shell 中打字）。
+
+/// 理想输出：
+/// Test io boost OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(set_io_boost(true), 0);
+    assert_eq!(set_io_boost(true), 1);
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(set_io_boost(false), 1);
+        user_lib::exit(0);
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    // 子进程的修改不影响父进程
+    assert_eq!(set_io_boost(false), 1);
+    assert_eq!(set_io_boost(false), 0);
+    println!("Test io boost OK!");
+    0
+}
@@ -0,0 +1,61 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, getpid, is_descendant, mmap, waitpid, yield_, MMAP_SHARED};
+
+/// 程序行为：父进程 fork 出子进程 A，A 再 fork 出孙进程 B。父进程查询 A、B 得到 1，
+/// 查询自己和 initproc 得到 0，查询不存在的 pid 得到 -1；A 查询父进程得到 0，查询 B 得到 1。
+
+/// 理想输出：
+/// Test is descendant OK!
+
+const NO_SUCH_PID: usize = 100000;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    assert_eq!(mmap(start, 4096, 3 | MMAP_SHARED), 0);
+    // [0]: B 的 pid，[1]: 父进程查询完毕的标志
+    let shared = start as *mut usize;
+    let me = getpid() as usize;
+    let a = fork();
+    if a == 0 {
+        let b = fork();
+        if b == 0 {
+            while unsafe { shared.add(1).read_volatile() } == 0 {
+                yield_();
+            }
+            exit(0);
+        }
+        unsafe {
+            shared.write_volatile(b as usize);
+        }
+        let ok = is_descendant(me) == 0 && is_descendant(b as usize) == 1;
+        let mut exit_code: i32 = -1;
+        assert_eq!(waitpid(b as usize, &mut exit_code), b);
+        exit(if ok { 0 } else { -1 });
+    }
+    let b = loop {
+        let b = unsafe { shared.read_volatile() };
+        if b != 0 {
+            break b;
+        }
+        yield_();
+    };
+    assert_eq!(is_descendant(a as usize), 1);
+    assert_eq!(is_descendant(b), 1);
+    assert_eq!(is_descendant(me), 0);
+    assert_eq!(is_descendant(0), 0);
+    assert_eq!(is_descendant(NO_SUCH_PID), -1);
+    unsafe {
+        shared.add(1).write_volatile(1);
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(a as usize, &mut exit_code), a);
+    assert_eq!(exit_code, 0);
+    println!("Test is descendant OK!");
+    0
+}
@@ -0,0 +1,45 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::console::getchar;
+use user_lib::{fork, get_time, kill, sleep_blocking, waitpid};
+
+/// 程序行为：子进程分别停在等待键盘输入的 read 和长时间的 sleep 系统调用中，
+/// 父进程用 SIGKILL 杀死它们。内核只标记子进程，等它离开系统调用、即将返回用户态时
+/// 再回收，子进程以 -9 退出，不会因重复借用而 panic，也不会一直等下去。
+/// 运行期间不要在终端输入字符。
+
+/// 理想输出：
+/// Test kill in syscall OK!
+
+const SIGKILL: usize = 9;
+const WAIT_MS: usize = 100;
+const LONG_SLEEP_MS: usize = 100_000;
+
+fn kill_while(in_syscall: fn()) {
+    let pid = fork();
+    if pid == 0 {
+        in_syscall();
+        panic!("the syscall returned to user mode after SIGKILL");
+    }
+    sleep_blocking(WAIT_MS);
+    let start = get_time();
+    assert_eq!(kill(pid as usize, SIGKILL), 0);
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -9);
+    assert!(get_time() - start < LONG_SLEEP_MS as isize);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    kill_while(|| {
+        getchar();
+    });
+    kill_while(|| sleep_blocking(LONG_SLEEP_MS));
+    println!("Test kill in syscall OK!");
+    0
+}
@@ -0,0 +1,48 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::console::STDOUT;
+use user_lib::{flush_stdout, fork, waitpid, write, yield_};
+
+/// 程序行为：两个进程交替地分段写出各自的行，每写一段就让出 CPU。
+/// 内核按行缓冲输出，因此每一行都应完整出现，不会与另一个进程的输出交错。
+
+/// 理想输出（两进程的行之间顺序不定）：
+/// AAAAAAAAAAAAAAAA
+/// BBBBBBBBBBBBBBBB
+/// ...
+/// no newline here
+/// Test line buffer OK!
+
+const LINES: usize = 5;
+
+fn write_lines(piece: &str) {
+    for _ in 0..LINES {
+        for _ in 0..4 {
+            assert_eq!(write(STDOUT, piece.as_bytes()), piece.len() as isize);
+            yield_();
+        }
+        write(STDOUT, b"\n");
+    }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        write_lines("BBBB");
+        user_lib::exit(0);
+    }
+    write_lines("AAAA");
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    // 不以换行结尾的内容需要显式刷新
+    write(STDOUT, b"no newline here");
+    assert_eq!(flush_stdout(), 0);
+    write(STDOUT, b"\n");
+    println!("Test line buffer OK!");
+    0
+}
@@ -0,0 +1,34 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::list_apps;
+
+/// 程序行为：list_apps 把内核中所有应用名以 NUL 分隔写入缓冲区并返回个数，
+/// 其中应包含 ch5b_initproc 与本程序；缓冲区放不下的名字不会被截断写入。
+
+/// 理想输出：
+/// Test list apps OK!
+
+const BUF_SIZE: usize = 8192;
+
+static mut BUF: [u8; BUF_SIZE] = [0; BUF_SIZE];
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let buf = unsafe { &mut BUF };
+    let count = list_apps(buf);
+    assert!(count > 0);
+    let names = || buf.split(|&b| b == 0).take(count as usize);
+    assert!(names().all(|name| !name.is_empty()));
+    assert!(names().any(|name| name == b"ch5b_initproc"));
+    assert!(names().any(|name| name == b"ch5_list_apps"));
+
+    let mut small = [0xffu8; 4];
+    assert_eq!(list_apps(&mut small), 0);
+    assert!(small.iter().all(|&b| b == 0xff));
+    println!("Test list apps OK!");
+    0
+}
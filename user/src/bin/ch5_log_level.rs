@@ -0,0 +1,33 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::EINVAL;
+use user_lib::{set_log_level, yield_, LOG_OFF, LOG_TRACE};
+
+/// 程序行为：打开 TRACE 级别日志后 yield，内核输出任务切换的跟踪信息；
+/// 关闭日志后再 yield，不再有内核日志输出；最后恢复原来的日志级别。
+/// 非法的级别返回 -EINVAL 且不改变当前级别。
+
+/// 理想输出：
+/// [TRACE] [kernel] switch to pid ...（若干行，仅出现在第一段）
+/// Test log level OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let original = set_log_level(LOG_TRACE);
+    assert!(original >= 0);
+    assert_eq!(set_log_level(6), -EINVAL);
+    for _ in 0..3 {
+        yield_();
+    }
+    assert_eq!(set_log_level(LOG_OFF), LOG_TRACE as isize);
+    for _ in 0..3 {
+        yield_();
+    }
+    assert_eq!(set_log_level(original as usize), LOG_OFF as isize);
+    println!("Test log level OK!");
+    0
+}
@@ -0,0 +1,57 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, membarrier, mmap, waitpid, yield_, MMAP_SHARED};
+
+/// 程序行为：父进程在与子进程共享的页中写入数据后调用 membarrier 再写入标志，
+/// 之后被调度的子进程看到标志时应看到全部数据；单核上尽力而为。
+
+/// 理想输出：
+/// Test membarrier OK!
+
+const WORDS: usize = 256;
+const ROUNDS: usize = 20;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    assert_eq!(mmap(start, 4096, 3 | MMAP_SHARED), 0);
+    let flag = start as *mut usize;
+    let data = unsafe { core::slice::from_raw_parts_mut((start + 8) as *mut usize, WORDS) };
+    let pid = fork();
+    if pid == 0 {
+        for round in 1..=ROUNDS {
+            while unsafe { flag.read_volatile() } != round {
+                yield_();
+            }
+            for (i, word) in data.iter().enumerate() {
+                if unsafe { (word as *const usize).read_volatile() } != round * 1000 + i {
+                    exit(1);
+                }
+            }
+            // 通知父进程本轮已检查完毕
+            unsafe { flag.write_volatile(0) };
+            assert_eq!(membarrier(), 0);
+        }
+        exit(0);
+    }
+    for round in 1..=ROUNDS {
+        for (i, word) in data.iter_mut().enumerate() {
+            *word = round * 1000 + i;
+        }
+        // 数据先于标志对子进程可见
+        assert_eq!(membarrier(), 0);
+        unsafe { flag.write_volatile(round) };
+        while unsafe { flag.read_volatile() } != 0 {
+            yield_();
+        }
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    println!("Test membarrier OK!");
+    0
+}
@@ -0,0 +1,53 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::{EINVAL, ENOMEM};
+use user_lib::{mincore, mmap, swapout};
+
+/// 程序行为：mmap 四页并全部写入后换出，四页都不再驻留；隔页读取使第 0、2 页被换入，
+/// 此时恰好这两页驻留。包含未映射页的范围返回 -ENOMEM，回绕的范围返回 -EINVAL。
+
+/// 理想输出：
+/// Test mincore OK!
+
+const PAGE_SIZE: usize = 4096;
+const PAGES: usize = 4;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    assert_eq!(mmap(start, PAGES * PAGE_SIZE, 3), 0);
+    for i in 0..PAGES {
+        unsafe {
+            *((start + i * PAGE_SIZE) as *mut u8) = i as u8;
+        }
+    }
+    let mut vec = [0xffu8; PAGES];
+    assert_eq!(mincore(start, PAGES * PAGE_SIZE, &mut vec), 0);
+    assert_eq!(vec, [1; PAGES]);
+    assert_eq!(swapout(start, PAGES * PAGE_SIZE), PAGES as isize);
+    assert_eq!(mincore(start, PAGES * PAGE_SIZE, &mut vec), 0);
+    assert_eq!(vec, [0; PAGES]);
+    // 隔页访问，只有访问过的页被换入
+    for i in (0..PAGES).step_by(2) {
+        let byte = unsafe { ((start + i * PAGE_SIZE) as *const u8).read_volatile() };
+        assert_eq!(byte, i as u8);
+    }
+    assert_eq!(mincore(start, PAGES * PAGE_SIZE, &mut vec), 0);
+    assert_eq!(vec, [1, 0, 1, 0]);
+    // 不足一页的长度按页向上取整
+    let mut one = [0xffu8; 1];
+    assert_eq!(mincore(start + PAGE_SIZE, 1, &mut one), 0);
+    assert_eq!(one, [0]);
+    // 范围延伸到未映射的页
+    let mut vec = [0u8; PAGES + 1];
+    assert_eq!(mincore(start, (PAGES + 1) * PAGE_SIZE, &mut vec), -ENOMEM);
+    assert_eq!(mincore(start - PAGE_SIZE, PAGE_SIZE, &mut vec), -ENOMEM);
+    // start + len 回绕
+    assert_eq!(mincore(start, usize::MAX, &mut vec), -EINVAL);
+    println!("Test mincore OK!");
+    0
+}
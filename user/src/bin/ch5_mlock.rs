@@ -0,0 +1,53 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::ENOMEM;
+use user_lib::{mincore, mlock, mmap, munlock, munmap, swapout};
+
+/// 程序行为：锁定 4 页 mmap 区域中间的两页后整体换出，只有未锁定的两页被换出，
+/// 锁定的页仍然驻留；解锁后它们也能换出。锁定已换出的页会先把它们换入，
+/// 数据保持不变。锁定未映射的范围返回 -ENOMEM。
+
+/// 理想输出：
+/// Test mlock OK!
+
+const PAGE: usize = 4096;
+const PAGES: usize = 4;
+
+fn pattern(addr: usize) -> u8 {
+    (addr ^ (addr >> 12)) as u8
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    let mut vec = [0u8; PAGES];
+    assert_eq!(mmap(start, PAGE * PAGES, 3), 0);
+    for addr in start..start + PAGE * PAGES {
+        unsafe { (addr as *mut u8).write_volatile(pattern(addr)) };
+    }
+    assert_eq!(mlock(start + PAGE, PAGE * 2), 0);
+    assert_eq!(swapout(start, PAGE * PAGES), 2);
+    assert_eq!(mincore(start, PAGE * PAGES, &mut vec), 0);
+    assert_eq!(vec, [0, 1, 1, 0]);
+    assert_eq!(munlock(start + PAGE, PAGE * 2), 0);
+    assert_eq!(swapout(start, PAGE * PAGES), 2);
+    assert_eq!(mincore(start, PAGE * PAGES, &mut vec), 0);
+    assert_eq!(vec, [0; PAGES]);
+    // locking brings the pages back without touching them
+    assert_eq!(mlock(start, PAGE * PAGES), 0);
+    assert_eq!(mincore(start, PAGE * PAGES, &mut vec), 0);
+    assert_eq!(vec, [1; PAGES]);
+    assert_eq!(swapout(start, PAGE * PAGES), 0);
+    assert!((start..start + PAGE * PAGES)
+        .all(|addr| unsafe { (addr as *const u8).read_volatile() } == pattern(addr)));
+    // the range must be mapped all the way
+    assert_eq!(mlock(start + PAGE * PAGES, PAGE), -ENOMEM);
+    assert_eq!(mlock(start, PAGE * (PAGES + 1)), -ENOMEM);
+    assert_eq!(munmap(start, PAGE * PAGES), 0);
+    println!("Test mlock OK!");
+    0
+}
@@ -0,0 +1,41 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::{EEXIST, EINVAL, ENOENT, ENOMEM};
+use user_lib::{mmap, mmap_app};
+
+/// 程序行为：将另一个应用的 ELF 镜像只读映射到自身地址空间，读取其 ELF 魔数。
+/// 不存在的应用名返回 -ENOENT，未对齐的起始地址返回 -EINVAL，映射到已占用的区域返回 -EEXIST，
+/// 包括完全落在已有区域之内的情形（如用户栈）；超出用户地址空间的起始地址返回 -ENOMEM。
+
+/// 理想输出：
+/// Test mmap_app OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    assert_eq!(mmap_app(start, "no_such_app\0"), -ENOENT);
+    let len = mmap_app(start, "ch5b_initproc\0");
+    assert!(len > 4);
+    let image = unsafe { core::slice::from_raw_parts(start as *const u8, len as usize) };
+    assert_eq!(&image[..4], b"\x7fELF");
+    assert_eq!(mmap_app(start, "ch5b_initproc\0"), -EEXIST);
+    // 未对齐的起始地址
+    assert_eq!(mmap_app(start + 0x100000 + 1, "ch5b_initproc\0"), -EINVAL);
+    // 完全落在一块已有 mmap 区域之内
+    let big: usize = 0x20000000;
+    let big_len: usize = 1 << 20;
+    assert_eq!(mmap(big, big_len, 3), 0);
+    assert_eq!(mmap_app(big + 0x1000, "ch5b_initproc\0"), -EEXIST);
+    // 用户栈所在的页
+    let local = 0u8;
+    let stack_page = &local as *const u8 as usize & !0xfff;
+    assert_eq!(mmap_app(stack_page, "ch5b_initproc\0"), -EEXIST);
+    // 超出用户地址空间
+    assert_eq!(mmap_app(usize::MAX & !0xfff, "ch5b_initproc\0"), -ENOMEM);
+    println!("Test mmap_app OK!");
+    0
+}
@@ -0,0 +1,40 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{mmap, munmap, MMAP_RETURN_BASE, MMAP_SHARED};
+
+/// 程序行为：带 MMAP_RETURN_BASE 的 mmap 成功时返回映射的起始地址，即传入的 start，
+/// 可以直接当作指针使用；失败时返回负的错误码，不会和地址混淆。
+/// 不带该标志的 mmap 仍按实验要求返回 0。
+
+/// 理想输出：
+/// Test mmap base OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    let len: usize = 4096 * 2;
+    let base = mmap(start, len, 3 | MMAP_RETURN_BASE);
+    assert_eq!(base, start as isize);
+    let p = base as usize as *mut u8;
+    unsafe {
+        p.write_volatile(42);
+        p.add(len - 1).write_volatile(43);
+        assert_eq!(p.read_volatile(), 42);
+    }
+    // 共享区域同样返回起始地址
+    let shared = start + len;
+    let ret = mmap(shared, 4096, 3 | MMAP_SHARED | MMAP_RETURN_BASE);
+    assert_eq!(ret, shared as isize);
+    // 已被占用的区间返回错误码而不是地址
+    assert!(mmap(start, 4096, 3 | MMAP_RETURN_BASE) < 0);
+    // 不带标志时返回 0
+    let plain = shared + 4096;
+    assert_eq!(mmap(plain, 4096, 3), 0);
+    assert_eq!(munmap(start, len + 4096 * 2), 0);
+    println!("Test mmap base OK!");
+    0
+}
@@ -0,0 +1,71 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork_on_stack, mincore, mmap, waitpid, MMAP_GROWSDOWN, MMAP_SHARED};
+
+/// 程序行为：MMAP_GROWSDOWN 映射的区域起初只有最高一页有物理页。子进程在这段栈上递归，
+/// 栈向下扩展而不是缺页退出，扩展后的页在 mincore 中可见。递归到最低的保护页时
+/// 子进程以 -2 退出。长度不足两页或同时带 MMAP_SHARED 返回 -EINVAL。
+
+/// 理想输出：
+/// Test mmap growsdown OK!
+
+const PAGE: usize = 4096;
+const PAGES: usize = 16;
+const STACK: usize = 0x10000000;
+const TOP: usize = STACK + PAGES * PAGE;
+
+/// 每层约 1K 栈
+fn recurse(depth: usize) -> usize {
+    let mut frame = [0u8; 1024];
+    // 写满整层，编译器不能把它优化掉
+    for byte in frame.iter_mut() {
+        unsafe { core::ptr::write_volatile(byte, depth as u8) };
+    }
+    if depth == 0 {
+        return 0;
+    }
+    recurse(depth - 1) + unsafe { core::ptr::read_volatile(&frame[depth % 1024]) } as usize
+}
+
+fn grow() -> ! {
+    // 约 10 页，不碰保护页
+    assert_eq!(recurse(40), 820);
+    let mut vec = [0u8; PAGES];
+    assert_eq!(mincore(STACK, PAGES * PAGE, &mut vec), 0);
+    let resident = vec.iter().filter(|&&page| page == 1).count();
+    let ok = vec[0] == 0 && resident > 5 && vec[PAGES - 1] == 1;
+    exit(if ok { 0 } else { -1 });
+}
+
+fn overflow() -> ! {
+    recurse(100);
+    exit(0);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(mmap(STACK, PAGE, 3 | MMAP_GROWSDOWN), -22);
+    assert_eq!(mmap(STACK, 2 * PAGE, 3 | MMAP_GROWSDOWN | MMAP_SHARED), -22);
+    assert_eq!(mmap(STACK, PAGES * PAGE, 3 | MMAP_GROWSDOWN), 0);
+    let mut vec = [0u8; PAGES];
+    assert_eq!(mincore(STACK, PAGES * PAGE, &mut vec), 0);
+    assert_eq!(vec[..PAGES - 1], [0; PAGES - 1]);
+    assert_eq!(vec[PAGES - 1], 1);
+
+    let mut exit_code: i32 = 1;
+    let pid = fork_on_stack(TOP, grow);
+    assert!(pid > 0);
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+
+    let pid = fork_on_stack(TOP, overflow);
+    assert!(pid > 0);
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -2);
+    println!("Test mmap growsdown OK!");
+    0
+}
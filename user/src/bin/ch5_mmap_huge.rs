@@ -0,0 +1,52 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::{EFAULT, EINVAL};
+use user_lib::{mmap, munmap, page_size, translate, MMAP_HUGE};
+
+/// 程序行为：用 MMAP_HUGE 映射一个 2MB 大页，在整个区间内写入并读回；
+/// page_size 查询确认该区间由单个 2MB 页表项映射，且物理页连续。
+/// 起始地址或长度未按 2MB 对齐时返回 -EINVAL；munmap 整个大页后该地址不再有映射。
+
+/// 理想输出：
+/// Test mmap huge page OK!
+
+const HUGE_PAGE_SIZE: usize = 0x20_0000;
+const PAGE_SIZE: usize = 4096;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x4000_0000;
+    let prot = 3 | MMAP_HUGE;
+    assert_eq!(mmap(start + PAGE_SIZE, HUGE_PAGE_SIZE, prot), -EINVAL);
+    assert_eq!(mmap(start, PAGE_SIZE, prot), -EINVAL);
+    assert_eq!(mmap(start, HUGE_PAGE_SIZE, prot), 0);
+
+    for offset in (0..HUGE_PAGE_SIZE).step_by(PAGE_SIZE) {
+        let addr = (start + offset) as *mut usize;
+        unsafe { addr.write_volatile(offset) };
+    }
+    for offset in (0..HUGE_PAGE_SIZE).step_by(PAGE_SIZE) {
+        let addr = (start + offset) as *const usize;
+        assert_eq!(unsafe { addr.read_volatile() }, offset);
+    }
+    assert_eq!(page_size(start), HUGE_PAGE_SIZE as isize);
+    assert_eq!(page_size(start + HUGE_PAGE_SIZE - 1), HUGE_PAGE_SIZE as isize);
+    let first = translate(start);
+    let last = translate(start + HUGE_PAGE_SIZE - PAGE_SIZE);
+    assert_eq!(first % (HUGE_PAGE_SIZE / PAGE_SIZE) as isize, 0);
+    assert_eq!(last - first, (HUGE_PAGE_SIZE / PAGE_SIZE - 1) as isize);
+
+    // 普通映射仍使用 4K 页
+    let small = start + HUGE_PAGE_SIZE;
+    assert_eq!(mmap(small, PAGE_SIZE, 3), 0);
+    assert_eq!(page_size(small), PAGE_SIZE as isize);
+
+    assert_eq!(munmap(start, HUGE_PAGE_SIZE), 0);
+    assert_eq!(page_size(start), -EFAULT);
+    println!("Test mmap huge page OK!");
+    0
+}
@@ -0,0 +1,32 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::ENOMEM;
+use user_lib::{mincore, mmap, munmap};
+
+/// 程序行为：len 巨大、使 start + len 越过用户地址空间上界（Sv39 低半部分的顶端 1 << 38）
+/// 甚至溢出的 mmap 返回 -ENOMEM，且不留下任何映射：随后在同一位置映射一页仍然成功。
+
+/// 理想输出：
+/// Test mmap huge len OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    assert_eq!(mmap(start, usize::MAX - 4095, 3), -ENOMEM);
+    assert_eq!(mmap(start, usize::MAX - start + 1, 3), -ENOMEM);
+    assert_eq!(mmap(start, 1 << 38, 3), -ENOMEM);
+    // 恰好到达上界是允许的，但超出一页即失败
+    assert_eq!(mmap((1 << 38) - 4096, 8192, 3), -ENOMEM);
+    assert_eq!(mmap((1 << 38) - 4096, 4096, 3), 0);
+    assert_eq!(munmap((1 << 38) - 4096, 4096), 0);
+    let mut vec = [0u8; 1];
+    assert!(mincore(start, 4096, &mut vec) < 0);
+    assert_eq!(mmap(start, 4096, 3), 0);
+    assert_eq!(munmap(start, 4096), 0);
+    println!("Test mmap huge len OK!");
+    0
+}
@@ -0,0 +1,52 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, mmap, munmap, reserve, waitpid, MMAP_SHARED};
+
+/// 程序行为：一次 munmap 同时覆盖未提交的预留页、与子进程共享的页和私有页。
+/// 子进程解除映射后父进程的共享页内容不受影响；反复映射再解除映射共计远超物理内存的
+/// 页数仍然成功，说明没有泄漏也没有重复释放物理页。
+
+/// 理想输出：
+/// Test munmap mixed OK!
+
+const PAGE: usize = 4096;
+const PRIVATE_PAGES: usize = 512;
+const ROUNDS: usize = 80;
+
+fn map_mixed(start: usize) {
+    assert_eq!(reserve(start, PAGE), 0);
+    assert_eq!(mmap(start + PAGE, PAGE, 3 | MMAP_SHARED), 0);
+    assert_eq!(mmap(start + PAGE * 2, PAGE * PRIVATE_PAGES, 3), 0);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    let len = PAGE * (PRIVATE_PAGES + 2);
+    map_mixed(start);
+    let shared = (start + PAGE) as *mut usize;
+    unsafe {
+        shared.write_volatile(0x5a5a);
+    }
+    let pid = fork();
+    if pid == 0 {
+        exit(munmap(start, len) as i32);
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    unsafe {
+        assert_eq!(shared.read_volatile(), 0x5a5a);
+    }
+    assert_eq!(munmap(start, len), 0);
+    for _ in 0..ROUNDS {
+        map_mixed(start);
+        assert_eq!(munmap(start, len), 0);
+    }
+    println!("Test munmap mixed OK!");
+    0
+}
@@ -0,0 +1,35 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{mmap, munmap};
+
+/// 程序行为：对从未映射的区间 munmap 返回 -1；区间只有一部分已映射时同样返回 -1，
+/// 且已映射的部分保持不变；之后对真正映射过的区间 munmap 仍然成功。
+
+/// 理想输出：
+/// Test munmap unmapped OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    let len: usize = 4096;
+    assert_eq!(munmap(start, len * 16), -1);
+    assert_eq!(mmap(start, len * 2, 3), 0);
+    // 区间的后半部分从未映射，整体失败，前两页不受影响
+    assert_eq!(munmap(start, len * 4), -1);
+    // 区间的前半部分从未映射
+    assert_eq!(munmap(start - len * 2, len * 4), -1);
+    for i in start..(start + len * 2) {
+        let addr: *mut u8 = i as *mut u8;
+        unsafe {
+            *addr = i as u8;
+        }
+    }
+    assert_eq!(munmap(start, len * 2), 0);
+    assert_eq!(munmap(start, len * 2), -1);
+    println!("Test munmap unmapped OK!");
+    0
+}
@@ -0,0 +1,27 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{dump_regions, mmap, name_region};
+
+/// 程序行为：为一块 mmap 区域命名，并打印内存布局。
+
+/// 理想输出：
+/// [kernel] memory map of pid ...
+/// [kernel] [0x10000000, 0x10002000) R | W | U scratch-buffer
+/// Test name region OK!
+
+#[no_mangle]
+fn main() -> i32 {
+    let start: usize = 0x10000000;
+    let len: usize = 8192;
+    assert_eq!(0, mmap(start, len, 3));
+    assert_eq!(name_region(start + 4096, "scratch-buffer\0"), 0);
+    // 未被任何区域覆盖的地址
+    assert_eq!(name_region(start + len, "nothing\0"), -1);
+    assert_eq!(dump_regions(), 0);
+    println!("Test name region OK!");
+    0
+}
@@ -0,0 +1,29 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{getpid, kernel_assert};
+
+/// 程序行为：打印自身 pid 后触发一次内核断言。内核以 panic-test 特性构建时，
+/// panic 处理函数应打印当前任务的 pid、名字、trap 上下文与内核栈顶，随后关机；
+/// 其余情况下系统调用返回 -1，测试直接通过。
+
+/// 理想输出（panic-test）：
+/// kernel_assert from pid N
+/// [kernel] Panicked at src/syscall/process.rs:... deliberate kernel assert from sys_kernel_assert
+/// [kernel] panic in pid N
+/// [kernel] name: ch5_panic_dump
+/// 理想输出（其余情况）：
+/// kernel_assert from pid N
+/// Test panic dump OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    println!("kernel_assert from pid {}", getpid());
+    // 只有未开启 panic-test 的内核才会返回
+    assert_eq!(kernel_assert(), -1);
+    println!("Test panic dump OK!");
+    0
+}
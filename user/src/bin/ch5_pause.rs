@@ -0,0 +1,35 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{fork, kill, pause, sleep_blocking, waitpid_cpu_time};
+
+/// 程序行为：子进程调用 pause 阻塞，父进程睡眠 200ms 后用 SIGKILL 杀死它。
+/// 子进程应以 -9 退出，且暂停期间不在就绪队列中，几乎不占用 CPU 时间。
+
+/// 理想输出：
+/// Test pause OK!
+
+const SIGKILL: usize = 9;
+const WAIT_MS: usize = 200;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        pause();
+        panic!("pause returned after SIGKILL");
+    }
+    sleep_blocking(WAIT_MS);
+    assert_eq!(kill(pid as usize, SIGKILL), 0);
+    let mut exit_code: i32 = 0;
+    let mut cpu_time: usize = 0;
+    assert_eq!(waitpid_cpu_time(pid as usize, &mut exit_code, &mut cpu_time), pid);
+    assert_eq!(exit_code, -9);
+    // 远小于父进程等待的时间
+    assert!(cpu_time < WAIT_MS * 1000 / 10);
+    println!("Test pause OK!");
+    0
+}
@@ -0,0 +1,52 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, getpid, wait, yield_};
+
+/// 程序行为：反复 fork 立即退出的子进程，同时最多保留 8 个未回收的子进程。每次 fork
+/// 得到的 pid 都不能与仍未回收的子进程或父进程自身重复，被回收的 pid 会很快被复用。
+
+/// 理想输出：
+/// Test pid unique OK!
+
+const ROUNDS: usize = 500;
+const OUTSTANDING: usize = 8;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let me = getpid();
+    let mut live = [-1isize; OUTSTANDING];
+    for round in 0..ROUNDS {
+        let slot = match live.iter().position(|&pid| pid < 0) {
+            Some(slot) => slot,
+            None => {
+                let mut exit_code: i32 = 0;
+                let pid = wait(&mut exit_code);
+                assert!(pid > 0);
+                assert_eq!(exit_code, pid as i32);
+                live.iter().position(|&live_pid| live_pid == pid).unwrap()
+            }
+        };
+        live[slot] = -1;
+        let pid = fork();
+        if pid == 0 {
+            if round % 3 == 0 {
+                yield_();
+            }
+            exit(getpid() as i32);
+        }
+        assert!(pid > 0);
+        assert_ne!(pid, me);
+        assert!(!live.contains(&pid), "pid {} handed out twice", pid);
+        live[slot] = pid;
+    }
+    for _ in live.iter().filter(|&&pid| pid >= 0) {
+        let mut exit_code: i32 = 0;
+        assert!(wait(&mut exit_code) > 0);
+    }
+    println!("Test pid unique OK!");
+    0
+}
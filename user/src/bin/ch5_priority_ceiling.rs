@@ -0,0 +1,50 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::EPERM;
+use user_lib::{drop_priority_ceiling, exit, fork, set_priority, setpriority, waitpid};
+
+/// 程序行为：父进程把优先级设为 8 后锁定优先级上限，之后不能再把自己调到 8 以上，
+/// 但可以往下调，往下调不会降低上限。fork 出的子进程继承上限：
+/// 它自己调高优先级失败，父进程通过 setpriority 给它调高也失败。
+
+/// 理想输出：
+/// Test priority ceiling OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(set_priority(8), 8);
+    assert_eq!(drop_priority_ceiling(), 8);
+    assert_eq!(set_priority(16), -1);
+    assert_eq!(set_priority(9), -1);
+    assert_eq!(set_priority(8), 8);
+    assert_eq!(set_priority(4), 4);
+    // lowering the priority does not lower the ceiling until asked to
+    assert_eq!(set_priority(8), 8);
+    let pid = fork();
+    if pid == 0 {
+        if set_priority(16) != -1 || set_priority(100) != -1 {
+            exit(1);
+        }
+        if set_priority(8) != 8 || set_priority(3) != 3 {
+            exit(2);
+        }
+        // a lower ceiling in the child does not loosen with a higher request
+        if drop_priority_ceiling() != 3 || set_priority(8) != -1 {
+            exit(3);
+        }
+        exit(0);
+    }
+    assert_eq!(setpriority(pid as usize, 16), -EPERM);
+    assert_eq!(setpriority(pid as usize, 6), 6);
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    // the child's lower ceiling stays with the child
+    assert_eq!(set_priority(8), 8);
+    println!("Test priority ceiling OK!");
+    0
+}
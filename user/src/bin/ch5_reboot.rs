@@ -0,0 +1,28 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{fork, reboot, waitpid};
+
+/// 程序行为：只有 initproc 可以重启系统，普通进程及其子进程调用 reboot 均返回 -1。
+/// 真正的重启路径需要手动验证。
+
+/// 理想输出：
+/// Test reboot OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(reboot(), -1);
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(reboot(), -1);
+        user_lib::exit(0);
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    println!("Test reboot OK!");
+    0
+}
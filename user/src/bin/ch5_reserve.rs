@@ -0,0 +1,69 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::{EEXIST, EINVAL, ENOMEM};
+use user_lib::{commit, fork, mmap, munmap, reserve, waitpid};
+
+/// 程序行为：预留 16 页地址空间后，与之重叠的 mmap 失败；提交中间 4 页后这 4 页可读写，
+/// 未提交的页访问时触发缺页，子进程以 -2 退出；最后整段 munmap 释放预留。
+/// 预留落在已有 mmap 区域之内返回 -EEXIST，超出用户地址空间返回 -ENOMEM。
+
+/// 理想输出：
+/// [kernel] PageFault in application, bad addr = 0x10000000, ...
+/// [kernel] PageFault in application, bad addr = 0x1000f000, ...
+/// Test reserve OK!
+
+const PAGE: usize = 4096;
+
+fn touch_in_child(addr: usize) {
+    let pid = fork();
+    if pid == 0 {
+        unsafe {
+            (addr as *mut u8).write_volatile(1);
+        }
+        panic!("write to an uncommitted page did not fault");
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -2);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    assert_eq!(reserve(start, PAGE * 16), 0);
+    assert!(mmap(start + PAGE * 3, PAGE, 3) < 0);
+    assert!(reserve(start + PAGE * 15, PAGE * 2) < 0);
+    // 提交的区间必须落在同一个预留内
+    assert_eq!(commit(start + PAGE * 14, PAGE * 4, 3), -EINVAL);
+    assert_eq!(commit(start + PAGE * 6, PAGE * 4, 3), 0);
+    for i in (start + PAGE * 6)..(start + PAGE * 10) {
+        unsafe {
+            (i as *mut u8).write_volatile(i as u8);
+        }
+    }
+    for i in (start + PAGE * 6)..(start + PAGE * 10) {
+        unsafe {
+            assert_eq!((i as *const u8).read_volatile(), i as u8);
+        }
+    }
+    touch_in_child(start);
+    touch_in_child(start + PAGE * 15);
+    // 已提交的部分不能再次提交，剩余的预留仍可以
+    assert_eq!(commit(start + PAGE * 6, PAGE, 3), -EINVAL);
+    assert!(mmap(start + PAGE * 12, PAGE, 3) < 0);
+    assert_eq!(munmap(start, PAGE * 16), 0);
+    assert_eq!(mmap(start, PAGE, 3), 0);
+    assert_eq!(munmap(start, PAGE), 0);
+    // 完全落在已有 mmap 区域之内的预留
+    assert_eq!(mmap(start, PAGE * 8, 3), 0);
+    assert_eq!(reserve(start + PAGE * 2, PAGE * 2), -EEXIST);
+    assert_eq!(commit(start + PAGE * 2, PAGE * 2, 3), -EINVAL);
+    assert_eq!(munmap(start, PAGE * 8), 0);
+    assert_eq!(reserve(usize::MAX & !(PAGE - 1), PAGE), -ENOMEM);
+    println!("Test reserve OK!");
+    0
+}
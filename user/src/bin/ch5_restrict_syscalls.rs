@@ -0,0 +1,66 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, getpid, restrict_syscalls, sys_restrict_syscalls, waitpid, yield_, SYSCALL_EXIT,
+    SYSCALL_FORK, SYSCALL_GETPID, SYSCALL_RESTRICT_SYSCALLS, SYSCALL_WAITPID, SYSCALL_YIELD,
+};
+
+/// 程序行为：子进程只允许 yield 和 exit，yield 照常返回，调用 getpid 时被内核杀死，
+/// 退出码为 -31。过滤器随 fork 继承；再次设置只能在原有基础上收紧，不能放宽。
+/// 过长的位图返回 -EINVAL。
+
+/// 理想输出：
+/// [kernel] syscall 172 denied by the syscall filter, killed.（出现三次）
+/// Test restrict syscalls OK!
+
+fn wait_for(pid: isize) -> i32 {
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    exit_code
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(sys_restrict_syscalls(&[0xff; 64]), -22);
+
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(restrict_syscalls(&[SYSCALL_YIELD, SYSCALL_EXIT]), 0);
+        yield_();
+        getpid();
+        exit(0);
+    }
+    assert_eq!(wait_for(pid), -31);
+
+    // 继承：孙进程同样受限
+    let pid = fork();
+    if pid == 0 {
+        let allowed = [SYSCALL_YIELD, SYSCALL_EXIT, SYSCALL_FORK, SYSCALL_WAITPID];
+        assert_eq!(restrict_syscalls(&allowed), 0);
+        let pid = fork();
+        if pid == 0 {
+            getpid();
+            exit(0);
+        }
+        exit(if wait_for(pid) == -31 { 0 } else { -1 });
+    }
+    assert_eq!(wait_for(pid), 0);
+
+    // 只能收紧
+    let pid = fork();
+    if pid == 0 {
+        let allowed = [SYSCALL_YIELD, SYSCALL_EXIT, SYSCALL_RESTRICT_SYSCALLS];
+        assert_eq!(restrict_syscalls(&allowed), 0);
+        let allowed = [SYSCALL_EXIT, SYSCALL_RESTRICT_SYSCALLS, SYSCALL_GETPID];
+        assert_eq!(restrict_syscalls(&allowed), 0);
+        getpid();
+        exit(0);
+    }
+    assert_eq!(wait_for(pid), -31);
+    println!("Test restrict syscalls OK!");
+    0
+}
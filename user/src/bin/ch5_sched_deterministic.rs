@@ -0,0 +1,92 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, kill, mmap, pause, sched_set_deterministic, sched_setscheduler, set_io_boost,
+    set_priority, sigaction, task_info_of, waitpid, yield_, TaskInfo, TaskStatus, MMAP_SHARED,
+    SCHED_RR, SIGUSR1,
+};
+
+/// 程序行为：打开确定性调度后，同一组子进程（不同优先级，申请了 SCHED_RR 和 I/O 加速）
+/// 先全部阻塞在 pause 上，再按顺序唤醒，各自在共享页上记录自己被调度的顺序。
+/// 同样的负载跑两遍，两次的调度记录完全相同。确定性调度打开后不能关闭。
+
+/// 理想输出：
+/// Test sched deterministic OK!
+
+const CHILDREN: usize = 3;
+const ROUNDS: usize = 20;
+const LOG_LEN: usize = CHILDREN * ROUNDS;
+
+fn on_usr1(_signum: usize) {}
+
+fn child(log: *mut usize, me: usize) -> ! {
+    sched_setscheduler(SCHED_RR);
+    set_io_boost(true);
+    set_priority(me as isize + 2);
+    sigaction(SIGUSR1, Some(on_usr1));
+    pause();
+    for _ in 0..ROUNDS {
+        unsafe {
+            let idx = log.read_volatile();
+            log.add(1 + idx).write_volatile(me);
+            log.write_volatile(idx + 1);
+        }
+        yield_();
+    }
+    exit(0);
+}
+
+/// log[0] 是已写入的条数，之后是调度记录
+fn run(log: *mut usize) {
+    let mut pids = [0isize; CHILDREN];
+    for (me, pid) in pids.iter_mut().enumerate() {
+        *pid = fork();
+        if *pid == 0 {
+            child(log, me);
+        }
+    }
+    // 全部阻塞后再按顺序唤醒
+    for &pid in pids.iter() {
+        let mut info = TaskInfo::new();
+        loop {
+            assert_eq!(task_info_of(pid as usize, &mut info), 0);
+            if info.status == TaskStatus::Blocked {
+                break;
+            }
+            yield_();
+        }
+    }
+    for &pid in pids.iter() {
+        assert_eq!(kill(pid as usize, SIGUSR1), 0);
+    }
+    for &pid in pids.iter() {
+        let mut exit_code: i32 = -1;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+        assert_eq!(exit_code, 0);
+    }
+    assert_eq!(unsafe { log.read_volatile() }, LOG_LEN);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    assert_eq!(mmap(start, 2 * 4096, 3 | MMAP_SHARED), 0);
+    let first = start as *mut usize;
+    let second = (start + 4096) as *mut usize;
+    assert!(sched_set_deterministic(true) >= 0);
+    assert_eq!(sched_set_deterministic(true), 1);
+    assert_eq!(sched_set_deterministic(false), -1);
+    run(first);
+    run(second);
+    for i in 1..=LOG_LEN {
+        assert_eq!(unsafe { first.add(i).read_volatile() }, unsafe {
+            second.add(i).read_volatile()
+        });
+    }
+    println!("Test sched deterministic OK!");
+    0
+}
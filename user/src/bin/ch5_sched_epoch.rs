@@ -0,0 +1,55 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, mmap, sched_epoch, waitpid, yield_, MMAP_SHARED};
+
+/// 程序行为：父进程连续 fork 两个同优先级的子进程，它们从同样的 pass 开始轮流运行。
+/// 每个子进程每次被调度后把自己的调度计数写到共享页里，并和对方最近写下的计数比较，
+/// 两者始终相差不超过 1。
+
+/// 理想输出：
+/// Test sched epoch OK!
+
+const ROUNDS: isize = 100;
+
+fn child(slots: *mut isize, me: usize) -> ! {
+    let other = 1 - me;
+    while sched_epoch() < ROUNDS {
+        let epoch = sched_epoch();
+        unsafe {
+            slots.add(me).write_volatile(epoch);
+            if (epoch - slots.add(other).read_volatile()).abs() > 1 {
+                exit(1);
+            }
+        }
+        yield_();
+    }
+    exit(0);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    assert_eq!(mmap(start, 4096, 3 | MMAP_SHARED), 0);
+    let slots = start as *mut isize;
+    // the epoch counts this run too
+    assert!(sched_epoch() >= 1);
+    // fork both before either runs, so they start with the same pass
+    let mut pids = [0; 2];
+    for (me, pid) in pids.iter_mut().enumerate() {
+        *pid = fork();
+        if *pid == 0 {
+            child(slots, me);
+        }
+    }
+    for pid in pids {
+        let mut exit_code: i32 = -1;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+        assert_eq!(exit_code, 0);
+    }
+    println!("Test sched epoch OK!");
+    0
+}
@@ -0,0 +1,48 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::ESRCH;
+use user_lib::{exit, fork, sched_getinterval, setpriority, sleep, waitpid};
+
+/// 程序行为：两个子进程分别设为优先级 2 和 8，在同一就绪集合下，
+/// 两者报告的调度间隔之比应约为 8:2，即低优先级子进程的间隔约为高优先级的 4 倍。
+/// 不存在的进程返回 -ESRCH。
+
+/// 理想输出：
+/// Test sched_getinterval OK!
+
+const LOW_PRIO: isize = 2;
+const HIGH_PRIO: isize = 8;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let low = fork();
+    if low == 0 {
+        sleep(200);
+        exit(0);
+    }
+    let high = fork();
+    if high == 0 {
+        sleep(200);
+        exit(0);
+    }
+    assert_eq!(setpriority(low as usize, LOW_PRIO), LOW_PRIO);
+    assert_eq!(setpriority(high as usize, HIGH_PRIO), HIGH_PRIO);
+    let low_interval = sched_getinterval(low as usize);
+    let high_interval = sched_getinterval(high as usize);
+    println!("low interval = {}, high interval = {}", low_interval, high_interval);
+    assert!(high_interval >= 1);
+    assert!(low_interval > high_interval);
+    // 间隔与优先级成反比，每个间隔至多有半个时间片的舍入误差
+    let error = (low_interval * LOW_PRIO - high_interval * HIGH_PRIO).abs();
+    assert!(error <= HIGH_PRIO);
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(low as usize, &mut exit_code), low);
+    assert_eq!(waitpid(high as usize, &mut exit_code), high);
+    assert_eq!(sched_getinterval(low as usize), -ESRCH);
+    println!("Test sched_getinterval OK!");
+    0
+}
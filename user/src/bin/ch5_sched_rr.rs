@@ -0,0 +1,64 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, mmap, sched_setscheduler, waitpid, yield_, MMAP_SHARED, SCHED_RR, SCHED_STRIDE,
+};
+
+/// 程序行为：一个子进程切换为轮转调度（RR），另一个保持 stride 调度，二者都反复让出 CPU
+/// 并在共享页上计数。RR 与 stride 两类任务交替获得调度，RR 类只有一个任务，而 stride 类
+/// 还包括父进程、shell 等，所以 RR 子进程的计数不少于 stride 子进程，且二者都有进展。
+
+/// 理想输出：
+/// rr <rr 计数> stride <stride 计数>
+/// Test sched rr OK!
+
+const ROUNDS: usize = 300;
+const SLACK: usize = 8;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(sched_setscheduler(2), -22);
+    let start: usize = 0x10000000;
+    assert_eq!(mmap(start, 4096, 3 | MMAP_SHARED), 0);
+    // [0]: RR 子进程计数，[1]: stride 子进程计数，[2]: 开始标志
+    let shared = start as *mut usize;
+    let mut pids = [0isize; 2];
+    for (i, pid) in pids.iter_mut().enumerate() {
+        *pid = fork();
+        if *pid == 0 {
+            let policy = if i == 0 { SCHED_RR } else { SCHED_STRIDE };
+            assert_eq!(sched_setscheduler(policy), 0);
+            while unsafe { shared.add(2).read_volatile() } == 0 {
+                yield_();
+            }
+            // stride 子进程在 RR 子进程结束后停下
+            while unsafe { shared.read_volatile() } < ROUNDS {
+                yield_();
+                unsafe {
+                    let count = shared.add(i).read_volatile();
+                    shared.add(i).write_volatile(count + 1);
+                }
+            }
+            exit(0);
+        }
+    }
+    unsafe {
+        shared.add(2).write_volatile(1);
+    }
+    for &pid in pids.iter() {
+        let mut exit_code: i32 = -1;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+        assert_eq!(exit_code, 0);
+    }
+    let (rr, stride) = unsafe { (shared.read_volatile(), shared.add(1).read_volatile()) };
+    println!("rr {} stride {}", rr, stride);
+    assert_eq!(rr, ROUNDS);
+    assert!(stride > 0);
+    assert!(stride <= rr + SLACK);
+    println!("Test sched rr OK!");
+    0
+}
@@ -0,0 +1,43 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{get_time, setitimer, sigaction, SIGALRM};
+
+/// 程序行为：设置周期为 50ms 的间隔定时器并忙等 500ms，SIGALRM 处理函数
+/// 应被调用约 10 次；关闭定时器后不再收到 SIGALRM。
+
+/// 理想输出：
+/// Test setitimer OK!
+
+static ALARMS: AtomicUsize = AtomicUsize::new(0);
+
+fn on_alarm(signum: usize) {
+    assert_eq!(signum, SIGALRM);
+    ALARMS.fetch_add(1, Ordering::SeqCst);
+}
+
+fn busy_wait(ms: isize) {
+    let start = get_time();
+    while get_time() - start < ms {}
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(sigaction(SIGALRM, Some(on_alarm)), 0);
+    assert_eq!(setitimer(50, 50), 0);
+    busy_wait(500);
+    // 关闭定时器，返回值为上一个定时器的剩余时间
+    let left = setitimer(0, 0);
+    assert!(left > 0 && left <= 50);
+    let alarms = ALARMS.load(Ordering::SeqCst);
+    assert!((8..=11).contains(&alarms), "{} alarms in 500ms", alarms);
+    busy_wait(200);
+    assert_eq!(ALARMS.load(Ordering::SeqCst), alarms);
+    assert_eq!(setitimer(0, 0), 0);
+    println!("Test setitimer OK!");
+    0
+}
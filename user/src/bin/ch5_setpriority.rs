@@ -0,0 +1,63 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::{EINVAL, EPERM, ESRCH};
+use user_lib::{fork, get_time, getpid, setpriority, waitpid};
+
+/// 程序行为：父进程降低其中一个子进程的优先级，该子进程在相同时间内的计数应明显更少。
+/// 子进程无权修改父进程的优先级。
+
+/// 理想输出：
+/// Test setpriority OK!
+
+const MAX_TIME: isize = 1000;
+
+fn spin_delay() {
+    let mut j = true;
+    for _ in 0..10 {
+        j = !j;
+    }
+}
+
+fn count_during() -> i32 {
+    let start_time = get_time();
+    let mut acc = 0;
+    loop {
+        spin_delay();
+        acc += 1;
+        if acc % 400 == 0 && get_time() - start_time > MAX_TIME {
+            return acc;
+        }
+    }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let parent = getpid() as usize;
+    let slow = fork();
+    if slow == 0 {
+        // 子进程不能修改父进程的优先级
+        assert_eq!(setpriority(parent, 8), -EPERM);
+        user_lib::exit(count_during());
+    }
+    let fast = fork();
+    if fast == 0 {
+        user_lib::exit(count_during());
+    }
+    assert_eq!(setpriority(slow as usize, 2), 2);
+    assert_eq!(setpriority(fast as usize, 16), 16);
+    assert_eq!(setpriority(slow as usize, 1), -EINVAL);
+    let mut slow_count: i32 = 0;
+    let mut fast_count: i32 = 0;
+    assert_eq!(waitpid(slow as usize, &mut slow_count), slow);
+    assert_eq!(waitpid(fast as usize, &mut fast_count), fast);
+    println!("slow count = {}, fast count = {}", slow_count, fast_count);
+    assert!(slow_count < fast_count);
+    // 已退出的进程不再能被设置优先级
+    assert_eq!(setpriority(slow as usize, 8), -ESRCH);
+    println!("Test setpriority OK!");
+    0
+}
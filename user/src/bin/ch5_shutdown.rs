@@ -0,0 +1,32 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::EBUSY;
+use user_lib::{fork, shutdown, spawn, waitpid};
+
+/// 程序行为：fork 一个子进程后开始关机，此后 fork 与 spawn 均返回 -EBUSY，
+/// 已有的子进程仍可被正常回收；本进程退出后系统关机。
+/// 注意：运行本测例会关闭系统，应作为最后一个测例运行。
+
+/// 理想输出：
+/// Test shutdown OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        return 7;
+    }
+    assert_eq!(shutdown(), 0);
+    assert_eq!(shutdown(), -EBUSY);
+    assert_eq!(fork(), -EBUSY);
+    assert_eq!(spawn("ch5_exit0\0"), -EBUSY);
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 7);
+    println!("Test shutdown OK!");
+    0
+}
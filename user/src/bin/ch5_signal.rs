@@ -0,0 +1,58 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::errno::{EINTR, EINVAL};
+use user_lib::{
+    exit, fork, getpid, kill, pause, sigaction, sleep_blocking, waitpid, SIGKILL, SIGUSR1,
+    SIGUSR2,
+};
+
+/// 程序行为：注册 SIGUSR1 处理函数后向自己发送 SIGUSR1，处理函数运行后程序继续执行；
+/// pause 中的子进程收到 SIGUSR1 后先运行处理函数，pause 返回 -EINTR；
+/// 未注册处理函数的 SIGUSR2 按默认动作终止进程，退出码为 -12；SIGKILL 不能注册处理函数。
+
+/// 理想输出：
+/// Test signal OK!
+
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+fn on_usr1(signum: usize) {
+    assert_eq!(signum, SIGUSR1);
+    HANDLED.fetch_add(1, Ordering::SeqCst);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(sigaction(SIGKILL, Some(on_usr1)), -EINVAL);
+    assert_eq!(sigaction(SIGUSR1, Some(on_usr1)), 0);
+    assert_eq!(kill(getpid() as usize, SIGUSR1), 0);
+    assert_eq!(HANDLED.load(Ordering::SeqCst), 1);
+
+    // 子进程继承处理函数
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(pause(), -EINTR);
+        exit(HANDLED.load(Ordering::SeqCst) as i32);
+    }
+    sleep_blocking(100);
+    assert_eq!(kill(pid as usize, SIGUSR1), 0);
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 2);
+
+    let pid = fork();
+    if pid == 0 {
+        pause();
+        panic!("SIGUSR2 should have terminated the child");
+    }
+    sleep_blocking(100);
+    assert_eq!(kill(pid as usize, SIGUSR2), 0);
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -(SIGUSR2 as i32));
+    println!("Test signal OK!");
+    0
+}
@@ -0,0 +1,101 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::arch::asm;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{getpid, kill, sigaction, SIGUSR1, SIGUSR2, SYSCALL_KILL};
+
+/// 程序行为：在 t0-t6、a2-a6 中放入特定值后直接 ecall 向自己发送 SIGUSR1，
+/// 处理函数会破坏这些寄存器；sigreturn 之后它们应全部恢复，程序从 ecall 之后继续执行。
+/// 随后检查嵌套信号：SIGUSR1 处理函数中再次发送 SIGUSR1 和 SIGUSR2，
+/// SIGUSR2 的处理函数嵌套运行，而 SIGUSR1 在自身处理函数返回后才再次递送。
+
+/// 理想输出：
+/// Test sigreturn OK!
+
+static EVENTS: [AtomicUsize; 8] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+static EVENT_COUNT: AtomicUsize = AtomicUsize::new(0);
+static USR1_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+fn record(event: usize) {
+    let idx = EVENT_COUNT.fetch_add(1, Ordering::SeqCst);
+    EVENTS[idx].store(event, Ordering::SeqCst);
+}
+
+fn clobber(_signum: usize) {
+    unsafe {
+        asm!(
+            "li t0, 0", "li t1, 0", "li t2, 0", "li t3, 0", "li t4, 0", "li t5, 0", "li t6, 0",
+            "li a2, 0", "li a3, 0", "li a4, 0", "li a5, 0", "li a6, 0",
+            out("t0") _, out("t1") _, out("t2") _, out("t3") _, out("t4") _, out("t5") _,
+            out("t6") _, out("a2") _, out("a3") _, out("a4") _, out("a5") _, out("a6") _,
+        );
+    }
+}
+
+fn nested_usr1(_signum: usize) {
+    record(1);
+    if USR1_RUNS.fetch_add(1, Ordering::SeqCst) == 0 {
+        // 被屏蔽，直到本处理函数返回
+        kill(getpid() as usize, SIGUSR1);
+        kill(getpid() as usize, SIGUSR2);
+    }
+    record(3);
+}
+
+fn nested_usr2(_signum: usize) {
+    record(2);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(sigaction(SIGUSR1, Some(clobber)), 0);
+    let magic: [usize; 12] = core::array::from_fn(|i| 0x5a5a_0000 + i);
+    let mut regs = magic;
+    let ret: isize;
+    unsafe {
+        asm!(
+            "ecall",
+            inlateout("a0") getpid() as usize => ret,
+            in("a1") SIGUSR1,
+            in("a7") SYSCALL_KILL,
+            inlateout("t0") regs[0] => regs[0],
+            inlateout("t1") regs[1] => regs[1],
+            inlateout("t2") regs[2] => regs[2],
+            inlateout("t3") regs[3] => regs[3],
+            inlateout("t4") regs[4] => regs[4],
+            inlateout("t5") regs[5] => regs[5],
+            inlateout("t6") regs[6] => regs[6],
+            inlateout("a2") regs[7] => regs[7],
+            inlateout("a3") regs[8] => regs[8],
+            inlateout("a4") regs[9] => regs[9],
+            inlateout("a5") regs[10] => regs[10],
+            inlateout("a6") regs[11] => regs[11],
+        );
+    }
+    assert_eq!(ret, 0);
+    assert_eq!(regs, magic);
+
+    assert_eq!(sigaction(SIGUSR1, Some(nested_usr1)), 0);
+    assert_eq!(sigaction(SIGUSR2, Some(nested_usr2)), 0);
+    assert_eq!(kill(getpid() as usize, SIGUSR1), 0);
+    let expected = [1, 2, 3, 1, 3];
+    assert_eq!(EVENT_COUNT.load(Ordering::SeqCst), expected.len());
+    for (event, &want) in EVENTS.iter().zip(expected.iter()) {
+        assert_eq!(event.load(Ordering::SeqCst), want);
+    }
+    println!("Test sigreturn OK!");
+    0
+}
@@ -0,0 +1,41 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{fork, get_time, sleep_blocking, waitpid};
+
+/// 程序行为：父进程与三个子进程都进入阻塞睡眠，离开就绪队列。
+/// 所有任务都在睡眠时处理器进入空闲流程（wfi），由时钟中断唤醒到期的任务。
+/// 每个任务醒来时经过的时间都不少于其睡眠时长。
+
+/// 理想输出：
+/// Test sleep blocking OK!
+
+const CHILDREN: usize = 3;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut pids = [0isize; CHILDREN];
+    for (i, pid) in pids.iter_mut().enumerate() {
+        *pid = fork();
+        if *pid == 0 {
+            let sleep_ms = 100 * (i + 1);
+            let start = get_time();
+            sleep_blocking(sleep_ms);
+            let elapsed = get_time() - start;
+            user_lib::exit(if elapsed >= sleep_ms as isize { 0 } else { -1 });
+        }
+    }
+    let start = get_time();
+    sleep_blocking(50);
+    assert!(get_time() - start >= 50);
+    for pid in pids {
+        let mut exit_code: i32 = -1;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+        assert_eq!(exit_code, 0);
+    }
+    println!("Test sleep blocking OK!");
+    0
+}
@@ -0,0 +1,33 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+/// 程序行为：通过 stack_size! 申请 256 KiB 的用户栈，随后递归使用约 128 KiB 的栈。
+/// 默认的 16 KiB 用户栈在这一深度下会触发缺页，而申请到的栈应能容纳。
+
+/// 理想输出：
+/// Test stack_size OK!
+
+const FRAME_BYTES: usize = 1024;
+const DEPTH: usize = 128;
+
+stack_size!(256 * 1024);
+
+fn recurse(depth: usize) -> usize {
+    let mut frame = [0u8; FRAME_BYTES];
+    frame[depth % FRAME_BYTES] = depth as u8;
+    // 读回整块数组，防止编译器省去栈上的数组
+    let byte = unsafe { core::ptr::read_volatile(&frame[depth % FRAME_BYTES]) };
+    let below = if depth == 0 { 0 } else { recurse(depth - 1) };
+    below + byte as usize
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let expected: usize = (0..=DEPTH).map(|depth| depth as u8 as usize).sum();
+    assert_eq!(recurse(DEPTH), expected);
+    println!("Test stack_size OK!");
+    0
+}
@@ -0,0 +1,51 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, get_limit, mmap, setpriority, stride_trace, waitpid, yield_, StrideEntry,
+    LIMIT_BIG_STRIDE, MMAP_SHARED,
+};
+
+/// 程序行为：把子进程的优先级设为大于 BIG_STRIDE 的值，调用仍然成功（返回该优先级），
+/// 但 stride 至少为 1，不会因为整除得 0 让 pass 永远不动。子进程退出后父进程
+/// 仍能被调度并回收它。
+
+/// 理想输出：
+/// Test stride floor OK!
+
+fn find(entries: &[StrideEntry], pid: usize) -> StrideEntry {
+    *entries.iter().find(|entry| entry.pid == pid).unwrap()
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let big_stride = get_limit(LIMIT_BIG_STRIDE) as isize;
+    let start: usize = 0x10000000;
+    assert_eq!(mmap(start, 4096, 3 | MMAP_SHARED), 0);
+    let go = start as *mut usize;
+    let pid = fork();
+    if pid == 0 {
+        while unsafe { go.read_volatile() } == 0 {
+            yield_();
+        }
+        exit(0);
+    }
+    let mut entries = [StrideEntry::default(); 16];
+    for prio in [big_stride + 1, isize::MAX] {
+        assert_eq!(setpriority(pid as usize, prio), prio);
+        let ready = stride_trace(&mut entries) as usize;
+        let child = find(&entries[..ready.min(16)], pid as usize);
+        assert_eq!(child.priority, prio);
+        assert_eq!(child.stride, 1);
+    }
+    // stride 为 1 的子进程 pass 落后父进程很多，先放行再让出 CPU
+    unsafe { go.write_volatile(1) };
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    println!("Test stride floor OK!");
+    0
+}
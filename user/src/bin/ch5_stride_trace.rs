@@ -0,0 +1,60 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, get_limit, setpriority, stride_trace, waitpid, yield_, StrideEntry,
+    LIMIT_BIG_STRIDE,
+};
+
+/// 程序行为：fork 三个优先级不同的子进程，父进程在让出 CPU 之前读取就绪队列的
+/// stride 状态，每个子进程的 stride 应等于 BIG_STRIDE / priority，并打印调度状态表。
+
+/// 理想输出：
+/// pid <pid> pass <pass> stride <stride> priority <prio>（每个就绪进程一行）
+/// Test stride trace OK!
+
+const PRIORITIES: [isize; 3] = [5, 10, 20];
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let big_stride = get_limit(LIMIT_BIG_STRIDE) as usize;
+    let mut pids = [0isize; 3];
+    for (i, &prio) in PRIORITIES.iter().enumerate() {
+        let pid = fork();
+        if pid == 0 {
+            for _ in 0..10 {
+                yield_();
+            }
+            exit(0);
+        }
+        assert_eq!(setpriority(pid as usize, prio), prio);
+        pids[i] = pid;
+    }
+    let mut entries = [StrideEntry::default(); 16];
+    let ready = stride_trace(&mut entries);
+    assert!(ready >= 3);
+    let entries = &entries[..(ready as usize).min(16)];
+    for entry in entries {
+        println!(
+            "pid {} pass {} stride {} priority {}",
+            entry.pid, entry.pass, entry.stride, entry.priority
+        );
+    }
+    for (&pid, &prio) in pids.iter().zip(PRIORITIES.iter()) {
+        let entry = entries
+            .iter()
+            .find(|entry| entry.pid == pid as usize)
+            .unwrap();
+        assert_eq!(entry.priority, prio);
+        assert_eq!(entry.stride, big_stride / prio as usize);
+    }
+    for &pid in pids.iter() {
+        let mut exit_code: i32 = -1;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    }
+    println!("Test stride trace OK!");
+    0
+}
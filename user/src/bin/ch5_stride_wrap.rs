@@ -0,0 +1,67 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, mmap, setpriority, waitpid, yield_, MMAP_SHARED};
+
+/// 程序行为：两个优先级为 2 的子进程的 stride 接近 BIG_STRIDE 的一半，每调度两次 pass
+/// 就回绕一次。它们交替让出 CPU 并在共享页上计数，回绕多次后两者的计数始终接近，
+/// 说明回绕后的比较仍然公平，内核也没有因为溢出而 panic。
+
+/// 理想输出：
+/// Test stride wrap OK!
+
+const ROUNDS: usize = 200;
+const SLACK: usize = 8;
+
+fn count_rounds(counters: *mut usize, me: usize) -> ! {
+    let other = 1 - me;
+    // 等两个子进程都设好优先级再开始
+    while unsafe { counters.add(2).read_volatile() } == 0 {
+        yield_();
+    }
+    for _ in 0..ROUNDS {
+        yield_();
+        unsafe {
+            let mine = counters.add(me).read_volatile() + 1;
+            counters.add(me).write_volatile(mine);
+            let theirs = counters.add(other).read_volatile();
+            // 对方提前结束之后就不再比较
+            if theirs < ROUNDS && mine > theirs + SLACK {
+                exit(-1);
+            }
+        }
+    }
+    exit(0);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    assert_eq!(mmap(start, 4096, 3 | MMAP_SHARED), 0);
+    let counters = start as *mut usize;
+    let mut pids = [0isize; 2];
+    for (me, pid) in pids.iter_mut().enumerate() {
+        *pid = fork();
+        if *pid == 0 {
+            count_rounds(counters, me);
+        }
+        assert_eq!(setpriority(*pid as usize, 2), 2);
+    }
+    unsafe {
+        counters.add(2).write_volatile(1);
+    }
+    for &pid in pids.iter() {
+        let mut exit_code: i32 = -1;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+        assert_eq!(exit_code, 0);
+    }
+    unsafe {
+        assert_eq!(counters.read_volatile(), ROUNDS);
+        assert_eq!(counters.add(1).read_volatile(), ROUNDS);
+    }
+    println!("Test stride wrap OK!");
+    0
+}
@@ -0,0 +1,60 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, mincore, mmap, munmap, swapout, waitpid};
+
+/// 程序行为：向 4 页 mmap 区域写入特征数据后换出，页面不再驻留；再次读取时
+/// 由缺页处理换入，数据保持不变。已换出的页被 fork 出的子进程复制，只读映射不能换出。
+
+/// 理想输出：
+/// Test swapout OK!
+
+const PAGE: usize = 4096;
+const PAGES: usize = 4;
+
+fn pattern(addr: usize) -> u8 {
+    (addr ^ (addr >> 12)) as u8
+}
+
+fn check_pattern(start: usize) -> bool {
+    (start..start + PAGE * PAGES).all(|addr| unsafe { *(addr as *const u8) } == pattern(addr))
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    let mut vec = [0u8; PAGES];
+    assert_eq!(mmap(start, PAGE * PAGES, 3), 0);
+    for addr in start..start + PAGE * PAGES {
+        unsafe {
+            *(addr as *mut u8) = pattern(addr);
+        }
+    }
+    assert_eq!(swapout(start, PAGE * PAGES), PAGES as isize);
+    assert_eq!(mincore(start, PAGE * PAGES, &mut vec), 0);
+    assert_eq!(vec, [0; PAGES]);
+    // 已换出的页不会再次换出
+    assert_eq!(swapout(start, PAGE * PAGES), 0);
+    // 子进程从换出空间复制这些页
+    let pid = fork();
+    if pid == 0 {
+        exit(if check_pattern(start) { 0 } else { -1 });
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert!(check_pattern(start));
+    assert_eq!(mincore(start, PAGE * PAGES, &mut vec), 0);
+    assert_eq!(vec, [1; PAGES]);
+    // 换出后直接解除映射，换出空间随之释放
+    assert_eq!(swapout(start, PAGE * PAGES), PAGES as isize);
+    assert_eq!(munmap(start, PAGE * PAGES), 0);
+    assert_eq!(mmap(start, PAGE, 1), 0);
+    assert_eq!(swapout(start, PAGE), -22);
+    assert_eq!(munmap(start, PAGE), 0);
+    println!("Test swapout OK!");
+    0
+}
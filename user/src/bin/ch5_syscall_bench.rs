@@ -0,0 +1,43 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, getpid, task_info, TaskInfo};
+
+/// 程序行为：分别连续调用 getpid、get_time、task_info 若干次，打印每毫秒完成的调用数，
+/// 用于比较系统调用热路径优化前后的吞吐量。task_info 统计的调用次数应与实际一致。
+
+/// 理想输出：
+/// getpid: <n> calls/ms
+/// get_time: <n> calls/ms
+/// task_info: <n> calls/ms
+/// Test syscall bench OK!
+
+const SYSCALL_GETPID: usize = 172;
+const SYSCALL_TASK_INFO: usize = 410;
+const ROUNDS: usize = 20000;
+
+fn bench(name: &str, mut f: impl FnMut()) {
+    let start = get_time();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    let elapsed = (get_time() - start).max(1) as usize;
+    println!("{}: {} calls/ms", name, ROUNDS / elapsed);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = getpid();
+    bench("getpid", || assert_eq!(getpid(), pid));
+    bench("get_time", || assert!(get_time() >= 0));
+    let info = TaskInfo::new();
+    bench("task_info", || assert_eq!(task_info(&info), 0));
+    assert_eq!(task_info(&info), 0);
+    assert!(info.syscall_times[SYSCALL_GETPID] as usize >= ROUNDS + 1);
+    assert_eq!(info.syscall_times[SYSCALL_TASK_INFO] as usize, ROUNDS + 1);
+    println!("Test syscall bench OK!");
+    0
+}
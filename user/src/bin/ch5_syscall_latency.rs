@@ -0,0 +1,55 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    enable_syscall_latency, fork, syscall_latency, waitpid, SyscallLatency, SYSCALL_FORK,
+    SYSCALL_GETPID,
+};
+
+/// 程序行为：开启系统调用耗时统计后执行若干次 fork，fork 的记录次数与总耗时应大于零；
+/// 关闭统计后再调用不再计入。
+
+/// 理想输出：
+/// Test syscall latency OK!
+
+const FORKS: usize = 4;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut before = SyscallLatency::new();
+    assert_eq!(syscall_latency(SYSCALL_FORK, &mut before), 0);
+    assert_eq!(enable_syscall_latency(true), 0);
+    for _ in 0..FORKS {
+        let pid = fork();
+        if pid == 0 {
+            user_lib::exit(0);
+        }
+        let mut exit_code: i32 = -1;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    }
+    assert_eq!(enable_syscall_latency(false), 1);
+    let mut after = SyscallLatency::new();
+    assert_eq!(syscall_latency(SYSCALL_FORK, &mut after), 0);
+    println!(
+        "fork: {} calls, {} us in total, histogram {:?}",
+        after.count - before.count,
+        after.total_us - before.total_us,
+        after.buckets
+    );
+    assert!(after.count - before.count >= FORKS);
+    assert!(after.total_us > before.total_us);
+    assert_eq!(after.buckets.iter().sum::<usize>(), after.count);
+    // 关闭后不再记录
+    let mut getpid_before = SyscallLatency::new();
+    assert_eq!(syscall_latency(SYSCALL_GETPID, &mut getpid_before), 0);
+    user_lib::getpid();
+    let mut getpid_after = SyscallLatency::new();
+    assert_eq!(syscall_latency(SYSCALL_GETPID, &mut getpid_after), 0);
+    assert_eq!(getpid_after.count, getpid_before.count);
+    assert!(syscall_latency(500, &mut getpid_after) < 0);
+    println!("Test syscall latency OK!");
+    0
+}
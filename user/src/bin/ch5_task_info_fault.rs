@@ -0,0 +1,35 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{mmap, munmap, task_info, TaskInfo};
+
+/// 程序行为：TaskInfo 指针跨越两页，第一页已映射而第二页未映射。
+/// task_info 应返回 -1，且第一页中不应有任何字节被写入。
+
+/// 理想输出：
+/// Test task_info fault OK!
+
+const PAGE_SIZE: usize = 4096;
+const FILL: u8 = 0xa5;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    // 只映射一页，紧随其后的一页保持未映射
+    assert_eq!(mmap(start, PAGE_SIZE, 3), 0);
+    let page = unsafe { core::slice::from_raw_parts_mut(start as *mut u8, PAGE_SIZE) };
+    page.fill(FILL);
+    let info_addr = start + PAGE_SIZE - 16;
+    let info = unsafe { &*(info_addr as *const TaskInfo) };
+    assert_eq!(task_info(info), -1);
+    assert!(page.iter().all(|&byte| byte == FILL));
+    // 完整落在已映射页内的指针仍然可用
+    let info = TaskInfo::new();
+    assert_eq!(task_info(&info), 0);
+    assert_eq!(munmap(start, PAGE_SIZE), 0);
+    println!("Test task_info fault OK!");
+    0
+}
@@ -0,0 +1,53 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, getpid, task_info, waitpid, yield_, TaskInfo, SYSCALL_FORK, SYSCALL_GETPID,
+    SYSCALL_TASK_INFO, SYSCALL_YIELD,
+};
+
+/// 程序行为：父进程先调用若干次 getpid 和 yield 再 fork，之后父子进程调用不同的系统调用。
+/// 子进程的系统调用计数从零开始，只包含它自己的调用；父进程的计数不受 fork 和子进程影响。
+
+/// 理想输出：
+/// Test task info fork OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    for _ in 0..3 {
+        getpid();
+    }
+    for _ in 0..2 {
+        yield_();
+    }
+    let pid = fork();
+    if pid == 0 {
+        for _ in 0..5 {
+            getpid();
+        }
+        let info = TaskInfo::new();
+        assert_eq!(task_info(&info), 0);
+        let ok = info.syscall_times[SYSCALL_GETPID] == 5
+            && info.syscall_times[SYSCALL_YIELD] == 0
+            && info.syscall_times[SYSCALL_FORK] == 0
+            && info.syscall_times[SYSCALL_TASK_INFO] == 1;
+        exit(if ok { 0 } else { -1 });
+    }
+    for _ in 0..4 {
+        yield_();
+    }
+    let info = TaskInfo::new();
+    assert_eq!(task_info(&info), 0);
+    assert_eq!(info.syscall_times[SYSCALL_GETPID], 3);
+    assert_eq!(info.syscall_times[SYSCALL_YIELD], 6);
+    assert_eq!(info.syscall_times[SYSCALL_FORK], 1);
+    assert_eq!(info.syscall_times[SYSCALL_TASK_INFO], 1);
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    println!("Test task info fork OK!");
+    0
+}
@@ -0,0 +1,31 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{task_info, TaskInfo};
+
+/// 程序行为：反复先把 TaskInfo 中的计数清零，再调用 task_info，并在返回后立即读取。
+/// 每次读到的都是内核刚写入的值：task_info 自己的调用次数每次恰好加一。
+
+/// 理想输出：
+/// Test task_info fresh OK!
+
+const SYSCALL_TASK_INFO: usize = 410;
+const ROUNDS: u32 = 1000;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let info = TaskInfo::new();
+    let count = &info.syscall_times[SYSCALL_TASK_INFO] as *const u32 as *mut u32;
+    assert_eq!(task_info(&info), 0);
+    let first = unsafe { count.read_volatile() };
+    for round in 1..=ROUNDS {
+        unsafe { count.write_volatile(0) };
+        assert_eq!(task_info(&info), 0);
+        assert_eq!(unsafe { count.read_volatile() }, first + round);
+    }
+    println!("Test task_info fresh OK!");
+    0
+}
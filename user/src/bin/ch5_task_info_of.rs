@@ -0,0 +1,89 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::{EPERM, ESRCH};
+use user_lib::{
+    exit, fork, getpid, getppid, mmap, sleep_blocking, task_info_of, waitpid, yield_, TaskInfo,
+    TaskStatus, MMAP_SHARED,
+};
+
+/// 程序行为：查询自己的状态为 Running；刚 fork 出、尚未运行的子进程为 Ready；
+/// 睡眠中的子进程为 Blocked；已退出但未被回收的子进程为 Exited（僵尸）；
+/// 回收之后再查询返回 -ESRCH。孙进程可以查询，子进程查询父进程返回 -EPERM。
+
+/// 理想输出：
+/// Test task_info_of OK!
+
+const SHARED: usize = 0x10000000;
+
+fn status_of(pid: usize) -> TaskStatus {
+    let mut info = TaskInfo::new();
+    assert_eq!(task_info_of(pid, &mut info), 0);
+    info.status
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(status_of(getpid() as usize), TaskStatus::Running);
+
+    let pid = fork();
+    if pid == 0 {
+        exit(0);
+    }
+    // 除非时钟中断恰好让子进程先运行，此时它尚未被调度过
+    let status = status_of(pid as usize);
+    assert!(status == TaskStatus::Ready || status == TaskStatus::Exited);
+    while status_of(pid as usize) != TaskStatus::Exited {
+        yield_();
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    let mut info = TaskInfo::new();
+    assert_eq!(task_info_of(pid as usize, &mut info), -ESRCH);
+
+    let pid = fork();
+    if pid == 0 {
+        sleep_blocking(200);
+        exit(0);
+    }
+    for _ in 0..5 {
+        yield_();
+    }
+    assert_eq!(status_of(pid as usize), TaskStatus::Blocked);
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+
+    // 只能查询自己所在的子树
+    assert_eq!(mmap(SHARED, 4096, 3 | MMAP_SHARED), 0);
+    let grandchild_pid = SHARED as *mut usize;
+    let pid = fork();
+    if pid == 0 {
+        let mut info = TaskInfo::new();
+        assert_eq!(task_info_of(getppid() as usize, &mut info), -EPERM);
+        let grandchild = fork();
+        if grandchild == 0 {
+            sleep_blocking(500);
+            exit(0);
+        }
+        unsafe { grandchild_pid.write_volatile(grandchild as usize) };
+        sleep_blocking(200);
+        exit(0);
+    }
+    let grandchild = loop {
+        match unsafe { grandchild_pid.read_volatile() } {
+            0 => {
+                yield_();
+            }
+            pid => break pid,
+        }
+    };
+    let mut info = TaskInfo::new();
+    assert_eq!(task_info_of(grandchild, &mut info), 0);
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    // 子进程退出后孙进程被过继给 initproc，不再属于本进程的子树
+    assert_eq!(task_info_of(grandchild, &mut info), -EPERM);
+    println!("Test task_info_of OK!");
+    0
+}
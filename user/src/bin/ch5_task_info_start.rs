@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, get_time, task_info, task_info_of, waitpid, TaskInfo};
+
+/// 程序行为：父进程先创建几个忙等的子进程，再创建被测子进程，被测子进程在就绪队列中
+/// 排在它们后面。它还没运行时 task_info_of 报告的时间为 0；它第一次运行后，
+/// task_info 报告的时间从第一次运行算起，而不是从 fork 算起。
+
+/// 理想输出：
+/// Test task info start OK!
+
+const BURNERS: usize = 4;
+const BURN_MS: isize = 30;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut burners = [0isize; BURNERS];
+    for pid in burners.iter_mut() {
+        *pid = fork();
+        if *pid == 0 {
+            let start = get_time();
+            while get_time() - start < BURN_MS {}
+            exit(0);
+        }
+    }
+    let forked_at = get_time();
+    let pid = fork();
+    if pid == 0 {
+        let first_run = get_time();
+        let info = TaskInfo::new();
+        assert_eq!(task_info(&info), 0);
+        let now = get_time();
+        // 排队的时间不算在内
+        let ok = info.time as isize <= now - first_run + 1
+            && (first_run - forked_at < 10 || (info.time as isize) < now - forked_at);
+        exit(if ok { 0 } else { -1 });
+    }
+    let mut info = TaskInfo::new();
+    assert_eq!(task_info_of(pid as usize, &mut info), 0);
+    assert_eq!(info.time, 0);
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    for &burner in burners.iter() {
+        assert_eq!(waitpid(burner as usize, &mut exit_code), burner);
+        assert_eq!(exit_code, 0);
+    }
+    println!("Test task info start OK!");
+    0
+}
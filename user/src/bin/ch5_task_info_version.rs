@@ -0,0 +1,38 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::EINVAL;
+use user_lib::{
+    getpid, sys_task_info_of, sys_task_info_v, task_info, task_info_v, TaskInfo,
+    TASK_INFO_VERSION,
+};
+
+/// 程序行为：以与内核不一致的 TaskInfo 版本号调用 task_info_v 返回 -EINVAL，且结构体
+/// 不被写入；版本号一致时调用成功。不带版本号的 task_info 保持原有的布局与参数。
+
+/// 理想输出：
+/// Test task_info version OK!
+
+const UNTOUCHED: usize = 0x5a5a5a5a;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut info = TaskInfo::new();
+    info.time = UNTOUCHED;
+    assert_eq!(sys_task_info_v(&info, TASK_INFO_VERSION + 1), -EINVAL);
+    assert_eq!(sys_task_info_v(&info, 0), -EINVAL);
+    assert_eq!(sys_task_info_of(getpid() as usize, &mut info, TASK_INFO_VERSION + 1), -EINVAL);
+    assert_eq!(info.time, UNTOUCHED);
+    assert_eq!(task_info_v(&info), 0);
+    // task_info_v takes a shared reference, so read back what the kernel wrote
+    unsafe {
+        assert_ne!(core::ptr::read_volatile(&info.time), UNTOUCHED);
+    }
+    let info = TaskInfo::new();
+    assert_eq!(task_info(&info), 0);
+    println!("Test task_info version OK!");
+    0
+}
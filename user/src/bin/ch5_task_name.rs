@@ -0,0 +1,43 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::{EINVAL, ERANGE};
+use user_lib::{exit, fork, get_task_name, set_task_name, waitpid};
+
+/// 程序行为：被 exec/spawn 启动后任务名即应用名 ch5_task_name；改名后读回新名字；
+/// fork 出的子进程继承任务名，子进程改名不影响父进程。
+
+/// 理想输出：
+/// Test task name OK!
+
+fn name_is(expected: &str) -> bool {
+    let mut buf = [0u8; 64];
+    let len = get_task_name(&mut buf);
+    len == expected.len() as isize + 1 && &buf[..expected.len()] == expected.as_bytes()
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert!(name_is("ch5_task_name"));
+    let mut small = [0u8; 4];
+    assert_eq!(get_task_name(&mut small), -ERANGE);
+    assert_eq!(set_task_name("\0"), -EINVAL);
+    assert_eq!(set_task_name("renamed\0"), 0);
+    assert!(name_is("renamed"));
+
+    let pid = fork();
+    if pid == 0 {
+        let inherited = name_is("renamed");
+        assert_eq!(set_task_name("child\0"), 0);
+        exit(if inherited && name_is("child") { 0 } else { 1 });
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert!(name_is("renamed"));
+    println!("Test task name OK!");
+    0
+}
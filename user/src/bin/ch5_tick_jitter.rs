@@ -0,0 +1,52 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::vdso_time_us;
+
+/// 程序行为：忙等并观察 vDSO 页中时间的每次更新（每次时钟中断更新一次），
+/// 相邻两次更新的间隔应落在 10ms 的整数倍附近，且首尾跨度与经过的时钟周期数相符，
+/// 即时钟中断间隔不随负载漂移。
+
+/// 理想输出：
+/// Test tick jitter OK!
+
+const TICK_US: usize = 10_000;
+const JITTER_US: usize = 2_000;
+const TICKS: usize = 50;
+
+fn next_update(last: usize) -> usize {
+    loop {
+        let now = vdso_time_us();
+        if now != last {
+            return now;
+        }
+    }
+}
+
+fn off_grid(interval: usize) -> usize {
+    let rem = interval % TICK_US;
+    rem.min(TICK_US - rem)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let first = next_update(vdso_time_us());
+    let mut last = first;
+    let mut ticks = 0;
+    for _ in 0..TICKS {
+        let now = next_update(last);
+        let interval = now - last;
+        assert!(interval + JITTER_US >= TICK_US, "tick came early: {}us", interval);
+        assert!(off_grid(interval) < JITTER_US, "tick off grid: {}us", interval);
+        ticks += (interval + TICK_US / 2) / TICK_US;
+        last = now;
+    }
+    // 各次间隔的误差不应累积
+    assert!(off_grid(last - first) < JITTER_US);
+    assert_eq!((last - first + TICK_US / 2) / TICK_US, ticks);
+    println!("Test tick jitter OK!");
+    0
+}
@@ -0,0 +1,39 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{fork, get_tls, set_tls, tp, waitpid, yield_};
+
+/// 程序行为：父子进程各自设置不同的 TLS 指针，然后反复让出 CPU。
+/// 每次切换回来后 tp 寄存器与 get_tls 都应仍是自己设置的值。
+
+/// 理想输出：
+/// Test tls OK!
+
+const ROUNDS: usize = 50;
+
+fn check_tls(value: usize) {
+    assert_eq!(set_tls(value), 0);
+    for _ in 0..ROUNDS {
+        yield_();
+        assert_eq!(tp(), value);
+        assert_eq!(get_tls(), value);
+    }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        check_tls(0x2222_0000);
+        user_lib::exit(0);
+    }
+    check_tls(0x1111_0000);
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    println!("Test tls OK!");
+    0
+}
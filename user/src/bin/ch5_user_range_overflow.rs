@@ -0,0 +1,26 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::EINVAL;
+use user_lib::{exec_mem, task_info, TaskInfo};
+
+/// 程序行为：传入首地址加长度会回绕的用户缓冲区。内核应把这些区间视为不可访问，
+/// 返回 -1，而不是因 end 回绕到 start 之下而放行、随后在内核中 panic。
+
+/// 理想输出：
+/// Test user range overflow OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    // len = usize::MAX，end 回绕到 start 之下
+    assert_eq!(exec_mem(0x10000, usize::MAX), -EINVAL);
+    assert_eq!(exec_mem(0x10000, usize::MAX - 0x8000), -EINVAL);
+    // 结构体跨过地址空间的末尾
+    let info = unsafe { &*((usize::MAX - 15) as *const TaskInfo) };
+    assert_eq!(task_info(info), -1);
+    println!("Test user range overflow OK!");
+    0
+}
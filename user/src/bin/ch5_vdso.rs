@@ -0,0 +1,39 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, get_times, vdso_time_us, TimeVal};
+
+/// 程序行为：不经系统调用读取 vDSO 页中的时间，它应随时间前进，
+/// 且与 get_times 得到的 monotonic 时间相差不超过几个时钟中断周期（每个 10ms）。
+
+/// 理想输出：
+/// Test vdso OK!
+
+const TOLERANCE_US: usize = 30_000;
+
+fn syscall_time_us() -> usize {
+    let mut times = [TimeVal::default(), TimeVal::default()];
+    assert_eq!(get_times(&mut times), 0);
+    times[0].sec * 1_000_000 + times[0].usec
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start = get_time();
+    // 至少经过一次时钟中断
+    while get_time() - start < 50 {}
+    let first = vdso_time_us();
+    let now = syscall_time_us();
+    assert!(first <= now);
+    assert!(now - first < TOLERANCE_US);
+    let start = get_time();
+    while get_time() - start < 100 {}
+    let second = vdso_time_us();
+    assert!(second > first);
+    assert!(second - first >= 50_000);
+    println!("Test vdso OK!");
+    0
+}
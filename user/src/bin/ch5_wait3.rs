@@ -0,0 +1,41 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, get_time, wait3, RUsage};
+
+/// 程序行为：子进程计算约 100ms 后以 7 退出，父进程用一次 wait3 回收它，
+/// 同时得到退出码和资源使用：CPU 时间非零，最大驻留内存非零。
+/// 两个输出参数都可以省略。
+
+/// 理想输出：
+/// Test wait3 OK!
+
+const BUSY_MS: isize = 100;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        let start = get_time();
+        while get_time() - start < BUSY_MS {}
+        exit(7);
+    }
+    let mut exit_code: i32 = 0;
+    let mut usage = RUsage::default();
+    assert_eq!(wait3(pid, Some(&mut exit_code), Some(&mut usage)), pid);
+    assert_eq!(exit_code, 7);
+    assert!(usage.cpu_time > 0);
+    assert!(usage.max_rss > 0);
+
+    let pid = fork();
+    if pid == 0 {
+        exit(3);
+    }
+    assert_eq!(wait3(pid, None, None), pid);
+    assert_eq!(wait3(pid, None, None), -10);
+    println!("Test wait3 OK!");
+    0
+}
@@ -0,0 +1,42 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::ECHILD;
+use user_lib::{exit, fork, sleep, waitall, WaitResult};
+
+/// 程序行为：fork 五个以不同退出码退出的子进程，等它们全部退出后
+/// 用一次 waitall 回收全部子进程，核对 pid 与退出码；之后再调用返回 -ECHILD。
+
+/// 理想输出：
+/// Test waitall OK!
+
+const N: usize = 5;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut pids = [0isize; N];
+    for (i, pid) in pids.iter_mut().enumerate() {
+        *pid = fork();
+        if *pid == 0 {
+            exit(100 + i as i32);
+        }
+        assert!(*pid > 0);
+    }
+    // 子进程立即退出，等待足够长的时间让它们都成为僵尸进程
+    sleep(100);
+    let mut results = [WaitResult::default(); N + 1];
+    assert_eq!(waitall(&mut results), N as isize);
+    for (i, pid) in pids.iter().enumerate() {
+        let result = results[..N]
+            .iter()
+            .find(|r| r.pid == *pid as usize)
+            .expect("child not reaped");
+        assert_eq!(result.exit_code, 100 + i as i32);
+    }
+    assert_eq!(waitall(&mut results), -ECHILD);
+    println!("Test waitall OK!");
+    0
+}
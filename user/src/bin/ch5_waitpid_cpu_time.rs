@@ -0,0 +1,40 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{fork, get_time, waitpid_cpu_time};
+
+/// 程序行为：子进程进行约 200ms 的计算后退出，父进程回收时获取其 CPU 时间。
+/// 该时间应大于零，且不超过子进程从创建到被回收所经历的时间。
+
+/// 理想输出：
+/// Test waitpid cpu time OK!
+
+const COMPUTE_MS: isize = 200;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start = get_time();
+    let pid = fork();
+    if pid == 0 {
+        let begin = get_time();
+        let mut acc: usize = 0;
+        while get_time() - begin < COMPUTE_MS {
+            for i in 0..1000 {
+                acc = acc.wrapping_mul(31).wrapping_add(i);
+            }
+        }
+        user_lib::exit((acc & 1) as i32);
+    }
+    let mut exit_code: i32 = -1;
+    let mut cpu_time_us: usize = 0;
+    assert_eq!(waitpid_cpu_time(pid as usize, &mut exit_code, &mut cpu_time_us), pid);
+    let elapsed_ms = (get_time() - start) as usize;
+    println!("child cpu time = {}us, elapsed = {}ms", cpu_time_us, elapsed_ms);
+    assert!(cpu_time_us > 0);
+    assert!(cpu_time_us / 1000 <= elapsed_ms);
+    println!("Test waitpid cpu time OK!");
+    0
+}
@@ -0,0 +1,49 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, set_auto_reap, sys_waitpid, waitpid, waitpid_livelocks, yield_};
+
+/// 程序行为：子进程开启自动回收后 fork 出孙进程，只调用一次 waitpid 等待它，此后只让出 CPU
+/// 而不再检查。孙进程退出后被自动回收而不复存在：父进程等待子进程，子进程等待已不存在的孙进程，
+/// shell 与 initproc 也都在 waitpid 中空转，又没有阻塞的任务与定时器，
+/// 内核应检测到活锁并打印警告，检测计数增加。
+/// 需要单独运行，其他并行运行的测例会让条件不成立。
+
+/// 理想输出：
+/// [kernel] waitpid livelock: ...
+/// Test waitpid livelock OK!
+
+const SPINS: usize = 1000;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let before = waitpid_livelocks();
+    assert!(before >= 0);
+    let pid = fork();
+    if pid == 0 {
+        set_auto_reap(true);
+        let grandchild = fork();
+        if grandchild == 0 {
+            for _ in 0..10 {
+                yield_();
+            }
+            exit(0);
+        }
+        let mut exit_code: i32 = 0;
+        assert_eq!(sys_waitpid(grandchild, &mut exit_code), -2);
+        // spin as a waitpid loop would, without ever asking again
+        for _ in 0..SPINS {
+            yield_();
+        }
+        exit(0);
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert!(waitpid_livelocks() > before);
+    println!("Test waitpid livelock OK!");
+    0
+}
@@ -0,0 +1,48 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::EFAULT;
+use user_lib::{exit, fork, sys_waitpid, yield_};
+
+/// 程序行为：sys_waitpid 的 exit_code 指针为空时只返回 pid，不写入退出码；
+/// 指针非空但不可写（未映射）时返回 -EFAULT，且子进程不会被回收，之后仍可正常等待。
+
+/// 理想输出：
+/// Test waitpid null ptr OK!
+
+const UNMAPPED: usize = 0x10000000;
+
+fn wait_raw(pid: isize, exit_code: *mut i32) -> isize {
+    loop {
+        match sys_waitpid(pid, exit_code) {
+            -2 => {
+                yield_();
+            }
+            n => return n,
+        }
+    }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        exit(7);
+    }
+    assert_eq!(wait_raw(pid, UNMAPPED as *mut i32), -EFAULT);
+    // 子进程仍未被回收
+    assert_eq!(wait_raw(pid, core::ptr::null_mut()), pid);
+
+    let pid = fork();
+    if pid == 0 {
+        exit(8);
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(wait_raw(pid, &mut exit_code), pid);
+    assert_eq!(exit_code, 8);
+    println!("Test waitpid null ptr OK!");
+    0
+}
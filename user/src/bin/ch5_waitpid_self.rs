@@ -0,0 +1,33 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::errno::ECHILD;
+use user_lib::{exit, fork, getpid, getppid, waitpid};
+
+/// 程序行为：进程等待自身的 pid 应立即返回 -ECHILD，而不是阻塞。
+/// 等待一个存在但并非子进程的进程（如父进程）同样返回 -ECHILD。
+
+/// 理想输出：
+/// Test waitpid self OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(getpid() as usize, &mut exit_code), -ECHILD);
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(waitpid(getpid() as usize, &mut exit_code), -ECHILD);
+        // 父进程仍然存活，但不是本进程的子进程
+        assert_eq!(waitpid(getppid() as usize, &mut exit_code), -ECHILD);
+        exit(7);
+    }
+    // 有子进程时等待自身也不会等到子进程
+    assert_eq!(waitpid(getpid() as usize, &mut exit_code), -ECHILD);
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 7);
+    println!("Test waitpid self OK!");
+    0
+}
@@ -0,0 +1,30 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, sleep_blocking, waitpid, waitpid_livelocks};
+
+/// 程序行为：子进程阻塞睡眠后正常退出，父进程在 waitpid 中等待它。
+/// 睡眠的子进程会被定时器唤醒，这不是活锁，内核的检测计数不应增加。
+
+/// 理想输出：
+/// Test waitpid sleep no livelock OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let before = waitpid_livelocks();
+    assert!(before >= 0);
+    let pid = fork();
+    if pid == 0 {
+        sleep_blocking(100);
+        exit(0);
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert_eq!(waitpid_livelocks(), before);
+    println!("Test waitpid sleep no livelock OK!");
+    0
+}
@@ -0,0 +1,68 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, kill, pause, sigaction, sleep_blocking, waitpid, waitqueue_stats, yield_,
+    WaitQueueStats, SIGUSR1,
+};
+
+/// 程序行为：若干轮中，每轮的子进程先睡眠几次，再 pause 等待父进程的信号。
+/// 父进程看到它们全部阻塞在 pause 上后逐个发信号唤醒并回收。阻塞计数随之涨落，
+/// 每轮结束后都回到 0，说明没有任务留在等待队列中。
+
+/// 理想输出：
+/// Test waitqueue stats OK!
+
+const ROUNDS: usize = 5;
+const CHILDREN: usize = 3;
+
+fn on_usr1(_signum: usize) {}
+
+fn stats() -> WaitQueueStats {
+    let mut stats = WaitQueueStats::default();
+    let blocked = waitqueue_stats(&mut stats);
+    assert_eq!(blocked as usize, stats.sleep + stats.pause);
+    stats
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(sigaction(SIGUSR1, Some(on_usr1)), 0);
+    for _ in 0..ROUNDS {
+        let mut pids = [0isize; CHILDREN];
+        for pid in pids.iter_mut() {
+            *pid = fork();
+            if *pid == 0 {
+                for _ in 0..3 {
+                    sleep_blocking(5);
+                }
+                pause();
+                exit(0);
+            }
+        }
+        loop {
+            let stats = stats();
+            assert!(stats.sleep + stats.pause <= CHILDREN);
+            if stats.pause == CHILDREN {
+                break;
+            }
+            yield_();
+        }
+        for &pid in pids.iter() {
+            assert_eq!(kill(pid as usize, SIGUSR1), 0);
+        }
+        for &pid in pids.iter() {
+            let mut exit_code: i32 = -1;
+            assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+            assert_eq!(exit_code, 0);
+        }
+        let stats = stats();
+        assert_eq!(stats.sleep, 0);
+        assert_eq!(stats.pause, 0);
+    }
+    println!("Test waitqueue stats OK!");
+    0
+}
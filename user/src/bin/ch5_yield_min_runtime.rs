@@ -0,0 +1,57 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, get_time, mmap, sched_epoch, set_yield_min_runtime, waitpid, yield_, MMAP_SHARED,
+};
+
+/// 程序行为：子进程不停地 yield，父进程先在同样的紧密 yield 循环里跑 WINDOW_MS 毫秒，
+/// 记下被调度的次数；再设置 yield 的最短运行时间 MIN_US 微秒后重复一遍。后一次的
+/// 调度次数不超过 WINDOW_MS 内按 MIN_US 和时钟中断能切换的上限，且少于前一次。
+
+/// 理想输出：
+/// Test yield min runtime OK!
+
+const WINDOW_MS: isize = 50;
+const MIN_US: usize = 2000;
+/// Timer ticks per second, each one may switch the task out early
+const TICKS_PER_SEC: isize = 100;
+
+fn switches_in_window() -> isize {
+    let start_epoch = sched_epoch();
+    let start = get_time();
+    while get_time() - start < WINDOW_MS {
+        yield_();
+    }
+    sched_epoch() - start_epoch
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    assert_eq!(mmap(start, 4096, 3 | MMAP_SHARED), 0);
+    let done = start as *mut usize;
+    let pid = fork();
+    if pid == 0 {
+        while unsafe { done.read_volatile() } == 0 {
+            yield_();
+        }
+        exit(0);
+    }
+    let naive = switches_in_window();
+    assert_eq!(set_yield_min_runtime(MIN_US), 0);
+    let bounded = switches_in_window();
+    assert_eq!(set_yield_min_runtime(0), MIN_US as isize);
+    unsafe { done.write_volatile(1) };
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    let bound = WINDOW_MS * 1000 / MIN_US as isize + WINDOW_MS * TICKS_PER_SEC / 1000 + 2;
+    assert!(bounded <= bound);
+    assert!(bounded < naive);
+    println!("Test yield min runtime OK!");
+    0
+}
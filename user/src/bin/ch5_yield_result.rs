@@ -0,0 +1,36 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{fork, kill, waitpid, yield_, SIGKILL};
+
+/// 程序行为：没有其他可运行任务时（等待子进程的 shell 与 initproc 不计）yield 返回 0；
+/// fork 出一个不停 yield 的子进程后，父进程每次 yield 都返回 1；杀死并回收子进程后
+/// yield 再次返回 0。
+
+/// 理想输出：
+/// Test yield result OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    for _ in 0..5 {
+        assert_eq!(yield_(), 0);
+    }
+    let pid = fork();
+    if pid == 0 {
+        loop {
+            yield_();
+        }
+    }
+    for _ in 0..10 {
+        assert_eq!(yield_(), 1);
+    }
+    assert_eq!(kill(pid as usize, SIGKILL), 0);
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(yield_(), 0);
+    println!("Test yield result OK!");
+    0
+}
@@ -0,0 +1,52 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, get_limit, get_time, sleep_blocking, wait, zombie_count,
+    LIMIT_ZOMBIE_WARN_THRESHOLD,
+};
+
+/// 程序行为：fork 出超过告警阈值个立即退出的子进程但不回收，僵尸进程数随之上升；
+/// 之后的时钟中断里内核打印一次僵尸进程过多的告警，告警计数增加。全部回收后
+/// 僵尸进程数回到原来的值。
+
+/// 理想输出：
+/// [kernel] N zombie tasks are waiting to be reaped, is a waitpid missing?
+/// Test zombie count OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let threshold = get_limit(LIMIT_ZOMBIE_WARN_THRESHOLD) as usize;
+    let mut warnings_before = 0;
+    let zombies_before = zombie_count(Some(&mut warnings_before)) as usize;
+    let children = threshold + 8;
+    for _ in 0..children {
+        if fork() == 0 {
+            exit(0);
+        }
+    }
+    // every child exits as soon as it first runs
+    let start = get_time();
+    while (zombie_count(None) as usize) < zombies_before + children {
+        assert!(get_time() - start < 2000, "children did not exit");
+        sleep_blocking(1);
+    }
+    // at most one warning per second, an earlier one may still hold it back
+    let mut warnings = warnings_before;
+    while warnings == warnings_before {
+        assert!(get_time() - start < 3000, "no zombie warning");
+        sleep_blocking(10);
+        zombie_count(Some(&mut warnings));
+    }
+    let mut exit_code: i32 = 0;
+    for _ in 0..children {
+        assert!(wait(&mut exit_code) > 0);
+        assert_eq!(exit_code, 0);
+    }
+    assert_eq!(zombie_count(None) as usize, zombies_before);
+    println!("Test zombie count OK!");
+    0
+}
@@ -0,0 +1,16 @@
+//! Error numbers returned by syscalls, matching the kernel
+//!
+//! A failing syscall returns the negated error number, e.g. `-ENOMEM`.
+
+pub const EPERM: isize = 1;
+pub const ENOENT: isize = 2;
+pub const ESRCH: isize = 3;
+pub const EINTR: isize = 4;
+pub const ECHILD: isize = 10;
+pub const ENOMEM: isize = 12;
+pub const EFAULT: isize = 14;
+pub const EBUSY: isize = 16;
+pub const EEXIST: isize = 17;
+pub const EINVAL: isize = 22;
+pub const ERANGE: isize = 34;
+pub const ENAMETOOLONG: isize = 36;
@@ -5,6 +5,7 @@
 
 #[macro_use]
 pub mod console;
+pub mod errno;
 mod lang_items;
 mod syscall;
 
@@ -20,6 +21,17 @@ pub use syscall::*;
 
 const USER_HEAP_SIZE: usize = 16384;
 
+/// Ask the loader for a user stack of `$size` bytes instead of the default,
+/// rounded up to whole pages and capped by the kernel. Use once per app
+#[macro_export]
+macro_rules! stack_size {
+    ($size: expr) => {
+        #[used]
+        #[link_section = ".stack_size"]
+        static STACK_SIZE: usize = $size;
+    };
+}
+
 static mut HEAP_SPACE: [u8; USER_HEAP_SIZE] = [0; USER_HEAP_SIZE];
 
 #[global_allocator]
@@ -104,6 +116,7 @@ pub enum TaskStatus {
     Ready,
     Running,
     Exited,
+    Blocked,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -114,6 +127,9 @@ pub struct SyscallInfo {
 
 const MAX_SYSCALL_NUM: usize = 500;
 
+/// Layout version of `TaskInfo`, must match the one of the kernel
+pub const TASK_INFO_VERSION: usize = 1;
+
 #[derive(Debug)]
 pub struct TaskInfo {
     pub status: TaskStatus,
@@ -131,6 +147,70 @@ impl TaskInfo {
     }
 }
 
+pub const LATENCY_BUCKETS: usize = 16;
+
+/// Latency record of one syscall, bucket `i` counts calls that took
+/// less than `2^(i+1)` us, the last one everything longer
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct SyscallLatency {
+    pub count: usize,
+    pub total_us: usize,
+    pub buckets: [usize; LATENCY_BUCKETS],
+}
+
+impl SyscallLatency {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Scheduler state of one ready task, see `stride_trace`
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct StrideEntry {
+    pub pid: usize,
+    pub pass: usize,
+    pub stride: usize,
+    pub priority: isize,
+}
+
+/// One child reaped by `waitall`
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct WaitResult {
+    pub pid: usize,
+    pub exit_code: i32,
+}
+
+/// Resources used by a child reaped by `wait3`
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RUsage {
+    /// CPU time in us
+    pub cpu_time: usize,
+    /// Peak size of the address space in KiB, page tables included
+    pub max_rss: usize,
+}
+
+/// Number of blocked tasks by what they wait for, see `waitqueue_stats`
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct WaitQueueStats {
+    /// Sleeping on the timer queue
+    pub sleep: usize,
+    /// In `pause` until a signal arrives
+    pub pause: usize,
+}
+
+/// Registers x0-x31 and the pc saved by `checkpoint`
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Checkpoint {
+    pub x: [usize; 32],
+    pub sepc: usize,
+}
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct Stat {
@@ -220,10 +300,43 @@ pub fn exit(exit_code: i32) -> ! {
     sys_exit(exit_code);
 }
 
+/// Give up the CPU, return 1 if another task ran meanwhile and 0 if we were
+/// the only one with work to do (tasks spinning in waitpid do not count)
 pub fn yield_() -> isize {
     sys_yield()
 }
 
+/// Spin-loop hint: yield only if some other task is ready. Returns 1 if it
+/// yielded, 0 if not
+pub fn cpu_relax() -> isize {
+    sys_cpu_relax()
+}
+
+/// Keep running through `yield_` until `us` have passed since this task was
+/// switched in, 0 turns it off; returns the previous minimum
+pub fn set_yield_min_runtime(us: usize) -> isize {
+    sys_set_yield_min_runtime(us)
+}
+
+/// Address of the read-only page the kernel keeps the time in
+pub const VDSO: usize = usize::MAX - 3 * 4096 + 1;
+
+/// Time in us as of the last timer interrupt, read without a syscall
+pub fn vdso_time_us() -> usize {
+    unsafe { (VDSO as *const usize).read_volatile() }
+}
+
+/// The clock of `get_time`
+pub const CLOCK_REALTIME: usize = 0;
+pub const CLOCK_MONOTONIC: usize = 1;
+/// The clock of `vdso_time_us`, advancing once per timer tick
+pub const CLOCK_MONOTONIC_COARSE: usize = 6;
+
+/// Granularity of `clock_id`, written to `res`
+pub fn clock_getres(clock_id: usize, res: &mut TimeVal) -> isize {
+    sys_clock_getres(clock_id, res)
+}
+
 pub fn get_time() -> isize {
     let time = TimeVal::new();
     match sys_get_time(&time, 0) {
@@ -232,22 +345,133 @@ pub fn get_time() -> isize {
     }
 }
 
+/// `CLOCK_MONOTONIC` and `CLOCK_REALTIME`, in that order, taken at the same instant
+pub fn get_times(times: &mut [TimeVal; 2]) -> isize {
+    sys_get_times(times)
+}
+
 pub fn getpid() -> isize {
     sys_getpid()
 }
 
+pub fn getppid() -> isize {
+    sys_getppid()
+}
+
 pub fn fork() -> isize {
     sys_fork()
 }
 
+/// Fork a child that runs `entry` on the stack ending at `stack_top`, with
+/// nothing of the parent's frames under it. Returns the child pid, or the
+/// error of `sys_fork_sp` in the parent.
+pub fn fork_on_stack(stack_top: usize, entry: fn() -> !) -> isize {
+    let ret: isize;
+    unsafe {
+        // the child must not touch the old stack, so it jumps to entry
+        // straight from the ecall
+        core::arch::asm!(
+            "ecall",
+            "bnez a0, 1f",
+            "jalr t0",
+            "1:",
+            inlateout("x10") stack_top => ret,
+            in("x17") SYSCALL_FORK_SP,
+            in("x5") entry as usize,
+        );
+    }
+    ret
+}
+
+/// The syscall counters of task_info start over in the new image
 pub fn exec(path: &str, args: &[*const u8]) -> isize {
-    sys_exec(path, args)
+    sys_exec(path, args, 0)
+}
+
+/// `flags` bit of `exec_flags` keeping the syscall counters of the old image
+pub const EXEC_KEEP_SYSCALL_TIMES: usize = 1;
+
+/// `exec` taking `EXEC_*` flags, -EINVAL for an unknown bit
+pub fn exec_flags(path: &str, args: &[*const u8], flags: usize) -> isize {
+    sys_exec(path, args, flags)
 }
 
 pub fn set_priority(prio: isize) -> isize {
     sys_set_priority(prio)
 }
 
+pub fn set_tls(ptr: usize) -> isize {
+    sys_set_tls(ptr)
+}
+
+pub fn get_tls() -> usize {
+    sys_get_tls() as usize
+}
+
+/// Read the thread pointer register
+pub fn tp() -> usize {
+    let tp: usize;
+    unsafe {
+        core::arch::asm!("mv {}, tp", out(reg) tp);
+    }
+    tp
+}
+
+pub fn set_io_boost(enable: bool) -> isize {
+    sys_set_io_boost(enable)
+}
+
+pub fn setpriority(pid: usize, prio: isize) -> isize {
+    sys_setpriority(pid, prio)
+}
+
+pub const SCHED_STRIDE: usize = 0;
+pub const SCHED_RR: usize = 1;
+
+/// Switch the caller to `SCHED_STRIDE` or `SCHED_RR`. While both kinds are
+/// ready, round-robin tasks together get every other dispatch.
+pub fn sched_setscheduler(policy: usize) -> isize {
+    sys_sched_setscheduler(policy)
+}
+
+/// Allow only the syscalls in `allowed` from now on, any other one kills the
+/// task with exit code -31. Forked children inherit the filter, exec drops
+/// it; a second call can only narrow it further.
+pub fn restrict_syscalls(allowed: &[usize]) -> isize {
+    let mut bitmap = [0u8; (MAX_SYSCALL_NUM + 7) / 8];
+    for &id in allowed {
+        if id >= MAX_SYSCALL_NUM {
+            return -errno::EINVAL;
+        }
+        bitmap[id / 8] |= 1 << (id % 8);
+    }
+    sys_restrict_syscalls(&bitmap)
+}
+
+/// Schedule by stride alone with FIFO ties for the rest of the run, ignoring
+/// `SCHED_RR` and I/O boosts. It cannot be turned off again (-EPERM).
+/// Return the previous setting.
+pub fn sched_set_deterministic(enabled: bool) -> isize {
+    sys_sched_set_deterministic(enabled)
+}
+
+/// 1 if `pid` is below the caller in the process tree, 0 if not, -1 if there is no such task
+pub fn is_descendant(pid: usize) -> isize {
+    sys_is_descendant(pid)
+}
+
+/// Fill `entries` with the stride state of the ready tasks, returning how many
+/// tasks are ready (possibly more than fit in `entries`)
+pub fn stride_trace(entries: &mut [StrideEntry]) -> isize {
+    sys_stride_trace(entries)
+}
+
+/// Reap every zombie child that fits in `results` with one syscall, returning
+/// how many were reaped (0 if none is ready)
+pub fn waitall(results: &mut [WaitResult]) -> isize {
+    sys_waitall(results)
+}
+
 pub fn wait(exit_code: &mut i32) -> isize {
     loop {
         match sys_waitpid(-1, exit_code as *mut _) {
@@ -274,6 +498,152 @@ pub fn waitpid(pid: usize, exit_code: &mut i32) -> isize {
     }
 }
 
+/// Like `waitpid`, also reporting the CPU time of the child in us
+pub fn waitpid_cpu_time(pid: usize, exit_code: &mut i32, cpu_time: &mut usize) -> isize {
+    loop {
+        match sys_waitpid_cpu_time(pid as isize, exit_code as *mut _, cpu_time as *mut _) {
+            -2 => {
+                sys_yield();
+            }
+            n => {
+                return n;
+            }
+        }
+    }
+}
+
+/// Like `waitpid`, also reporting the resource usage of the child; either
+/// out argument may be left out
+pub fn wait3(pid: isize, exit_code: Option<&mut i32>, rusage: Option<&mut RUsage>) -> isize {
+    let exit_code = exit_code.map_or(core::ptr::null_mut(), |code| code as *mut i32);
+    let rusage = rusage.map_or(core::ptr::null_mut(), |usage| usage as *mut RUsage);
+    loop {
+        match sys_wait3(pid, exit_code, rusage) {
+            -2 => {
+                sys_yield();
+            }
+            n => {
+                return n;
+            }
+        }
+    }
+}
+
+/// Fill `stats` with the number of tasks blocked on each kind of wait,
+/// return how many are blocked in all
+pub fn waitqueue_stats(stats: &mut WaitQueueStats) -> isize {
+    sys_waitqueue_stats(stats)
+}
+
+/// Never let this task or its descendants raise their priority above the
+/// current one again; returns the ceiling
+pub fn drop_priority_ceiling() -> isize {
+    sys_drop_priority_ceiling()
+}
+
+/// Have children freed as soon as they exit, so they never linger as zombies;
+/// `wait` then has nothing to collect. Returns the previous setting
+pub fn set_auto_reap(enabled: bool) -> isize {
+    sys_set_auto_reap(enabled)
+}
+
+/// Approximate number of ticks between two runs of task `pid` with the
+/// tasks ready now, -ESRCH if there is none, -EINVAL if it is not stride scheduled
+pub fn sched_getinterval(pid: usize) -> isize {
+    sys_sched_getinterval(pid)
+}
+
+/// Panic the kernel on purpose. Only a kernel built with the `panic-test`
+/// feature does so, any other returns -1
+pub fn kernel_assert() -> isize {
+    sys_kernel_assert()
+}
+
+/// Number of exited tasks nobody has reaped yet, optionally also how many
+/// times the kernel has warned about them piling up
+pub fn zombie_count(warnings: Option<&mut usize>) -> isize {
+    sys_zombie_count(warnings.map_or(core::ptr::null_mut(), |count| count as *mut usize))
+}
+
+/// Hash over the contents of every page of our address space except the stack
+/// we run on; a forked child matches its parent until either writes. The hash
+/// lands on that stack, so writing it leaves the next hash unchanged.
+pub fn addrspace_checksum() -> u64 {
+    let mut hash = 0;
+    assert_eq!(sys_addrspace_checksum(&mut hash), 0);
+    hash
+}
+
+/// Number of times the kernel has switched to this task, the current run included
+pub fn sched_epoch() -> isize {
+    sys_sched_epoch()
+}
+
+/// Number of times the kernel found every ready task spinning in waitpid
+pub fn waitpid_livelocks() -> isize {
+    sys_waitpid_livelocks()
+}
+
+pub fn enable_syscall_latency(enable: bool) -> isize {
+    sys_enable_syscall_latency(enable)
+}
+
+pub fn syscall_latency(num: usize, latency: &mut SyscallLatency) -> isize {
+    sys_syscall_latency(num, latency)
+}
+
+pub fn kill(pid: usize, signal: usize) -> isize {
+    sys_kill(pid, signal)
+}
+
+/// Block until a signal arrives
+pub fn pause() -> isize {
+    sys_pause()
+}
+
+pub const SIGKILL: usize = 9;
+pub const SIGUSR1: usize = 10;
+pub const SIGUSR2: usize = 12;
+pub const SIGALRM: usize = 14;
+const MAX_SIG: usize = 32;
+
+static mut SIGNAL_HANDLERS: [Option<fn(usize)>; MAX_SIG] = [None; MAX_SIG];
+
+/// Where the kernel enters a caught signal: run its handler, then resume
+/// the interrupted code
+extern "C" fn signal_trampoline(signum: usize) -> ! {
+    if let Some(handler) = unsafe { SIGNAL_HANDLERS[signum] } {
+        handler(signum);
+    }
+    sys_sigreturn();
+    unreachable!("sigreturn returned");
+}
+
+/// Raise SIGALRM after `value_ms`, then every `interval_ms` unless it is 0.
+/// A `value_ms` of 0 disarms the timer. Return the ms left on the previous timer.
+pub fn setitimer(interval_ms: usize, value_ms: usize) -> isize {
+    sys_setitimer(interval_ms, value_ms)
+}
+
+/// Run `handler` when `signum` is delivered, `None` restores the default action
+pub fn sigaction(signum: usize, handler: Option<fn(usize)>) -> isize {
+    if signum >= MAX_SIG {
+        return -errno::EINVAL;
+    }
+    let entry = match handler {
+        Some(_) => signal_trampoline as usize,
+        None => 0,
+    };
+    // in place before the kernel can deliver the signal
+    let previous = unsafe { core::mem::replace(&mut SIGNAL_HANDLERS[signum], handler) };
+    let ret = sys_sigaction(signum, entry);
+    if ret < 0 {
+        unsafe { SIGNAL_HANDLERS[signum] = previous };
+        return ret;
+    }
+    0
+}
+
 pub fn sleep_blocking(sleep_ms: usize) {
     sys_sleep(sleep_ms);
 }
@@ -284,14 +654,135 @@ pub fn sleep(period_ms: usize) {
         sys_yield();
     }
 }
+/// `prot` bit asking mmap for 2M megapages, `start` and `len` must be 2M aligned
+pub const MMAP_HUGE: usize = 1 << 3;
+/// `prot` bit asking mmap to share the area with forked children instead of copying it
+pub const MMAP_SHARED: usize = 1 << 4;
+/// `prot` bit asking for a stack: only the last page is backed, faults below
+/// it extend the area down to the guard page at `start`
+pub const MMAP_GROWSDOWN: usize = 1 << 6;
+/// `prot` bit asking mmap to return `start` on success instead of 0. User
+/// addresses are far below `isize::MAX`, so a negative return is still an error
+pub const MMAP_RETURN_BASE: usize = 1 << 7;
+
+/// Map `len` bytes at `start`, 0 on success and a negative errno on failure
 pub fn mmap(start: usize, len: usize, prot: usize) -> isize {
     sys_mmap(start, len, prot)
 }
 
+pub fn mmap_app(start: usize, name: &str) -> isize {
+    sys_mmap_app(start, name)
+}
+
+/// Exec the ELF image held in `[addr, addr + len)` of our own memory
+pub fn exec_mem(addr: usize, len: usize) -> isize {
+    sys_exec_mem(addr, len)
+}
+
+/// Physical page number `va` is mapped to, for debugging
+pub fn translate(va: usize) -> isize {
+    sys_translate(va)
+}
+
+/// Size of the page mapping `va`, for debugging
+pub fn page_size(va: usize) -> isize {
+    sys_page_size(va)
+}
+
+/// Residency of each page of `[start, start + len)`, one byte per page in `vec`
+pub fn mincore(start: usize, len: usize, vec: &mut [u8]) -> isize {
+    sys_mincore(start, len, vec)
+}
+
+pub const LIMIT_MAX_SYSCALL_NUM: usize = 0;
+pub const LIMIT_PAGE_SIZE: usize = 1;
+pub const LIMIT_BIG_STRIDE: usize = 2;
+pub const LIMIT_USER_STACK_SIZE: usize = 3;
+pub const LIMIT_MAX_PROC_DEPTH: usize = 4;
+pub const LIMIT_REALTIME_OFFSET_US: usize = 5;
+pub const LIMIT_ZOMBIE_WARN_THRESHOLD: usize = 6;
+
+/// Value of the kernel constant selected by `which`, one of the `LIMIT_*` above
+pub fn get_limit(which: usize) -> isize {
+    sys_get_limit(which)
+}
+
 pub fn munmap(start: usize, len: usize) -> isize {
     sys_munmap(start, len)
 }
 
+/// Reserve `[start, start + len)` without backing it, mmaps over it then fail
+pub fn reserve(start: usize, len: usize) -> isize {
+    sys_reserve(start, len)
+}
+
+/// Back part of an earlier reservation so it can be accessed with `prot`
+pub fn commit(start: usize, len: usize, prot: usize) -> isize {
+    sys_commit(start, len, prot)
+}
+
+/// Move the pages of `[start, start + len)` to swap space, return how many moved.
+/// They are faulted back in on the next access.
+pub fn swapout(start: usize, len: usize) -> isize {
+    sys_swapout(start, len)
+}
+
+/// Keep the pages of `[start, start + len)` resident, `swapout` skips them
+/// until `munlock`. -ENOMEM unless the whole range is mapped.
+pub fn mlock(start: usize, len: usize) -> isize {
+    sys_mlock(start, len)
+}
+
+pub fn munlock(start: usize, len: usize) -> isize {
+    sys_munlock(start, len)
+}
+
+/// Make every write before the call visible to every task running after it
+pub fn membarrier() -> isize {
+    sys_membarrier()
+}
+
+/// Save every register to `buf`, return 0 now and 1 when `restore` jumps back.
+/// Like setjmp, only registers are saved, not the stack they point into.
+pub fn checkpoint(buf: &mut Checkpoint) -> isize {
+    sys_checkpoint(buf)
+}
+
+/// Jump back to the `checkpoint` call that filled `buf`, only returns on failure
+pub fn restore(buf: &Checkpoint) -> isize {
+    sys_restore(buf)
+}
+
+/// Atomically store `new` at `addr` if it holds `expected`, return the value it held
+pub fn cas(addr: *mut usize, expected: usize, new: usize) -> isize {
+    sys_cas(addr, expected, new)
+}
+
+/// Rename the current task, `name` is NUL terminated
+pub fn set_task_name(name: &str) -> isize {
+    sys_set_task_name(name)
+}
+
+/// Copy the NUL terminated name of the current task to `buf`, return its length with the NUL
+pub fn get_task_name(buf: &mut [u8]) -> isize {
+    sys_get_task_name(buf)
+}
+
+/// Change the working directory, `path` is NUL terminated and may be relative
+pub fn chdir(path: &str) -> isize {
+    sys_chdir(path)
+}
+
+/// Copy the NUL terminated working directory to `buf`, return its length with the NUL
+pub fn getcwd(buf: &mut [u8]) -> isize {
+    sys_getcwd(buf)
+}
+
+/// Fill `buf` with NUL terminated app names, return how many fit
+pub fn list_apps(buf: &mut [u8]) -> isize {
+    sys_list_apps(buf)
+}
+
 pub fn spawn(path: &str) -> isize {
     sys_spawn(path)
 }
@@ -307,6 +798,61 @@ pub fn task_info(info: &TaskInfo) -> isize {
     sys_task_info(info)
 }
 
+/// `task_info` that fails with -EINVAL instead of writing `info` if the kernel's
+/// `TaskInfo` layout is not `TASK_INFO_VERSION`
+pub fn task_info_v(info: &TaskInfo) -> isize {
+    sys_task_info_v(info, TASK_INFO_VERSION)
+}
+
+/// Like `task_info`, for the task `pid`, a child that exited but was not waited
+/// for included
+pub fn task_info_of(pid: usize, info: &mut TaskInfo) -> isize {
+    sys_task_info_of(pid, info, TASK_INFO_VERSION)
+}
+
+pub fn set_cpu_budget(ticks: usize) -> isize {
+    sys_set_cpu_budget(ticks)
+}
+
+pub fn name_region(start: usize, name: &str) -> isize {
+    sys_name_region(start, name)
+}
+
+pub fn dump_regions() -> isize {
+    console::flush();
+    sys_dump_regions()
+}
+
+/// Flush both the user console buffer and the kernel line buffer
+pub fn flush_stdout() -> isize {
+    console::flush();
+    sys_flush()
+}
+
+pub fn reboot() -> isize {
+    console::flush();
+    sys_reboot()
+}
+
+/// Kernel log levels for `set_log_level`, each also emitting the ones before it
+pub const LOG_OFF: usize = 0;
+pub const LOG_ERROR: usize = 1;
+pub const LOG_WARN: usize = 2;
+pub const LOG_INFO: usize = 3;
+pub const LOG_DEBUG: usize = 4;
+pub const LOG_TRACE: usize = 5;
+
+/// Set the kernel log level, return the previous one
+pub fn set_log_level(level: usize) -> isize {
+    sys_set_log_level(level)
+}
+
+/// Start shutting down: fork and spawn fail from now on and the machine
+/// powers off when the caller exits
+pub fn shutdown() -> isize {
+    sys_shutdown()
+}
+
 pub fn thread_create(entry: usize, arg: usize) -> isize {
     sys_thread_create(entry, arg)
 }
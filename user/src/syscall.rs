@@ -1,7 +1,11 @@
 use crate::TaskInfo;
 
-use super::{Stat, TimeVal};
+use super::{
+    Checkpoint, RUsage, Stat, StrideEntry, SyscallLatency, TimeVal, WaitQueueStats, WaitResult,
+};
 
+pub const SYSCALL_GETCWD: usize = 17;
+pub const SYSCALL_CHDIR: usize = 49;
 pub const SYSCALL_OPENAT: usize = 56;
 pub const SYSCALL_CLOSE: usize = 57;
 pub const SYSCALL_READ: usize = 63;
@@ -27,6 +31,66 @@ pub const SYSCALL_MAIL_WRITE: usize = 402;
 pub const SYSCALL_DUP: usize = 24;
 pub const SYSCALL_PIPE: usize = 59;
 pub const SYSCALL_TASK_INFO: usize = 410;
+pub const SYSCALL_SET_CPU_BUDGET: usize = 411;
+pub const SYSCALL_NAME_REGION: usize = 412;
+pub const SYSCALL_DUMP_REGIONS: usize = 413;
+pub const SYSCALL_SETPRIORITY: usize = 414;
+pub const SYSCALL_FLUSH: usize = 415;
+pub const SYSCALL_REBOOT: usize = 142;
+pub const SYSCALL_MMAP_APP: usize = 416;
+pub const SYSCALL_KILL: usize = 129;
+pub const SYSCALL_TRANSLATE: usize = 417;
+pub const SYSCALL_SET_IO_BOOST: usize = 418;
+pub const SYSCALL_SET_TLS: usize = 419;
+pub const SYSCALL_GET_TLS: usize = 420;
+pub const SYSCALL_WAITPID_LIVELOCKS: usize = 421;
+pub const SYSCALL_ENABLE_SYSCALL_LATENCY: usize = 422;
+pub const SYSCALL_SYSCALL_LATENCY: usize = 423;
+pub const SYSCALL_MLOCK: usize = 228;
+pub const SYSCALL_MUNLOCK: usize = 229;
+pub const SYSCALL_MINCORE: usize = 232;
+pub const SYSCALL_GETPPID: usize = 173;
+pub const SYSCALL_GET_LIMIT: usize = 424;
+pub const SYSCALL_STRIDE_TRACE: usize = 425;
+pub const SYSCALL_PAGE_SIZE: usize = 426;
+pub const SYSCALL_PAUSE: usize = 427;
+pub const SYSCALL_SIGACTION: usize = 134;
+pub const SYSCALL_SIGRETURN: usize = 139;
+pub const SYSCALL_LIST_APPS: usize = 428;
+pub const SYSCALL_EXEC_MEM: usize = 429;
+pub const SYSCALL_WAITALL: usize = 430;
+pub const SYSCALL_SETITIMER: usize = 431;
+pub const SYSCALL_CAS: usize = 432;
+pub const SYSCALL_SET_TASK_NAME: usize = 433;
+pub const SYSCALL_GET_TASK_NAME: usize = 434;
+pub const SYSCALL_SHUTDOWN: usize = 435;
+pub const SYSCALL_SET_LOG_LEVEL: usize = 436;
+pub const SYSCALL_TASK_INFO_OF: usize = 437;
+pub const SYSCALL_RESERVE: usize = 438;
+pub const SYSCALL_COMMIT: usize = 439;
+pub const SYSCALL_CHECKPOINT: usize = 440;
+pub const SYSCALL_RESTORE: usize = 441;
+pub const SYSCALL_IS_DESCENDANT: usize = 442;
+pub const SYSCALL_SWAPOUT: usize = 443;
+pub const SYSCALL_GET_TIMES: usize = 444;
+pub const SYSCALL_CPU_RELAX: usize = 445;
+pub const SYSCALL_FORK_SP: usize = 446;
+pub const SYSCALL_SCHED_SET_DETERMINISTIC: usize = 447;
+pub const SYSCALL_WAIT3: usize = 448;
+pub const SYSCALL_RESTRICT_SYSCALLS: usize = 449;
+pub const SYSCALL_WAITQUEUE_STATS: usize = 450;
+pub const SYSCALL_DROP_PRIORITY_CEILING: usize = 451;
+pub const SYSCALL_ZOMBIE_COUNT: usize = 452;
+pub const SYSCALL_SCHED_EPOCH: usize = 453;
+pub const SYSCALL_SET_YIELD_MIN_RUNTIME: usize = 454;
+pub const SYSCALL_ADDRSPACE_CHECKSUM: usize = 455;
+pub const SYSCALL_SET_AUTO_REAP: usize = 456;
+pub const SYSCALL_KERNEL_ASSERT: usize = 457;
+pub const SYSCALL_SCHED_GETINTERVAL: usize = 458;
+pub const SYSCALL_TASK_INFO_V: usize = 459;
+pub const SYSCALL_CLOCK_GETRES: usize = 114;
+pub const SYSCALL_SCHED_SETSCHEDULER: usize = 119;
+pub const SYSCALL_MEMBARRIER: usize = 283;
 pub const SYSCALL_THREAD_CREATE: usize = 460;
 pub const SYSCALL_WAITTID: usize = 462;
 pub const SYSCALL_MUTEX_CREATE: usize = 463;
@@ -158,18 +222,74 @@ pub fn sys_get_time(time: &TimeVal, tz: usize) -> isize {
     syscall(SYSCALL_GETTIMEOFDAY, [time as *const _ as usize, tz, 0])
 }
 
+pub fn sys_clock_getres(clock_id: usize, res: &mut TimeVal) -> isize {
+    syscall(SYSCALL_CLOCK_GETRES, [clock_id, res as *mut _ as usize, 0])
+}
+
+pub fn sys_task_info_of(pid: usize, info: &mut TaskInfo, version: usize) -> isize {
+    syscall(SYSCALL_TASK_INFO_OF, [pid, info as *mut _ as usize, version])
+}
+
+pub fn sys_checkpoint(buf: &mut Checkpoint) -> isize {
+    syscall(
+        SYSCALL_CHECKPOINT,
+        [buf as *mut _ as usize, core::mem::size_of::<Checkpoint>(), 0],
+    )
+}
+
+pub fn sys_restore(buf: &Checkpoint) -> isize {
+    syscall(
+        SYSCALL_RESTORE,
+        [buf as *const _ as usize, core::mem::size_of::<Checkpoint>(), 0],
+    )
+}
+
+pub fn sys_is_descendant(pid: usize) -> isize {
+    syscall(SYSCALL_IS_DESCENDANT, [pid, 0, 0])
+}
+
+pub fn sys_sched_setscheduler(policy: usize) -> isize {
+    syscall(SYSCALL_SCHED_SETSCHEDULER, [policy, 0, 0])
+}
+
+pub fn sys_get_times(times: &mut [TimeVal; 2]) -> isize {
+    syscall(SYSCALL_GET_TIMES, [times as *mut _ as usize, 0, 0])
+}
+
+pub fn sys_cpu_relax() -> isize {
+    syscall(SYSCALL_CPU_RELAX, [0, 0, 0])
+}
+
+pub fn sys_sched_set_deterministic(enabled: bool) -> isize {
+    syscall(SYSCALL_SCHED_SET_DETERMINISTIC, [enabled as usize, 0, 0])
+}
+
+pub fn sys_membarrier() -> isize {
+    syscall(SYSCALL_MEMBARRIER, [0, 0, 0])
+}
+
 pub fn sys_getpid() -> isize {
     syscall(SYSCALL_GETPID, [0, 0, 0])
 }
 
+pub fn sys_getppid() -> isize {
+    syscall(SYSCALL_GETPPID, [0, 0, 0])
+}
+
 pub fn sys_fork() -> isize {
     syscall(SYSCALL_FORK, [0, 0, 0])
 }
 
-pub fn sys_exec(path: &str, args: &[*const u8]) -> isize {
+/// The child resumes here with `sp` switched, it must not return from this
+/// function; see `fork_on_stack`
+pub fn sys_fork_sp(new_sp: usize) -> isize {
+    syscall(SYSCALL_FORK_SP, [new_sp, 0, 0])
+}
+
+pub fn sys_exec(path: &str, args: &[*const u8], flags: usize) -> isize {
     syscall(
         SYSCALL_EXEC,
-        [path.as_ptr() as usize, args.as_ptr() as usize, 0],
+        [path.as_ptr() as usize, args.as_ptr() as usize, flags],
     )
 }
 
@@ -177,10 +297,74 @@ pub fn sys_waitpid(pid: isize, xstatus: *mut i32) -> isize {
     syscall(SYSCALL_WAITPID, [pid as usize, xstatus as usize, 0])
 }
 
+pub fn sys_waitpid_cpu_time(pid: isize, xstatus: *mut i32, cpu_time: *mut usize) -> isize {
+    syscall(
+        SYSCALL_WAITPID,
+        [pid as usize, xstatus as usize, cpu_time as usize],
+    )
+}
+
+pub fn sys_wait3(pid: isize, xstatus: *mut i32, rusage: *mut RUsage) -> isize {
+    syscall(
+        SYSCALL_WAIT3,
+        [pid as usize, xstatus as usize, rusage as usize],
+    )
+}
+
+pub fn sys_restrict_syscalls(bitmap: &[u8]) -> isize {
+    syscall(
+        SYSCALL_RESTRICT_SYSCALLS,
+        [bitmap.as_ptr() as usize, bitmap.len(), 0],
+    )
+}
+
+pub fn sys_waitqueue_stats(stats: &mut WaitQueueStats) -> isize {
+    syscall(
+        SYSCALL_WAITQUEUE_STATS,
+        [stats as *mut WaitQueueStats as usize, 0, 0],
+    )
+}
+
+pub fn sys_drop_priority_ceiling() -> isize {
+    syscall(SYSCALL_DROP_PRIORITY_CEILING, [0, 0, 0])
+}
+
+pub fn sys_zombie_count(warnings: *mut usize) -> isize {
+    syscall(SYSCALL_ZOMBIE_COUNT, [warnings as usize, 0, 0])
+}
+
+pub fn sys_sched_epoch() -> isize {
+    syscall(SYSCALL_SCHED_EPOCH, [0, 0, 0])
+}
+
+pub fn sys_set_yield_min_runtime(us: usize) -> isize {
+    syscall(SYSCALL_SET_YIELD_MIN_RUNTIME, [us, 0, 0])
+}
+
+pub fn sys_addrspace_checksum(hash: &mut u64) -> isize {
+    syscall(SYSCALL_ADDRSPACE_CHECKSUM, [hash as *mut _ as usize, 0, 0])
+}
+
+pub fn sys_set_auto_reap(enabled: bool) -> isize {
+    syscall(SYSCALL_SET_AUTO_REAP, [enabled as usize, 0, 0])
+}
+
+pub fn sys_sched_getinterval(pid: usize) -> isize {
+    syscall(SYSCALL_SCHED_GETINTERVAL, [pid, 0, 0])
+}
+
+pub fn sys_kernel_assert() -> isize {
+    syscall(SYSCALL_KERNEL_ASSERT, [0, 0, 0])
+}
+
 pub fn sys_set_priority(prio: isize) -> isize {
     syscall(SYSCALL_SET_PRIORITY, [prio as usize, 0, 0])
 }
 
+pub fn sys_setpriority(pid: usize, prio: isize) -> isize {
+    syscall(SYSCALL_SETPRIORITY, [pid, prio as usize, 0])
+}
+
 pub fn sys_mmap(start: usize, len: usize, prot: usize) -> isize {
     syscall(SYSCALL_MMAP, [start, len, prot])
 }
@@ -189,6 +373,26 @@ pub fn sys_munmap(start: usize, len: usize) -> isize {
     syscall(SYSCALL_MUNMAP, [start, len, 0])
 }
 
+pub fn sys_reserve(start: usize, len: usize) -> isize {
+    syscall(SYSCALL_RESERVE, [start, len, 0])
+}
+
+pub fn sys_commit(start: usize, len: usize, prot: usize) -> isize {
+    syscall(SYSCALL_COMMIT, [start, len, prot])
+}
+
+pub fn sys_swapout(start: usize, len: usize) -> isize {
+    syscall(SYSCALL_SWAPOUT, [start, len, 0])
+}
+
+pub fn sys_mlock(start: usize, len: usize) -> isize {
+    syscall(SYSCALL_MLOCK, [start, len, 0])
+}
+
+pub fn sys_munlock(start: usize, len: usize) -> isize {
+    syscall(SYSCALL_MUNLOCK, [start, len, 0])
+}
+
 pub fn sys_spawn(path: &str) -> isize {
     syscall(SYSCALL_SPAWN, [path.as_ptr() as usize, 0, 0])
 }
@@ -205,6 +409,147 @@ pub fn sys_task_info(info: &TaskInfo) -> isize {
     syscall(SYSCALL_TASK_INFO, [info as *const _ as usize, 0, 0])
 }
 
+pub fn sys_task_info_v(info: &TaskInfo, version: usize) -> isize {
+    syscall(SYSCALL_TASK_INFO_V, [info as *const _ as usize, version, 0])
+}
+
+pub fn sys_set_cpu_budget(ticks: usize) -> isize {
+    syscall(SYSCALL_SET_CPU_BUDGET, [ticks, 0, 0])
+}
+
+pub fn sys_name_region(start: usize, name: &str) -> isize {
+    syscall(SYSCALL_NAME_REGION, [start, name.as_ptr() as usize, 0])
+}
+
+pub fn sys_dump_regions() -> isize {
+    syscall(SYSCALL_DUMP_REGIONS, [0, 0, 0])
+}
+
+pub fn sys_flush() -> isize {
+    syscall(SYSCALL_FLUSH, [0, 0, 0])
+}
+
+pub fn sys_reboot() -> isize {
+    syscall(SYSCALL_REBOOT, [0, 0, 0])
+}
+
+pub fn sys_kill(pid: usize, signal: usize) -> isize {
+    syscall(SYSCALL_KILL, [pid, signal, 0])
+}
+
+pub fn sys_translate(va: usize) -> isize {
+    syscall(SYSCALL_TRANSLATE, [va, 0, 0])
+}
+
+pub fn sys_page_size(va: usize) -> isize {
+    syscall(SYSCALL_PAGE_SIZE, [va, 0, 0])
+}
+
+pub fn sys_pause() -> isize {
+    syscall(SYSCALL_PAUSE, [0, 0, 0])
+}
+
+pub fn sys_sigaction(signum: usize, handler: usize) -> isize {
+    syscall(SYSCALL_SIGACTION, [signum, handler, 0])
+}
+
+pub fn sys_sigreturn() -> isize {
+    syscall(SYSCALL_SIGRETURN, [0, 0, 0])
+}
+
+pub fn sys_cas(addr: *mut usize, expected: usize, new: usize) -> isize {
+    syscall(SYSCALL_CAS, [addr as usize, expected, new])
+}
+
+pub fn sys_set_task_name(name: &str) -> isize {
+    syscall(SYSCALL_SET_TASK_NAME, [name.as_ptr() as usize, 0, 0])
+}
+
+pub fn sys_get_task_name(buf: &mut [u8]) -> isize {
+    syscall(SYSCALL_GET_TASK_NAME, [buf.as_mut_ptr() as usize, buf.len(), 0])
+}
+
+pub fn sys_shutdown() -> isize {
+    syscall(SYSCALL_SHUTDOWN, [0, 0, 0])
+}
+
+pub fn sys_set_log_level(level: usize) -> isize {
+    syscall(SYSCALL_SET_LOG_LEVEL, [level, 0, 0])
+}
+
+pub fn sys_chdir(path: &str) -> isize {
+    syscall(SYSCALL_CHDIR, [path.as_ptr() as usize, 0, 0])
+}
+
+pub fn sys_getcwd(buf: &mut [u8]) -> isize {
+    syscall(SYSCALL_GETCWD, [buf.as_mut_ptr() as usize, buf.len(), 0])
+}
+
+pub fn sys_list_apps(buf: &mut [u8]) -> isize {
+    syscall(SYSCALL_LIST_APPS, [buf.as_mut_ptr() as usize, buf.len(), 0])
+}
+
+pub fn sys_set_io_boost(enable: bool) -> isize {
+    syscall(SYSCALL_SET_IO_BOOST, [enable as usize, 0, 0])
+}
+
+pub fn sys_set_tls(ptr: usize) -> isize {
+    syscall(SYSCALL_SET_TLS, [ptr, 0, 0])
+}
+
+pub fn sys_get_tls() -> isize {
+    syscall(SYSCALL_GET_TLS, [0, 0, 0])
+}
+
+pub fn sys_waitpid_livelocks() -> isize {
+    syscall(SYSCALL_WAITPID_LIVELOCKS, [0, 0, 0])
+}
+
+pub fn sys_enable_syscall_latency(enable: bool) -> isize {
+    syscall(SYSCALL_ENABLE_SYSCALL_LATENCY, [enable as usize, 0, 0])
+}
+
+pub fn sys_syscall_latency(num: usize, latency: &mut SyscallLatency) -> isize {
+    syscall(
+        SYSCALL_SYSCALL_LATENCY,
+        [num, latency as *mut _ as usize, 0],
+    )
+}
+
+pub fn sys_mincore(start: usize, len: usize, vec: &mut [u8]) -> isize {
+    syscall(SYSCALL_MINCORE, [start, len, vec.as_mut_ptr() as usize])
+}
+
+pub fn sys_get_limit(which: usize) -> isize {
+    syscall(SYSCALL_GET_LIMIT, [which, 0, 0])
+}
+
+pub fn sys_stride_trace(entries: &mut [StrideEntry]) -> isize {
+    syscall(
+        SYSCALL_STRIDE_TRACE,
+        [entries.as_mut_ptr() as usize, entries.len(), 0],
+    )
+}
+
+pub fn sys_waitall(results: &mut [WaitResult]) -> isize {
+    syscall(
+        SYSCALL_WAITALL,
+        [results.as_mut_ptr() as usize, results.len(), 0],
+    )
+}
+
+pub fn sys_setitimer(interval_ms: usize, value_ms: usize) -> isize {
+    syscall(SYSCALL_SETITIMER, [interval_ms, value_ms, 0])
+}
+
+pub fn sys_mmap_app(start: usize, name: &str) -> isize {
+    syscall(SYSCALL_MMAP_APP, [start, name.as_ptr() as usize, 0])
+}
+
+pub fn sys_exec_mem(addr: usize, len: usize) -> isize {
+    syscall(SYSCALL_EXEC_MEM, [addr, len, 0])
+}
+
 pub fn sys_thread_create(entry: usize, arg: usize) -> isize {
     syscall(SYSCALL_THREAD_CREATE, [entry, arg, 0])
 }